@@ -0,0 +1,334 @@
+use std::{env, io, path::PathBuf, time::Duration};
+
+use crate::image::Tolerance;
+
+pub const USAGE: &str = "\
+usage: boot <KERNEL_BINARY> [OPTIONS] [-- QEMU_ARGS...]
+
+options:
+    --no-run              only create the disk images, do not start QEMU
+    --uefi                boot the UEFI GPT image instead of the BIOS image
+    --fat-dir             boot the UEFI directory through a `fat:` drive (implies --uefi)
+    --ovmf <PATH>         OVMF firmware for UEFI boot (defaults to $OVMF_PATH)
+    --headless            run without a display window (-display none)
+    --serial <TARGET>     serial port destination: `stdio`, `none` or a file path
+    --memory <SIZE>       guest memory size, e.g. `512M` (QEMU's -m)
+    --smp <N>             number of virtual CPUs
+    --qemu-arg <ARG>      pass an extra argument to QEMU (repeatable)
+    --timeout <SECS>      kill QEMU if it is still running after SECS seconds
+    --gdb                 start a GDB server and wait for a debugger before booting
+    --screenshot <PATH>   save the screen as a PPM image after --screenshot-delay, then quit
+    --screenshot-delay <SECS>
+                          seconds to wait before taking --screenshot (default 5)
+    --golden-dir <DIR>    golden images for `[screenshot <name>]` requests of test kernels
+                          (default: tests/golden of the kernel crate)
+    --bless               overwrite golden images with the new screenshots
+    --tolerance <DELTA>   per-channel difference ignored when comparing with golden images
+    --max-diff-pixels <N> number of differing pixels allowed when comparing with golden images
+    --test / --no-test    force test mode on or off (detected from the binary path by default)
+    -h, --help            print this message";
+
+/// Default timeout for test kernels, so that a hung test does not block `cargo test` forever.
+const TEST_TIMEOUT: Duration = Duration::from_secs(300);
+const SCREENSHOT_DELAY: Duration = Duration::from_secs(5);
+
+/// Where the guest's first serial port is connected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Serial {
+    Stdio,
+    File(PathBuf),
+    None,
+}
+
+impl Serial {
+    fn parse(s: &str) -> Self {
+        match s {
+            "stdio" => Serial::Stdio,
+            "none" => Serial::None,
+            path => Serial::File(PathBuf::from(path)),
+        }
+    }
+
+    /// The value of QEMU's `-serial` option.
+    pub fn qemu_arg(&self) -> String {
+        match self {
+            Serial::Stdio => "stdio".into(),
+            Serial::File(path) => format!("file:{}", path.display()),
+            Serial::None => "none".into(),
+        }
+    }
+}
+
+/// Command line of the boot runner.
+///
+/// Options left as `None` fall back to a default that depends on whether the
+/// kernel is a test kernel; see the accessor methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub kernel_binary: PathBuf,
+    pub no_run: bool,
+    pub uefi: bool,
+    pub fat_dir: bool,
+    pub ovmf: Option<PathBuf>,
+    pub headless: Option<bool>,
+    pub serial: Option<Serial>,
+    pub memory: Option<String>,
+    pub smp: Option<usize>,
+    pub qemu_args: Vec<String>,
+    pub timeout: Option<Duration>,
+    pub gdb: bool,
+    pub screenshot: Option<PathBuf>,
+    pub screenshot_delay: Option<Duration>,
+    pub golden_dir: Option<PathBuf>,
+    pub bless: bool,
+    pub tolerance: Tolerance,
+    pub test: Option<bool>,
+    pub help: bool,
+}
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut args = args.into_iter();
+        let mut kernel_binary = None;
+        let mut parsed = Args {
+            kernel_binary: PathBuf::new(),
+            no_run: false,
+            uefi: false,
+            fat_dir: false,
+            ovmf: None,
+            headless: None,
+            serial: None,
+            memory: None,
+            smp: None,
+            qemu_args: Vec::new(),
+            timeout: None,
+            gdb: false,
+            screenshot: None,
+            screenshot_delay: None,
+            golden_dir: None,
+            bless: false,
+            tolerance: Tolerance::default(),
+            test: None,
+            help: false,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("missing value for `{}`", name))
+            };
+            match arg.as_str() {
+                "--no-run" => parsed.no_run = true,
+                "--uefi" => parsed.uefi = true,
+                "--fat-dir" => parsed.fat_dir = true,
+                "--ovmf" => parsed.ovmf = Some(PathBuf::from(value("--ovmf")?)),
+                "--headless" => parsed.headless = Some(true),
+                "--serial" => parsed.serial = Some(Serial::parse(&value("--serial")?)),
+                "--memory" | "-m" => parsed.memory = Some(value("--memory")?),
+                "--smp" => {
+                    let smp = value("--smp")?;
+                    parsed.smp = Some(
+                        smp.parse()
+                            .map_err(|_| format!("invalid CPU count `{}`", smp))?,
+                    );
+                }
+                "--qemu-arg" => parsed.qemu_args.push(value("--qemu-arg")?),
+                "--timeout" => parsed.timeout = Some(seconds(&value("--timeout")?)?),
+                "--gdb" => parsed.gdb = true,
+                "--screenshot" => parsed.screenshot = Some(PathBuf::from(value("--screenshot")?)),
+                "--screenshot-delay" => {
+                    parsed.screenshot_delay = Some(seconds(&value("--screenshot-delay")?)?)
+                }
+                "--golden-dir" => parsed.golden_dir = Some(PathBuf::from(value("--golden-dir")?)),
+                "--bless" => parsed.bless = true,
+                "--tolerance" => {
+                    let delta = value("--tolerance")?;
+                    parsed.tolerance.channel_delta = delta
+                        .parse()
+                        .map_err(|_| format!("invalid channel tolerance `{}`", delta))?;
+                }
+                "--max-diff-pixels" => {
+                    let pixels = value("--max-diff-pixels")?;
+                    parsed.tolerance.differing_pixels = pixels
+                        .parse()
+                        .map_err(|_| format!("invalid pixel count `{}`", pixels))?;
+                }
+                "--test" => parsed.test = Some(true),
+                "--no-test" => parsed.test = Some(false),
+                "-h" | "--help" => parsed.help = true,
+                "--" => parsed.qemu_args.extend(args.by_ref()),
+                other if other.starts_with('-') => {
+                    return Err(format!("unexpected argument `{}`", other))
+                }
+                path if kernel_binary.is_none() => kernel_binary = Some(PathBuf::from(path)),
+                other => return Err(format!("unexpected argument `{}`", other)),
+            }
+        }
+
+        match kernel_binary {
+            Some(path) => parsed.kernel_binary = path,
+            None if parsed.help => {}
+            None => return Err("missing kernel binary path".into()),
+        }
+        Ok(parsed)
+    }
+
+    /// Whether the kernel is run as a test, which makes QEMU exit through
+    /// isa-debug-exit and changes the defaults below.
+    ///
+    /// Unless forced with `--test`/`--no-test`, test kernels are detected by
+    /// their location: `cargo test` places test executables in
+    /// `target/<target>/<profile>/deps`.
+    pub fn is_test(&self) -> bool {
+        self.test.unwrap_or_else(|| {
+            self.kernel_binary
+                .parent()
+                .is_some_and(|dir| dir.ends_with("deps"))
+        })
+    }
+
+    pub fn is_uefi(&self) -> bool {
+        self.uefi || self.fat_dir
+    }
+
+    /// The OVMF firmware image, from `--ovmf` or the `OVMF_PATH` environment variable.
+    pub fn ovmf(&self) -> Option<PathBuf> {
+        self.ovmf
+            .clone()
+            .or_else(|| env::var_os("OVMF_PATH").map(PathBuf::from))
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.headless.unwrap_or_else(|| self.is_test())
+    }
+
+    pub fn serial(&self) -> Option<Serial> {
+        match &self.serial {
+            Some(serial) => Some(serial.clone()),
+            None if self.is_test() => Some(Serial::Stdio),
+            None => None,
+        }
+    }
+
+    pub fn screenshot_delay(&self) -> Duration {
+        self.screenshot_delay.unwrap_or(SCREENSHOT_DELAY)
+    }
+
+    pub fn golden_dir(&self) -> io::Result<PathBuf> {
+        match &self.golden_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => {
+                let manifest = locate_cargo_manifest::locate_manifest()
+                    .map_err(|err| io::Error::new(io::ErrorKind::NotFound, err.to_string()))?;
+                Ok(manifest.parent().unwrap().join("tests").join("golden"))
+            }
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        match self.timeout {
+            Some(timeout) => Some(timeout),
+            None if self.is_test() => Some(TEST_TIMEOUT),
+            None => None,
+        }
+    }
+}
+
+fn seconds(s: &str) -> Result<Duration, String> {
+    s.parse()
+        .map(Duration::from_secs)
+        .map_err(|_| format!("invalid number of seconds `{}`", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn kernel_binary_only() {
+        let args = parse(&["target/x86_64-unknown-none/debug/kernel"]).unwrap();
+        assert_eq!(args.kernel_binary, PathBuf::from("target/x86_64-unknown-none/debug/kernel"));
+        assert!(!args.no_run);
+        assert!(!args.is_uefi());
+        assert!(!args.is_test());
+        assert!(!args.is_headless());
+        assert_eq!(args.serial(), None);
+        assert_eq!(args.timeout(), None);
+    }
+
+    #[test]
+    fn test_mode_detected_from_deps_dir() {
+        let args = parse(&["target/x86_64-unknown-none/debug/deps/kernel-0123abcd"]).unwrap();
+        assert!(args.is_test());
+        assert!(args.is_headless());
+        assert_eq!(args.serial(), Some(Serial::Stdio));
+        assert_eq!(args.timeout(), Some(TEST_TIMEOUT));
+
+        let args = parse(&["target/debug/deps/kernel-0123abcd", "--no-test"]).unwrap();
+        assert!(!args.is_test());
+    }
+
+    #[test]
+    fn all_options() {
+        let args = parse(&[
+            "kernel", "--no-run", "--headless", "--serial", "serial.log", "--memory", "1G",
+            "--smp", "4", "--qemu-arg", "-d", "--qemu-arg", "int", "--timeout", "10", "--gdb",
+            "--test", "--", "-no-shutdown", "-S",
+        ])
+        .unwrap();
+        assert!(args.no_run);
+        assert!(args.is_headless());
+        assert_eq!(args.serial(), Some(Serial::File(PathBuf::from("serial.log"))));
+        assert_eq!(args.memory.as_deref(), Some("1G"));
+        assert_eq!(args.smp, Some(4));
+        assert_eq!(args.qemu_args, ["-d", "int", "-no-shutdown", "-S"]);
+        assert_eq!(args.timeout(), Some(Duration::from_secs(10)));
+        assert!(args.gdb);
+        assert!(args.is_test());
+    }
+
+    #[test]
+    fn uefi_options() {
+        let args = parse(&["kernel", "--uefi", "--ovmf", "bios/RELEASEX64_OVMF.fd"]).unwrap();
+        assert!(args.is_uefi());
+        assert!(!args.fat_dir);
+        assert_eq!(args.ovmf(), Some(PathBuf::from("bios/RELEASEX64_OVMF.fd")));
+
+        let args = parse(&["kernel", "--fat-dir"]).unwrap();
+        assert!(args.is_uefi());
+        assert!(args.fat_dir);
+    }
+
+    #[test]
+    fn screenshot_options() {
+        let args = parse(&[
+            "kernel", "--screenshot", "shot.ppm", "--screenshot-delay", "2", "--golden-dir",
+            "golden", "--bless", "--tolerance", "8", "--max-diff-pixels", "100",
+        ])
+        .unwrap();
+        assert_eq!(args.screenshot, Some(PathBuf::from("shot.ppm")));
+        assert_eq!(args.screenshot_delay(), Duration::from_secs(2));
+        assert_eq!(args.golden_dir().unwrap(), PathBuf::from("golden"));
+        assert!(args.bless);
+        assert_eq!(args.tolerance, Tolerance { channel_delta: 8, differing_pixels: 100 });
+
+        let args = parse(&["kernel"]).unwrap();
+        assert_eq!(args.screenshot_delay(), SCREENSHOT_DELAY);
+        assert_eq!(args.tolerance, Tolerance::default());
+        assert!(parse(&["kernel", "--tolerance", "256"]).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["kernel", "--bogus"]).is_err());
+        assert!(parse(&["kernel", "--smp"]).is_err());
+        assert!(parse(&["kernel", "--smp", "many"]).is_err());
+        assert!(parse(&["kernel", "other"]).is_err());
+        assert!(parse(&["--help"]).unwrap().help);
+    }
+}
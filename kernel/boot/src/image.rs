@@ -0,0 +1,212 @@
+//! RGB images in the binary PPM format written by QEMU's `screendump`, and the
+//! golden-image comparison used by screenshot tests.
+
+use std::{fmt, fs, io, path::Path};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major RGB pixels.
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Image { width, height, pixels: vec![[0; 3]; width * height] }
+    }
+
+    pub fn read_ppm(path: &Path) -> io::Result<Self> {
+        Self::parse_ppm(&fs::read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), err)))
+    }
+
+    pub fn write_ppm(&self, path: &Path) -> io::Result<()> {
+        let mut data = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        data.extend(self.pixels.iter().flatten());
+        fs::write(path, data)
+    }
+
+    /// Parses a binary (`P6`) PPM with 8-bit channels.
+    pub fn parse_ppm(data: &[u8]) -> Result<Self, String> {
+        let mut pos = 0;
+        let mut header = [0usize; 3];
+        if !data.starts_with(b"P6") {
+            return Err("not a binary PPM image".into());
+        }
+        pos += 2;
+        for field in header.iter_mut() {
+            // whitespace and `#` comments may separate the header fields
+            loop {
+                match data.get(pos) {
+                    Some(c) if c.is_ascii_whitespace() => pos += 1,
+                    Some(b'#') => {
+                        while data.get(pos).is_some_and(|&c| c != b'\n') {
+                            pos += 1;
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            let start = pos;
+            while data.get(pos).is_some_and(u8::is_ascii_digit) {
+                pos += 1;
+            }
+            *field = std::str::from_utf8(&data[start..pos])
+                .unwrap()
+                .parse()
+                .map_err(|_| "malformed PPM header")?;
+        }
+        let [width, height, max_value] = header;
+        if max_value != 255 {
+            return Err(format!("unsupported PPM max value {}", max_value));
+        }
+        // a single whitespace character separates the header from the raster
+        let raster = data.get(pos + 1..).ok_or("truncated PPM header")?;
+        if raster.len() < width * height * 3 {
+            return Err("truncated PPM raster".into());
+        }
+        let pixels = raster
+            .chunks_exact(3)
+            .take(width * height)
+            .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+            .collect();
+        Ok(Image { width, height, pixels })
+    }
+}
+
+/// How far a screenshot may deviate from its golden image and still match.
+///
+/// The default requires an exact match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Tolerance {
+    /// Largest difference allowed in any color channel before a pixel counts as differing.
+    pub channel_delta: u8,
+    /// Number of differing pixels allowed.
+    pub differing_pixels: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    Size { actual: (usize, usize), golden: (usize, usize) },
+    Pixels { differing_pixels: usize, max_channel_delta: u8, diff: Image },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::Size { actual, golden } => write!(
+                f,
+                "screenshot is {}x{} but the golden image is {}x{}",
+                actual.0, actual.1, golden.0, golden.1
+            ),
+            Mismatch::Pixels { differing_pixels, max_channel_delta, .. } => write!(
+                f,
+                "{} pixels differ from the golden image (max channel delta {})",
+                differing_pixels, max_channel_delta
+            ),
+        }
+    }
+}
+
+/// Compares `actual` against `golden`.
+///
+/// On a pixel mismatch the returned diff image shows matching pixels dimmed
+/// and differing pixels in red, to locate the regression at a glance.
+pub fn compare(actual: &Image, golden: &Image, tolerance: Tolerance) -> Result<(), Mismatch> {
+    if (actual.width, actual.height) != (golden.width, golden.height) {
+        return Err(Mismatch::Size {
+            actual: (actual.width, actual.height),
+            golden: (golden.width, golden.height),
+        });
+    }
+
+    let mut diff = Image::new(actual.width, actual.height);
+    let mut differing_pixels = 0;
+    let mut max_channel_delta = 0;
+    for ((a, g), d) in actual.pixels.iter().zip(&golden.pixels).zip(&mut diff.pixels) {
+        let delta = (0..3).map(|i| a[i].abs_diff(g[i])).max().unwrap();
+        max_channel_delta = max_channel_delta.max(delta);
+        if delta > tolerance.channel_delta {
+            differing_pixels += 1;
+            *d = [255, 0, 0];
+        } else {
+            *d = g.map(|c| c / 4);
+        }
+    }
+
+    if differing_pixels > tolerance.differing_pixels {
+        return Err(Mismatch::Pixels { differing_pixels, max_channel_delta, diff });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard(width: usize, height: usize) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.pixels[y * width + x] = if (x + y) % 2 == 0 { [255; 3] } else { [0, 0, 128] };
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let image = checkerboard(5, 3);
+        let mut data = b"P6\n# written by a test\n5 3\n255\n".to_vec();
+        data.extend(image.pixels.iter().flatten());
+        assert_eq!(Image::parse_ppm(&data).unwrap(), image);
+
+        let path = std::env::temp_dir().join(format!("boot-ppm-round-trip-{}.ppm", std::process::id()));
+        image.write_ppm(&path).unwrap();
+        assert_eq!(Image::read_ppm(&path).unwrap(), image);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn rejects_malformed_ppm() {
+        assert!(Image::parse_ppm(b"P3\n1 1\n255\n0 0 0").is_err());
+        assert!(Image::parse_ppm(b"P6\n1 1\n65535\n\0\0\0\0\0\0").is_err());
+        assert!(Image::parse_ppm(b"P6\n2 2\n255\n\0\0\0").is_err());
+    }
+
+    #[test]
+    fn compare_identical() {
+        let image = checkerboard(4, 4);
+        assert_eq!(compare(&image, &image, Tolerance::default()), Ok(()));
+    }
+
+    #[test]
+    fn compare_size_mismatch() {
+        assert!(matches!(
+            compare(&checkerboard(4, 4), &checkerboard(4, 5), Tolerance::default()),
+            Err(Mismatch::Size { actual: (4, 4), golden: (4, 5) })
+        ));
+    }
+
+    #[test]
+    fn compare_with_tolerance() {
+        let golden = checkerboard(4, 4);
+        let mut actual = golden.clone();
+        actual.pixels[0] = [250, 255, 255];
+        actual.pixels[5] = [0, 0, 0];
+
+        match compare(&actual, &golden, Tolerance::default()) {
+            Err(Mismatch::Pixels { differing_pixels, max_channel_delta, diff }) => {
+                assert_eq!(differing_pixels, 2);
+                assert_eq!(max_channel_delta, 255);
+                assert_eq!(diff.pixels[0], [255, 0, 0]);
+                assert_eq!(diff.pixels[1], [0, 0, 32]);
+            }
+            other => panic!("unexpected comparison result {:?}", other),
+        }
+
+        let tolerance = Tolerance { channel_delta: 5, differing_pixels: 1 };
+        assert_eq!(compare(&actual, &golden, tolerance), Ok(()));
+    }
+}
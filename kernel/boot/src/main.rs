@@ -1,48 +1,303 @@
 use std::{
+    fs,
+    io::{self, BufRead, BufReader},
     path::{Path, PathBuf},
-    process::Command,
+    process::{ChildStdout, Command, ExitStatus, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
 };
 
-const RUN_ARGS: &[&str] = &["--no-reboot", "-s"];
+use args::{Args, Serial, USAGE};
+use image::Image;
+use qmp::Qmp;
+
+mod args;
+mod image;
+mod qmp;
+
+const RUN_ARGS: &[&str] = &["--no-reboot"];
+const TEST_ARGS: &[&str] = &["-device", "isa-debug-exit,iobase=0xf4,iosize=0x04"];
+/// Exit status of QEMU when the kernel writes `QemuExitCode::Success` (0x10) to isa-debug-exit.
+const TEST_SUCCESS_EXIT_CODE: i32 = (0x10 << 1) | 1;
+/// Exit status of QEMU when the kernel writes `QemuExitCode::Failed` (0x11) to isa-debug-exit.
+const TEST_FAILURE_EXIT_CODE: i32 = (0x11 << 1) | 1;
+/// Exit status of the runner when QEMU was killed by `--timeout`, same as coreutils' `timeout`.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Serial line with which a test kernel asks for its screen to be compared
+/// against `tests/golden/<name>.ppm`, e.g. `[screenshot console]`.
+const SCREENSHOT_MARKER: &str = "[screenshot ";
+
+/// How a QEMU session ended.
+enum Outcome {
+    Exited(ExitStatus),
+    TimedOut,
+    /// QEMU was shut down after taking a screenshot, with the result of saving or checking it.
+    Screenshot(Result<(), String>),
+}
+
+enum ScreenshotRequest {
+    /// `--screenshot <PATH>` after the configured delay.
+    File(PathBuf),
+    /// A screenshot marker printed by a test kernel, naming its golden image.
+    Golden(String),
+}
 
 fn main() {
-    let mut args = std::env::args().skip(1); // skip executable name
+    let args = std::env::args().skip(1); // skip executable name
+    let args = match Args::parse(args) {
+        Ok(args) if args.help => {
+            println!("{}", USAGE);
+            return;
+        }
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+    let kernel_binary_path = args.kernel_binary.canonicalize().unwrap();
+
+    let images = create_disk_images(&kernel_binary_path);
+
+    if args.no_run {
+        println!("Created BIOS disk image at `{}`", images.bios.display());
+        println!("Created UEFI disk image at `{}`", images.uefi_gpt.display());
+        println!("Created UEFI directory at `{}`", images.uefi_dir.display());
+        return;
+    }
+
+    let mut run_cmd = qemu_command(&args);
+    if args.is_uefi() {
+        let ovmf = args.ovmf().unwrap_or_else(|| {
+            eprintln!("error: UEFI boot needs an OVMF firmware image, pass `--ovmf <PATH>` or set OVMF_PATH");
+            std::process::exit(2);
+        });
+        run_cmd.arg("-bios").arg(ovmf);
+        let drive = if args.fat_dir {
+            format!("format=raw,file=fat:rw:{}", images.uefi_dir.display())
+        } else {
+            format!("format=raw,file={}", images.uefi_gpt.display())
+        };
+        run_cmd.arg("-drive").arg(drive);
+    } else {
+        run_cmd
+            .arg("-drive")
+            .arg(format!("format=raw,file={}", images.bios.display()));
+    }
+
+    // unix socket paths are limited to ~100 bytes, too short for paths under `target`
+    let qmp_socket = std::env::temp_dir().join(format!("boot-{}.qmp", std::process::id()));
+    if args.screenshot.is_some() || args.is_test() {
+        let _ = fs::remove_file(&qmp_socket);
+        run_cmd
+            .arg("-qmp")
+            .arg(format!("unix:{},server=on,wait=off", qmp_socket.display()));
+    }
+
+    let exit_code = match run_qemu(&mut run_cmd, &args, &qmp_socket) {
+        Outcome::Exited(exit_status) => exit_code(&args, exit_status),
+        Outcome::TimedOut => {
+            eprintln!("QEMU timed out after {}s", args.timeout().unwrap().as_secs());
+            TIMEOUT_EXIT_CODE
+        }
+        Outcome::Screenshot(Ok(())) => 0,
+        Outcome::Screenshot(Err(err)) => {
+            eprintln!("error: {}", err);
+            1
+        }
+    };
+    let _ = fs::remove_file(&qmp_socket);
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+}
+
+/// Builds the QEMU command line for `args`, without any boot drive.
+fn qemu_command(args: &Args) -> Command {
+    let mut cmd = Command::new("qemu-system-x86_64");
+    cmd.args(RUN_ARGS);
+    if args.is_test() {
+        cmd.args(TEST_ARGS);
+    }
+    if args.is_headless() {
+        cmd.arg("-display").arg("none");
+    }
+    if let Some(serial) = args.serial() {
+        cmd.arg("-serial").arg(serial.qemu_arg());
+    }
+    if let Some(memory) = &args.memory {
+        cmd.arg("-m").arg(memory);
+    }
+    if let Some(smp) = args.smp {
+        cmd.arg("-smp").arg(smp.to_string());
+    }
+    if args.gdb {
+        // GDB server on tcp::1234, halted until the debugger continues
+        cmd.arg("-s").arg("-S");
+    } else if !args.is_test() {
+        cmd.arg("-s");
+    }
+    cmd.args(&args.qemu_args);
+    cmd
+}
+
+/// Runs QEMU until it exits, times out or a screenshot has been handled.
+fn run_qemu(cmd: &mut Command, args: &Args, qmp_socket: &Path) -> Outcome {
+    // test kernels request golden-image checks over serial, so watch it when it goes to stdio
+    let watch_serial = args.is_test() && args.serial() == Some(Serial::Stdio);
+    if watch_serial {
+        cmd.stdout(Stdio::piped());
+    }
+    let mut child = cmd.spawn().expect("failed to start qemu-system-x86_64");
+    let screenshot_markers = watch_serial.then(|| watch_serial_output(child.stdout.take().unwrap()));
+
+    let start = Instant::now();
+    loop {
+        if let Some(exit_status) = child.try_wait().unwrap() {
+            return Outcome::Exited(exit_status);
+        }
+        if args.timeout().is_some_and(|timeout| start.elapsed() >= timeout) {
+            child.kill().unwrap();
+            child.wait().unwrap();
+            return Outcome::TimedOut;
+        }
+
+        let request = match &args.screenshot {
+            Some(path) if start.elapsed() >= args.screenshot_delay() => {
+                Some(ScreenshotRequest::File(path.clone()))
+            }
+            _ => screenshot_markers
+                .as_ref()
+                .and_then(|markers| markers.try_recv().ok())
+                .map(ScreenshotRequest::Golden),
+        };
+        if let Some(request) = request {
+            let result = take_screenshot(args, qmp_socket, request);
+            // the guest is done once the screenshot is taken; kill it if it does not quit cleanly
+            let quit = Qmp::connect(qmp_socket).and_then(|mut qmp| qmp.quit());
+            if quit.is_err() {
+                child.kill().unwrap();
+            }
+            child.wait().unwrap();
+            return Outcome::Screenshot(result);
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+}
+
+/// Echoes the guest's serial output and forwards the names of requested screenshots.
+fn watch_serial_output(stdout: ChildStdout) -> Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            println!("{}", line);
+            if let Some(name) = line
+                .trim()
+                .strip_prefix(SCREENSHOT_MARKER)
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                let _ = tx.send(name.to_string());
+            }
+        }
+    });
+    rx
+}
 
-    let kernel_binary_path = {
-        let path = PathBuf::from(args.next().unwrap());
-        path.canonicalize().unwrap()
+fn take_screenshot(args: &Args, qmp_socket: &Path, request: ScreenshotRequest) -> Result<(), String> {
+    let capture = |path: &Path| {
+        Qmp::connect(qmp_socket)
+            .and_then(|mut qmp| qmp.screendump(path))
+            .map_err(|err| format!("failed to capture screenshot: {}", err))
     };
-    let no_boot = if let Some(arg) = args.next() {
-        match arg.as_str() {
-            "--no-run" => true,
-            other => panic!("unexpected argument `{}`", other),
+
+    let name = match request {
+        ScreenshotRequest::File(path) => {
+            let path = std::env::current_dir().unwrap().join(path);
+            capture(&path)?;
+            println!("Saved screenshot to `{}`", path.display());
+            return Ok(());
         }
-    } else {
-        false
+        ScreenshotRequest::Golden(name) => name,
     };
 
-    let bios = create_disk_images(&kernel_binary_path);
+    let out_dir = args.kernel_binary.canonicalize().unwrap();
+    let out_dir = out_dir.parent().unwrap();
+    let actual_path = out_dir.join(format!("{}.ppm", name));
+    capture(&actual_path)?;
+    let read = |path: &Path| Image::read_ppm(path).map_err(|err| err.to_string());
 
-    if no_boot {
-        println!("Created disk image at `{}`", bios.display());
-        return;
+    let golden_dir = args.golden_dir().map_err(|err| format!("failed to locate golden images: {}", err))?;
+    let golden_path = golden_dir.join(format!("{}.ppm", name));
+    if args.bless {
+        fs::create_dir_all(&golden_dir).map_err(|err| err.to_string())?;
+        fs::copy(&actual_path, &golden_path).map_err(|err| err.to_string())?;
+        println!("Blessed golden image `{}`", golden_path.display());
+        return Ok(());
     }
 
-    let mut run_cmd = Command::new("qemu-system-x86_64");
-    run_cmd
-        .arg("-drive")
-        .arg(format!("format=raw,file={}", bios.display()));
-    run_cmd.args(RUN_ARGS);
+    if !golden_path.exists() {
+        return Err(format!(
+            "golden image `{}` missing, rerun with --bless to create it from `{}`",
+            golden_path.display(),
+            actual_path.display()
+        ));
+    }
+
+    match image::compare(&read(&actual_path)?, &read(&golden_path)?, args.tolerance) {
+        Ok(()) => {
+            println!("Screenshot `{}` matches its golden image", name);
+            Ok(())
+        }
+        Err(mismatch) => {
+            if let image::Mismatch::Pixels { diff, .. } = &mismatch {
+                let diff_path = out_dir.join(format!("{}.diff.ppm", name));
+                if diff.write_ppm(&diff_path).is_ok() {
+                    eprintln!("Wrote diff image to `{}`", diff_path.display());
+                }
+            }
+            Err(format!(
+                "screenshot `{}` ({}) does not match `{}`: {}",
+                name,
+                actual_path.display(),
+                golden_path.display(),
+                mismatch
+            ))
+        }
+    }
+}
 
-    let exit_status = run_cmd.status().unwrap();
-    if !exit_status.success() {
-        std::process::exit(exit_status.code().unwrap_or(1));
+/// Translates QEMU's exit status into the runner's exit code.
+///
+/// Test kernels always leave QEMU through isa-debug-exit, so any other status
+/// (including a clean shutdown) means the test did not finish.
+fn exit_code(args: &Args, exit_status: ExitStatus) -> i32 {
+    match exit_status.code() {
+        Some(TEST_SUCCESS_EXIT_CODE) if args.is_test() => 0,
+        Some(TEST_FAILURE_EXIT_CODE) if args.is_test() => 1,
+        Some(0) if args.is_test() => 1,
+        Some(code) => code,
+        None => 1,
     }
 }
 
-pub fn create_disk_images(kernel_binary_path: &Path) -> PathBuf {
+/// Boot images produced by the bootloader builder for one kernel binary.
+pub struct DiskImages {
+    /// Raw disk image with the BIOS bootloader.
+    pub bios: PathBuf,
+    /// GPT disk image with an EFI system partition containing the UEFI bootloader.
+    pub uefi_gpt: PathBuf,
+    /// Directory laid out like an EFI system partition (`EFI/BOOT/BOOTX64.EFI` and
+    /// `kernel.elf`), bootable through QEMU's `fat:` virtual drive.
+    pub uefi_dir: PathBuf,
+}
+
+pub fn create_disk_images(kernel_binary_path: &Path) -> DiskImages {
     let bootloader_manifest_path = bootloader_locator::locate_bootloader("bootloader").unwrap();
     let kernel_manifest_path = locate_cargo_manifest::locate_manifest().unwrap();
+    let out_dir = kernel_binary_path.parent().unwrap();
 
     let mut build_cmd = Command::new(env!("CARGO"));
     build_cmd.current_dir(bootloader_manifest_path.parent().unwrap());
@@ -50,13 +305,12 @@ pub fn create_disk_images(kernel_binary_path: &Path) -> PathBuf {
     build_cmd
         .arg("--kernel-manifest")
         .arg(&kernel_manifest_path);
-    build_cmd.arg("--kernel-binary").arg(&kernel_binary_path);
+    build_cmd.arg("--kernel-binary").arg(kernel_binary_path);
     build_cmd
         .arg("--target-dir")
         .arg(kernel_manifest_path.parent().unwrap().join("target"));
-    build_cmd
-        .arg("--out-dir")
-        .arg(kernel_binary_path.parent().unwrap());
+    build_cmd.arg("--out-dir").arg(out_dir);
+    build_cmd.arg("--firmware").arg("all");
     build_cmd.arg("--quiet");
 
     if !build_cmd.status().unwrap().success() {
@@ -64,15 +318,33 @@ pub fn create_disk_images(kernel_binary_path: &Path) -> PathBuf {
     }
 
     let kernel_binary_name = kernel_binary_path.file_name().unwrap().to_str().unwrap();
-    let disk_image = kernel_binary_path
-        .parent()
-        .unwrap()
-        .join(format!("boot-bios-{}.img", kernel_binary_name));
-    if !disk_image.exists() {
-        panic!(
-            "Disk image does not exist at {} after bootloader build",
-            disk_image.display()
-        );
-    }
-    disk_image
+    let bios = out_dir.join(format!("boot-bios-{}.img", kernel_binary_name));
+    let uefi_gpt = out_dir.join(format!("boot-uefi-{}.img", kernel_binary_name));
+    let uefi_efi = out_dir.join(format!("boot-uefi-{}.efi", kernel_binary_name));
+    for disk_image in [&bios, &uefi_gpt, &uefi_efi] {
+        if !disk_image.exists() {
+            panic!(
+                "Disk image does not exist at {} after bootloader build",
+                disk_image.display()
+            );
+        }
+    }
+
+    let uefi_dir = out_dir.join(format!("boot-uefi-{}", kernel_binary_name));
+    create_uefi_dir(&uefi_dir, &uefi_efi, kernel_binary_path)
+        .unwrap_or_else(|err| panic!("failed to create {}: {}", uefi_dir.display(), err));
+
+    DiskImages { bios, uefi_gpt, uefi_dir }
+}
+
+/// Lays out `dir` like the EFI system partition the firmware boots from.
+///
+/// `kernel.elf` is placed next to `EFI` so the directory can also host the
+/// custom loader, which reads the kernel from the root of its boot volume.
+fn create_uefi_dir(dir: &Path, efi_file: &Path, kernel_binary_path: &Path) -> io::Result<()> {
+    let boot_dir = dir.join("EFI").join("BOOT");
+    fs::create_dir_all(&boot_dir)?;
+    fs::copy(efi_file, boot_dir.join("BOOTX64.EFI"))?;
+    fs::copy(kernel_binary_path, dir.join("kernel.elf"))?;
+    Ok(())
 }
@@ -0,0 +1,82 @@
+//! Minimal client for the QEMU Machine Protocol.
+//!
+//! Only the handful of commands the runner needs are supported, so replies are
+//! recognized by their top-level key instead of being parsed as full JSON.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How long to keep retrying while QEMU has not created its socket yet.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct Qmp {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Qmp {
+    /// Connects to the QMP server QEMU listens on through `-qmp unix:<socket>,server=on,wait=off`.
+    pub fn connect(socket: &Path) -> io::Result<Self> {
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        let stream = loop {
+            match UnixStream::connect(socket) {
+                Ok(stream) => break stream,
+                Err(_) if Instant::now() < deadline => thread::sleep(CONNECT_RETRY_INTERVAL),
+                Err(err) => return Err(err),
+            }
+        };
+        let mut qmp = Qmp {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+        // the server greets with {"QMP": {...}} and accepts no command before capabilities negotiation
+        qmp.read_message()?;
+        qmp.execute(r#"{"execute": "qmp_capabilities"}"#)?;
+        Ok(qmp)
+    }
+
+    /// Writes the current display contents to `path` on the host as a binary PPM.
+    pub fn screendump(&mut self, path: &Path) -> io::Result<()> {
+        let path = path.to_str().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "screenshot path is not valid UTF-8")
+        })?;
+        self.execute(&format!(
+            r#"{{"execute": "screendump", "arguments": {{"filename": "{}"}}}}"#,
+            path.replace('\\', "\\\\").replace('"', "\\\"")
+        ))
+    }
+
+    /// Asks QEMU to exit.
+    pub fn quit(&mut self) -> io::Result<()> {
+        self.execute(r#"{"execute": "quit"}"#)
+    }
+
+    fn execute(&mut self, command: &str) -> io::Result<()> {
+        self.writer.write_all(command.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        // asynchronous events may arrive before the reply to our command
+        loop {
+            let message = self.read_message()?;
+            if message.starts_with(r#"{"return""#) {
+                return Ok(());
+            }
+            if message.starts_with(r#"{"error""#) {
+                return Err(io::Error::other(format!("QMP command failed: {}", message)));
+            }
+        }
+    }
+
+    fn read_message(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "QMP connection closed"));
+        }
+        Ok(line.trim().to_string())
+    }
+}
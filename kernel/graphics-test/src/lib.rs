@@ -0,0 +1,21 @@
+//! Builds the kernel's graphics module for the host, so that drawing code can
+//! be covered by plain `cargo test` instead of booting a test kernel.
+//!
+//! Only the `PixelWriter`-generic parts are meant to be exercised here; the
+//! framebuffer itself is compiled but never constructed. The shell comes
+//! along with the terminal it belongs to, which is likewise never launched.
+
+extern crate alloc;
+
+#[path = "../../src/graphics/mod.rs"]
+pub mod graphics;
+#[path = "../../src/input.rs"]
+pub mod input;
+#[path = "../../src/clipboard.rs"]
+pub mod clipboard;
+
+// at the top level rather than in `apps`, which they only use to reach each other through `super`
+#[path = "../../src/apps/shell.rs"]
+pub mod shell;
+#[path = "../../src/apps/terminal.rs"]
+pub mod terminal;
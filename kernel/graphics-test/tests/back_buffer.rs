@@ -0,0 +1,101 @@
+use graphics_test::graphics::{
+    back_buffer::BackBuffer,
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+
+const BLACK: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+const WHITE: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const RED: PixelColor = PixelColor { r: 255, g: 0, b: 0 };
+
+/// A drawing target counting the pixels written to it.
+struct CountingWriter {
+    canvas: MemoryCanvas,
+    written: usize,
+}
+
+impl PixelWriter for CountingWriter {
+    fn size(&self) -> XY<usize> {
+        self.canvas.size()
+    }
+    fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor) {
+        self.written += 1;
+        self.canvas.draw_pixel(pos, color);
+    }
+    fn read_pixel(&self, pos: XY<usize>) -> PixelColor {
+        self.canvas.pixel(pos)
+    }
+    fn copy_rect(&mut self, src: Rect, dst: XY<usize>) {
+        self.canvas.copy_rect(src, dst);
+    }
+}
+
+fn screen(size: XY<usize>) -> CountingWriter {
+    CountingWriter { canvas: MemoryCanvas::new(size, BLACK), written: 0 }
+}
+
+#[test]
+fn flush_copies_only_dirty_areas() {
+    let mut back = BackBuffer::new(XY::new(20, 10), BLACK);
+    let mut front = screen(XY::new(20, 10));
+    back.fill_rect(Rect::new(1, 1, 3, 2), WHITE);
+    back.draw_pixel(XY::new(15, 8), RED);
+    assert_eq!(back.dirty_rects(), [Rect::new(1, 1, 3, 2), Rect::new(15, 8, 1, 1)]);
+
+    back.flush(&mut front);
+    assert_eq!(front.written, 7);
+    assert_eq!(front.canvas.pixels(), back.canvas().pixels());
+    assert!(back.dirty_rects().is_empty());
+
+    // nothing changed since
+    back.flush(&mut front);
+    assert_eq!(front.written, 7);
+}
+
+#[test]
+fn touching_areas_merge() {
+    let mut back = BackBuffer::new(XY::new(20, 10), BLACK);
+    for x in 0..5 {
+        back.draw_pixel(XY::new(x, 2), WHITE);
+    }
+    back.fill_rect(Rect::new(0, 3, 2, 2), WHITE);
+    assert_eq!(back.dirty_rects(), [Rect::new(0, 2, 5, 3)]);
+    // an area bridging two others swallows both
+    back.fill_rect(Rect::new(10, 2, 2, 2), WHITE);
+    back.fill_rect(Rect::new(4, 1, 7, 1), WHITE);
+    assert_eq!(back.dirty_rects(), [Rect::new(0, 1, 12, 4)]);
+}
+
+#[test]
+fn many_areas_collapse_into_bounds() {
+    let mut back = BackBuffer::new(XY::new(200, 10), BLACK);
+    for i in 0..40 {
+        back.draw_pixel(XY::new(i * 4, i % 2 * 5), WHITE);
+    }
+    assert!(back.dirty_rects().len() <= 32);
+    let mut front = screen(XY::new(200, 10));
+    back.flush(&mut front);
+    assert_eq!(front.canvas.pixels(), back.canvas().pixels());
+}
+
+#[test]
+fn copy_rect_marks_destination() {
+    let mut back = BackBuffer::new(XY::new(8, 8), BLACK);
+    let mut front = screen(XY::new(8, 8));
+    back.fill_rect(Rect::new(0, 0, 8, 2), WHITE);
+    back.flush(&mut front);
+    back.copy_rect(Rect::new(0, 0, 8, 2), XY::new(0, 5));
+    assert_eq!(back.dirty_rects(), [Rect::new(0, 5, 8, 2)]);
+    back.flush(&mut front);
+    assert_eq!(front.canvas.pixels(), back.canvas().pixels());
+}
+
+#[test]
+fn drawing_outside_is_not_dirty() {
+    let mut back = BackBuffer::new(XY::new(8, 8), BLACK);
+    back.fill_rect(Rect::new(-5, -5, 3, 3), WHITE);
+    back.mark_dirty(Rect::new(6, 6, 10, 10));
+    assert_eq!(back.dirty_rects(), [Rect::new(6, 6, 2, 2)]);
+}
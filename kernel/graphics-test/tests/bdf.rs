@@ -0,0 +1,118 @@
+use graphics_test::graphics::{
+    bdf::BdfFont,
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    font::{Font, FontError, GlyphSet, Hankaku},
+};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+const FG: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const BG: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+
+/// A 16x16 font with a full-width "あ" placeholder: a frame one pixel inside
+/// its bounding box, and a small glyph offset from the origin.
+const ZENKAKU: &str = "STARTFONT 2.1
+FONT -test-zenkaku
+SIZE 16 75 75
+FONTBOUNDINGBOX 16 16 0 -2
+STARTPROPERTIES 1
+CHARSET_REGISTRY \"ISO10646\"
+ENDPROPERTIES
+CHARS 3
+STARTCHAR U+3042
+ENCODING 12354
+DWIDTH 16 0
+BBX 14 14 1 -1
+BITMAP
+FFFC
+8004
+8004
+8004
+8004
+8004
+8004
+8004
+8004
+8004
+8004
+8004
+8004
+FFFC
+ENDCHAR
+STARTCHAR dot
+ENCODING 12290
+DWIDTH 16 0
+BBX 2 2 3 0
+BITMAP
+C0
+C0
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+DWIDTH 16 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+fn zenkaku() -> BdfFont {
+    BdfFont::parse(ZENKAKU.as_bytes()).unwrap()
+}
+
+#[test]
+fn parses_glyphs_onto_the_bounding_box() {
+    let font = zenkaku();
+    assert_eq!(font.glyph_size(), XY::new(8, 16));
+
+    let glyph = font.glyph('あ').unwrap();
+    assert_eq!(glyph.size, XY::new(16, 16));
+    // 16 rows high, baseline 2 rows above the bottom: the bitmap spans rows 1..15
+    assert!(!glyph.pixel(XY::new(1, 0)));
+    assert!(glyph.pixel(XY::new(1, 1)));
+    assert!(glyph.pixel(XY::new(14, 1)));
+    assert!(!glyph.pixel(XY::new(15, 1)));
+    assert!(glyph.pixel(XY::new(1, 14)));
+    assert!(!glyph.pixel(XY::new(2, 13)));
+    assert!(!glyph.pixel(XY::new(1, 15)));
+
+    let dot = font.glyph('。').unwrap();
+    assert!(dot.pixel(XY::new(3, 12)) && dot.pixel(XY::new(4, 13)));
+    assert!(!dot.pixel(XY::new(3, 14)));
+
+    assert!(font.glyph('a').is_none());
+}
+
+#[test]
+fn rejects_other_encodings_and_malformed_fonts() {
+    let jis = ZENKAKU.replace("\"ISO10646\"", "\"JISX0208.1983\"");
+    assert!(matches!(BdfFont::parse(jis.as_bytes()), Err(FontError::Unsupported(_))));
+    let broken = ZENKAKU.replace("BBX 2 2 3 0", "BBX 2 x 3 0");
+    assert_eq!(BdfFont::parse(broken.as_bytes()).err(), Some(FontError::Malformed { line: 32 }));
+
+    // negative or huge sizes are rejected instead of overflowing
+    for (from, to, line) in [
+        ("FONTBOUNDINGBOX 16 16 0 -2", "FONTBOUNDINGBOX 16 -16 0 -2", 4),
+        ("FONTBOUNDINGBOX 16 16 0 -2", "FONTBOUNDINGBOX 16 16 0 -2147483648", 4),
+        ("FONTBOUNDINGBOX 16 16 0 -2", "FONTBOUNDINGBOX 99999999 16 0 -2", 4),
+        ("DWIDTH 16 0\nBBX 2 2 3 0", "DWIDTH -16 0\nBBX 2 2 3 0", 31),
+        ("BBX 2 2 3 0", "BBX 2 2 3 2147483647", 32),
+    ] {
+        let broken = ZENKAKU.replace(from, to);
+        assert_eq!(BdfFont::parse(broken.as_bytes()).err(), Some(FontError::Malformed { line }), "{}", to);
+    }
+}
+
+#[test]
+fn falls_back_for_characters_the_regular_font_lacks() {
+    let zenkaku = zenkaku();
+    let font = Font::new(&HANKAKU).with_fallback(&zenkaku);
+    assert_eq!(font.glyph('a'), HANKAKU.glyph('a'));
+    assert_eq!(font.glyph('あ'), zenkaku.glyph('あ'));
+
+    let mut canvas = MemoryCanvas::new(XY::new(16, 16), BG);
+    font.draw_char(&mut canvas, XY::new(0, 0), FG, BG, 'あ');
+    assert_eq!(canvas.pixel(XY::new(14, 1)), FG);
+    assert_eq!(canvas.pixel(XY::new(15, 1)), BG);
+}
@@ -0,0 +1,39 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+
+fn gray(v: u8) -> PixelColor {
+    PixelColor { r: v, g: v, b: v }
+}
+
+/// A 4x4 canvas whose pixel at (x, y) has the gray level `y * 4 + x`.
+fn numbered() -> MemoryCanvas {
+    let mut canvas = MemoryCanvas::new(XY::new(4, 4), gray(0));
+    for y in 0..4 {
+        for x in 0..4 {
+            canvas.draw_pixel(XY::new(x, y), gray((y * 4 + x) as u8));
+        }
+    }
+    canvas
+}
+
+fn levels(canvas: &MemoryCanvas) -> Vec<u8> {
+    canvas.pixels().iter().map(|p| p.r).collect()
+}
+
+#[test]
+fn copy_rect_up_overlapping() {
+    let mut canvas = numbered();
+    canvas.copy_rect(Rect::new(0, 1, 4, 3), XY::new(0, 0));
+    assert_eq!(levels(&canvas), [4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 12, 13, 14, 15]);
+}
+
+#[test]
+fn copy_rect_down_overlapping() {
+    let mut canvas = numbered();
+    canvas.copy_rect(Rect::new(1, 0, 2, 3), XY::new(2, 1));
+    assert_eq!(levels(&canvas), [0, 1, 2, 3, 4, 5, 1, 2, 8, 9, 5, 6, 12, 13, 9, 10]);
+}
@@ -0,0 +1,52 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    color::{BlendMode, Premultiplied, Rgba},
+    common::{PixelColor, XY},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+
+const GRAY: PixelColor = PixelColor { r: 100, g: 100, b: 100 };
+
+#[test]
+fn premultiply_round_trips() {
+    let color = Rgba::new(200, 100, 0, 128);
+    assert_eq!(color.premultiply(), Premultiplied { r: 100, g: 50, b: 0, a: 128 });
+    assert_eq!(color.premultiply().unpremultiply(), Rgba::new(199, 100, 0, 128));
+    assert_eq!(Rgba::new(9, 9, 9, 0).premultiply().unpremultiply(), Rgba::TRANSPARENT);
+    assert_eq!(Rgba::from(GRAY), Rgba::new(100, 100, 100, 255));
+    assert_eq!(PixelColor::from(Rgba::new(1, 2, 3, 4)), PixelColor { r: 1, g: 2, b: 3 });
+}
+
+#[test]
+fn source_over() {
+    let over = |color: Rgba| BlendMode::SourceOver.blend(color.premultiply(), GRAY);
+    assert_eq!(over(Rgba::new(255, 0, 0, 255)), PixelColor { r: 255, g: 0, b: 0 });
+    assert_eq!(over(Rgba::new(255, 0, 0, 0)), GRAY);
+    assert_eq!(over(Rgba::new(200, 0, 0, 128)), PixelColor { r: 150, g: 50, b: 50 });
+}
+
+#[test]
+fn additive_saturates() {
+    let add = |color: Rgba| BlendMode::Additive.blend(color.premultiply(), GRAY);
+    assert_eq!(add(Rgba::new(200, 50, 0, 255)), PixelColor { r: 255, g: 150, b: 100 });
+    assert_eq!(add(Rgba::new(200, 50, 0, 128)), PixelColor { r: 200, g: 125, b: 100 });
+}
+
+#[test]
+fn blend_rect_clips() {
+    let mut canvas = MemoryCanvas::new(XY::new(4, 2), GRAY);
+    canvas.blend_rect(Rect::new(-1, 1, 3, 5), Rgba::new(0, 0, 0, 128), BlendMode::SourceOver);
+    let levels: Vec<u8> = canvas.pixels().iter().map(|p| p.r).collect();
+    assert_eq!(levels, [100, 100, 100, 100, 50, 50, 100, 100]);
+}
+
+#[test]
+fn blend_blit_composes_layers() {
+    let mut canvas = MemoryCanvas::new(XY::new(3, 1), GRAY);
+    let white = Rgba::new(255, 255, 255, 255);
+    let sprite = [Premultiplied::TRANSPARENT, white.with_alpha(64).premultiply(), white.premultiply()];
+    canvas.blend_blit(XY::new(1, 0), XY::new(3, 1), &sprite, BlendMode::SourceOver);
+    let levels: Vec<u8> = canvas.pixels().iter().map(|p| p.r).collect();
+    assert_eq!(levels, [100, 100, 139]);
+}
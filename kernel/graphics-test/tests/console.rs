@@ -0,0 +1,346 @@
+use graphics_test::graphics::{
+    frame_buffer::PixelWriter,
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    console::Console,
+    font::{Font, Hankaku},
+};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+const FG: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const BG: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+
+fn canvas() -> MemoryCanvas {
+    MemoryCanvas::new(XY::new(800, 450), PixelColor { r: 1, g: 2, b: 3 })
+}
+
+/// Whether the character cell at `cell` contains any foreground pixel.
+fn has_ink(canvas: &MemoryCanvas, cell: XY<usize>) -> bool {
+    let size = Font::new(&HANKAKU).char_size();
+    (0..size.y).any(|dy| {
+        (0..size.x).any(|dx| canvas.pixel(XY::new(cell.x * size.x + dx, cell.y * size.y + dy)) == FG)
+    })
+}
+
+#[test]
+fn flush_clears_to_background() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.put_string(&mut canvas, "\x1b[?25l");
+    console.flush(&mut canvas);
+    assert!(canvas.pixels().iter().all(|&pixel| pixel == BG));
+}
+
+#[test]
+fn put_string_advances_cursor() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    console.put_string(&mut canvas, "\x1b[?25lab\nc");
+
+    assert!(has_ink(&canvas, XY::new(0, 0)));
+    assert!(has_ink(&canvas, XY::new(1, 0)));
+    assert!(!has_ink(&canvas, XY::new(2, 0)));
+    assert!(has_ink(&canvas, XY::new(0, 1)));
+    assert!(!has_ink(&canvas, XY::new(1, 1)));
+}
+
+#[test]
+fn spaces_leave_no_ink() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    console.put_string(&mut canvas, "    ");
+    assert!((0..4).all(|x| !has_ink(&canvas, XY::new(x, 0))));
+}
+
+/// Renders `console`'s grid from scratch, for comparison with incremental updates.
+fn repainted(console: &mut Console) -> MemoryCanvas {
+    let mut canvas = canvas();
+    console.flush(&mut canvas);
+    canvas
+}
+
+#[test]
+fn wraps_long_lines() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    let line: String = ('a'..='z').cycle().take(83).collect();
+    console.put_string(&mut canvas, &line);
+
+    assert_eq!(console.cursor(), XY::new(3, 1));
+    assert_eq!(console.cell(XY::new(79, 0)).c, 'b');
+    assert_eq!(console.cell(XY::new(0, 1)).c, 'c');
+    assert!(has_ink(&canvas, XY::new(79, 0)));
+    assert!(has_ink(&canvas, XY::new(2, 1)));
+}
+
+#[test]
+fn full_line_does_not_add_empty_line() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.put_string(&mut canvas, &"x".repeat(80));
+    console.put_string(&mut canvas, "\ny");
+    assert_eq!(console.cell(XY::new(0, 1)).c, 'y');
+}
+
+#[test]
+fn scrolls_when_reaching_bottom() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    for i in 0..30 {
+        console.put_string(&mut canvas, &format!("line {}\n", i));
+    }
+
+    // lines 6..29 remain, followed by the empty line the cursor is on
+    assert_eq!(console.cursor(), XY::new(0, 24));
+    assert_eq!(console.cell(XY::new(5, 0)).c, '6');
+    assert_eq!(console.cell(XY::new(5, 23)).c, '2');
+    assert_eq!(console.cell(XY::new(6, 23)).c, '9');
+    assert_eq!(console.cell(XY::new(0, 24)).c, ' ');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+}
+
+#[test]
+fn scrolls_many_rows_in_one_write() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    console.put_string(&mut canvas, "first\n");
+    let text: String = (0..40).map(|i| format!("{}\n", i)).collect();
+    console.put_string(&mut canvas, &text);
+
+    assert_eq!(console.cell(XY::new(0, 0)).c, '1');
+    assert_eq!(console.cell(XY::new(1, 0)).c, '6');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+}
+
+#[test]
+fn redraws_only_dirty_cells() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    let marker = PixelColor { r: 0, g: 255, b: 0 };
+    canvas.draw_pixel(XY::new(795, 445), marker);
+
+    console.put_string(&mut canvas, "hello");
+    assert_eq!(canvas.pixel(XY::new(795, 445)), marker);
+}
+
+#[test]
+fn grid_fits_resolution() {
+    let console = Console::new(Font::new(&HANKAKU), XY::new(1280, 800));
+    assert_eq!(console.size(), XY::new(128, 44));
+    let console = Console::new(Font::new(&HANKAKU), XY::new(5, 5));
+    assert_eq!(console.size(), XY::new(1, 1));
+}
+
+#[test]
+fn scrolls_with_partial_bottom_row() {
+    // 450 + 10 pixels leave a strip below the last row that must not be touched
+    let mut canvas = MemoryCanvas::new(XY::new(805, 460), PixelColor { r: 1, g: 2, b: 3 });
+    let mut console = Console::new(Font::new(&HANKAKU), canvas.size());
+    assert_eq!(console.size(), XY::new(80, 25));
+    console.flush(&mut canvas);
+    for i in 0..30 {
+        console.put_string(&mut canvas, &format!("line {}\n", i));
+    }
+    assert_eq!(console.cell(XY::new(5, 0)).c, '6');
+    assert_eq!(canvas.pixel(XY::new(0, 455)), PixelColor { r: 1, g: 2, b: 3 });
+    assert_eq!(canvas.pixel(XY::new(802, 0)), PixelColor { r: 1, g: 2, b: 3 });
+}
+
+#[test]
+fn resize_keeps_text() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), canvas.size());
+    console.put_string(&mut canvas, "abc\ndef");
+
+    console.resize(XY::new(1280, 800));
+    assert_eq!(console.size(), XY::new(128, 44));
+    assert_eq!(console.cursor(), XY::new(3, 1));
+    assert_eq!(console.cell(XY::new(2, 0)).c, 'c');
+    assert_eq!(console.cell(XY::new(0, 1)).c, 'd');
+
+    console.resize(XY::new(20, 450));
+    assert_eq!(console.size(), XY::new(2, 25));
+    assert_eq!(console.cursor(), XY::new(2, 1));
+    assert_eq!(console.cell(XY::new(1, 1)).c, 'e');
+}
+
+#[test]
+fn resize_drops_rows_above_cursor() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), canvas.size());
+    for i in 0..10 {
+        console.put_string(&mut canvas, &format!("{}\n", i));
+    }
+    console.resize(XY::new(800, 4 * 18));
+    assert_eq!(console.cursor(), XY::new(0, 3));
+    assert_eq!(console.cell(XY::new(0, 0)).c, '7');
+    assert_eq!(console.cell(XY::new(0, 2)).c, '9');
+}
+
+fn cursor_after(s: &str) -> XY<usize> {
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.put_string(&mut canvas(), s);
+    console.cursor()
+}
+
+#[test]
+fn cursor_movement() {
+    assert_eq!(cursor_after("\x1b[5;10H"), XY::new(9, 4));
+    assert_eq!(cursor_after("\x1b[H"), XY::new(0, 0));
+    assert_eq!(cursor_after("\x1b[5;10H\x1b[2A\x1b[3D"), XY::new(6, 2));
+    assert_eq!(cursor_after("\x1b[5;10H\x1b[B\x1b[C"), XY::new(10, 5));
+    assert_eq!(cursor_after("\x1b[999;999H"), XY::new(79, 24));
+    assert_eq!(cursor_after("\x1b[10A\x1b[10D"), XY::new(0, 0));
+    assert_eq!(cursor_after("abc\x1b[G\x1b[7d"), XY::new(0, 6));
+    assert_eq!(cursor_after("a\tb\t"), XY::new(16, 0));
+    assert_eq!(cursor_after("ab\x08"), XY::new(1, 0));
+    assert_eq!(cursor_after("\x1b[3;4H\x1b7\x1b[H\x1b8"), XY::new(3, 2));
+    assert_eq!(cursor_after("\x1b[3;4H\x1b[s\x1b[H\x1b[u"), XY::new(3, 2));
+}
+
+#[test]
+fn sgr_colors() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.put_string(&mut canvas, "\x1b[31;44m \x1b[0m \x1b[48;2;1;2;3m \x1b[7m \x1b[m");
+
+    let red = PixelColor { r: 205, g: 0, b: 0 };
+    let blue = PixelColor { r: 0, g: 0, b: 238 };
+    let rgb = PixelColor { r: 1, g: 2, b: 3 };
+    let cell_pixel = |x: usize| canvas.pixel(XY::new(x * 10 + 9, 17));
+    assert_eq!(cell_pixel(0), blue);
+    assert_eq!(cell_pixel(1), BG);
+    assert_eq!(cell_pixel(2), rgb);
+    // reverse video draws the default foreground as background
+    assert_eq!(cell_pixel(3), FG);
+
+    console.put_string(&mut canvas, "\x1b[1;31mA");
+    let bright_red = PixelColor { r: 255, g: 0, b: 0 };
+    assert!(canvas.pixels().contains(&bright_red));
+    assert!(!canvas.pixels().contains(&red));
+}
+
+#[test]
+fn erase_in_line_and_display() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.put_string(&mut canvas, "abcdef\nghijkl\nmnopqr");
+
+    console.put_string(&mut canvas, "\x1b[1;3H\x1b[K");
+    assert_eq!(console.cell(XY::new(1, 0)).c, 'b');
+    assert_eq!(console.cell(XY::new(2, 0)).c, ' ');
+    console.put_string(&mut canvas, "\x1b[2;3H\x1b[1K");
+    assert_eq!(console.cell(XY::new(2, 1)).c, ' ');
+    assert_eq!(console.cell(XY::new(3, 1)).c, 'j');
+    console.put_string(&mut canvas, "\x1b[3;5H\x1b[J");
+    assert_eq!(console.cell(XY::new(3, 2)).c, 'p');
+    assert_eq!(console.cell(XY::new(4, 2)).c, ' ');
+
+    // erased cells take the current background color
+    console.put_string(&mut canvas, "\x1b[42m\x1b[2J");
+    assert!((0..3).all(|y| console.cell(XY::new(0, y)).c == ' '));
+    assert_eq!(canvas.pixel(XY::new(799, 449)), PixelColor { r: 0, g: 205, b: 0 });
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+}
+
+#[test]
+fn scroll_region() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    for y in 0..25 {
+        console.put_string(&mut canvas, &format!("\x1b[{};1H{}", y + 1, char::from(b'A' + y)));
+    }
+
+    // rows 3..=5 scroll on their own, the rest of the screen stays in place
+    console.put_string(&mut canvas, "\x1b[3;5r\x1b[5;1H\nx");
+    assert_eq!(console.cell(XY::new(0, 1)).c, 'B');
+    assert_eq!(console.cell(XY::new(0, 2)).c, 'D');
+    assert_eq!(console.cell(XY::new(0, 3)).c, 'E');
+    assert_eq!(console.cell(XY::new(0, 4)).c, 'x');
+    assert_eq!(console.cell(XY::new(0, 5)).c, 'F');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+
+    // reverse index at the top margin scrolls the region down
+    console.put_string(&mut canvas, "\x1b[3;1H\x1bMy");
+    assert_eq!(console.cell(XY::new(0, 2)).c, 'y');
+    assert_eq!(console.cell(XY::new(0, 3)).c, 'D');
+    assert_eq!(console.cell(XY::new(0, 5)).c, 'F');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+
+    // scrolling another region redraws the pending one
+    console.put_string(&mut canvas, "\x1b[S\x1b[r\x1b[2T");
+    assert_eq!(console.cell(XY::new(0, 2)).c, 'A');
+    assert_eq!(console.cell(XY::new(0, 4)).c, 'D');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+}
+
+#[test]
+fn insert_and_delete() {
+    let mut canvas = canvas();
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.put_string(&mut canvas, "abcd\nefgh\nijkl\x1b[1;2H\x1b[2@");
+    assert_eq!(console.cell(XY::new(1, 0)).c, ' ');
+    assert_eq!(console.cell(XY::new(3, 0)).c, 'b');
+    console.put_string(&mut canvas, "\x1b[3P");
+    assert_eq!(console.cell(XY::new(1, 0)).c, 'c');
+    console.put_string(&mut canvas, "\x1b[2;1H\x1b[L");
+    assert_eq!(console.cell(XY::new(0, 1)).c, ' ');
+    assert_eq!(console.cell(XY::new(0, 2)).c, 'e');
+    console.put_string(&mut canvas, "\x1b[2M");
+    assert_eq!(console.cell(XY::new(0, 1)).c, 'i');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+}
+
+fn wide_console() -> Console<'static> {
+    Console::new(Font::new(&HANKAKU), XY::new(800, 450))
+}
+
+#[test]
+fn wide_characters_take_two_cells() {
+    let mut canvas = canvas();
+    let mut console = wide_console();
+    console.put_string(&mut canvas, "aあb");
+    assert_eq!(console.cell(XY::new(1, 0)).c, 'あ');
+    assert!(console.cell(XY::new(2, 0)).is_continuation());
+    assert_eq!(console.cell(XY::new(3, 0)).c, 'b');
+    assert_eq!(console.cursor(), XY::new(4, 0));
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+    let mut dump = String::new();
+    console.dump_history(&mut dump).unwrap();
+    assert!(dump.starts_with("aあb\n"));
+}
+
+#[test]
+fn wide_character_wraps_instead_of_splitting() {
+    let mut canvas = canvas();
+    let mut console = wide_console();
+    console.put_string(&mut canvas, &"x".repeat(79));
+    console.put_string(&mut canvas, "漢");
+    assert_eq!(console.cell(XY::new(79, 0)).c, ' ');
+    assert_eq!(console.cell(XY::new(0, 1)).c, '漢');
+    assert_eq!(console.cursor(), XY::new(2, 1));
+}
+
+#[test]
+fn overwriting_half_of_a_wide_character() {
+    let mut canvas = canvas();
+    let mut console = wide_console();
+    console.put_string(&mut canvas, "あいう\x1b[1;2Hx\x1b[1;5Hy");
+    // "x" replaces the right half of "あ", "y" the left half of "う"
+    let row: String = (0..7).map(|x| console.cell(XY::new(x, 0)).c).collect();
+    assert_eq!(row, " xい\0y  ");
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+
+    console.put_string(&mut canvas, "\x1b[2;1Hかき\x1b[2;2H\x1b[K");
+    assert_eq!(console.cell(XY::new(0, 1)).c, ' ');
+    console.put_string(&mut canvas, "\x1b[3;1Hさし\x1b[3;1H\x1b[P");
+    assert_eq!(console.cell(XY::new(0, 2)).c, ' ');
+    assert_eq!(console.cell(XY::new(1, 2)).c, 'し');
+    assert!(canvas.pixels() == repainted(&mut console).pixels());
+}
@@ -0,0 +1,108 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    console::{Console, CursorStyle},
+    font::{Font, Hankaku},
+};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+const FG: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const BG: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+
+fn console() -> (Console<'static>, MemoryCanvas) {
+    let mut canvas = MemoryCanvas::new(XY::new(800, 450), BG);
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    console.flush(&mut canvas);
+    (console, canvas)
+}
+
+/// The pixels of the character cell at `cell`, row by row.
+fn cell_pixels(canvas: &MemoryCanvas, cell: XY<usize>) -> Vec<Vec<PixelColor>> {
+    let size = Font::new(&HANKAKU).char_size();
+    (0..size.y)
+        .map(|dy| (0..size.x).map(|dx| canvas.pixel(XY::new(cell.x * size.x + dx, cell.y * size.y + dy))).collect())
+        .collect()
+}
+
+fn is_filled(canvas: &MemoryCanvas, cell: XY<usize>, color: PixelColor) -> bool {
+    cell_pixels(canvas, cell).iter().flatten().all(|&pixel| pixel == color)
+}
+
+#[test]
+fn block_cursor_follows_output() {
+    let (mut console, mut canvas) = console();
+    assert!(is_filled(&canvas, XY::new(0, 0), FG));
+
+    console.put_string(&mut canvas, "ab");
+    assert!(is_filled(&canvas, XY::new(2, 0), FG));
+    // the old position is back to a plain 'a'
+    assert!(cell_pixels(&canvas, XY::new(0, 0)).iter().flatten().any(|&pixel| pixel == BG));
+}
+
+#[test]
+fn blinks_until_steady() {
+    let (mut console, mut canvas) = console();
+    console.blink_cursor(&mut canvas);
+    assert!(is_filled(&canvas, XY::new(0, 0), BG));
+    console.blink_cursor(&mut canvas);
+    assert!(is_filled(&canvas, XY::new(0, 0), FG));
+
+    // output shows the cursor right away
+    console.blink_cursor(&mut canvas);
+    console.put_string(&mut canvas, " ");
+    assert!(is_filled(&canvas, XY::new(1, 0), FG));
+
+    console.put_string(&mut canvas, "\x1b[2 q");
+    assert_eq!((console.cursor_style(), console.cursor_blinks()), (CursorStyle::Block, false));
+    console.blink_cursor(&mut canvas);
+    assert!(is_filled(&canvas, XY::new(1, 0), FG));
+
+    console.put_string(&mut canvas, "\x1b[?12h");
+    assert!(console.cursor_blinks());
+}
+
+#[test]
+fn underline_and_bar_styles() {
+    let (mut console, mut canvas) = console();
+    console.put_string(&mut canvas, "\x1b[4 q");
+    assert_eq!((console.cursor_style(), console.cursor_blinks()), (CursorStyle::Underline, false));
+    let rows = cell_pixels(&canvas, XY::new(0, 0));
+    assert!(rows[rows.len() - 2..].iter().flatten().all(|&pixel| pixel == FG));
+    assert!(rows[..rows.len() - 2].iter().flatten().all(|&pixel| pixel == BG));
+
+    console.put_string(&mut canvas, "\x1b[5 q");
+    assert_eq!((console.cursor_style(), console.cursor_blinks()), (CursorStyle::Bar, true));
+    let rows = cell_pixels(&canvas, XY::new(0, 0));
+    assert!(rows.iter().all(|row| row[..2] == [FG, FG] && row[2..].iter().all(|&pixel| pixel == BG)));
+}
+
+#[test]
+fn hidden_and_shown_by_dectcem() {
+    let (mut console, mut canvas) = console();
+    console.put_string(&mut canvas, "\x1b[?25l");
+    assert!(!console.is_cursor_enabled());
+    assert!(is_filled(&canvas, XY::new(0, 0), BG));
+    // a hidden cursor does not blink back on
+    console.blink_cursor(&mut canvas);
+    console.blink_cursor(&mut canvas);
+    assert!(is_filled(&canvas, XY::new(0, 0), BG));
+
+    console.put_string(&mut canvas, "\x1b[?25h");
+    assert!(is_filled(&canvas, XY::new(0, 0), FG));
+}
+
+#[test]
+fn scrolling_leaves_no_trace() {
+    let (mut console, mut canvas) = console();
+    // each write draws the cursor before the next one moves its pixels
+    for s in ["\x1b[3;8r\x1b[5;4Hx", "\x1b[2S", "\x1b[1T", "\x1b[3T", "\x1b[20;1H"] {
+        console.put_string(&mut canvas, s);
+    }
+    for i in 0..30 {
+        console.put_string(&mut canvas, &format!("line {}\n", i));
+    }
+
+    let mut repainted = MemoryCanvas::new(XY::new(800, 450), BG);
+    console.flush(&mut repainted);
+    assert!(canvas.pixels() == repainted.pixels());
+}
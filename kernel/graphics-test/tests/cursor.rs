@@ -0,0 +1,84 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    cursor::{Cursor, CursorShape},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+
+const BLACK: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+const WHITE: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+
+/// A canvas whose every pixel has a different color, so that restoring the
+/// wrong pixel shows up.
+fn patterned() -> MemoryCanvas {
+    let mut canvas = MemoryCanvas::new(XY::new(64, 48), BLACK);
+    for y in 0..48 {
+        for x in 0..64 {
+            canvas.draw_pixel(XY::new(x, y), PixelColor { r: x as u8 * 3, g: y as u8 * 5, b: 7 });
+        }
+    }
+    canvas
+}
+
+#[test]
+fn arrow_is_drawn_at_its_hotspot() {
+    let mut canvas = patterned();
+    let mut cursor = Cursor::new(CursorShape::Arrow, XY::new(10, 20));
+    cursor.show(&mut canvas);
+    assert_eq!(cursor.rect(), Rect::new(10, 20, 12, 19));
+    assert_eq!(canvas.pixel(XY::new(10, 20)), BLACK);
+    assert_eq!(canvas.pixel(XY::new(11, 22)), WHITE);
+    // transparent pixels of the sprite leave the screen alone
+    assert_eq!(canvas.pixel(XY::new(15, 20)), patterned().pixel(XY::new(15, 20)));
+}
+
+#[test]
+fn save_under_restores_the_screen() {
+    let mut canvas = patterned();
+    let mut cursor = Cursor::new(CursorShape::Arrow, XY::new(10, 20));
+    cursor.show(&mut canvas);
+    cursor.move_to(&mut canvas, XY::new(14, 25));
+    assert_eq!(canvas.pixel(XY::new(10, 20)), patterned().pixel(XY::new(10, 20)));
+    cursor.set_shape(&mut canvas, CursorShape::Busy);
+    cursor.hide(&mut canvas);
+    assert!(canvas.pixels() == patterned().pixels());
+}
+
+#[test]
+fn clamped_to_the_screen() {
+    let mut canvas = patterned();
+    let mut cursor = Cursor::new(CursorShape::TextBeam, XY::new(30, 30));
+    cursor.show(&mut canvas);
+    assert_eq!(cursor.move_to(&mut canvas, XY::new(100, -5)), XY::new(63, 0));
+    assert_eq!(cursor.move_to(&mut canvas, XY::new(-3, 60)), XY::new(0, 47));
+    cursor.hide(&mut canvas);
+    assert!(canvas.pixels() == patterned().pixels());
+}
+
+#[test]
+fn drawing_below_while_hidden() {
+    let mut canvas = patterned();
+    let mut cursor = Cursor::new(CursorShape::ResizeHorizontal, XY::new(20, 20));
+    cursor.show(&mut canvas);
+    cursor.hidden(&mut canvas, |w| w.fill_rect(Rect::new(0, 0, 40, 40), WHITE));
+    assert!(cursor.is_visible());
+    // the sprite is back on top, its outline above the hot spot
+    assert_eq!(canvas.pixel(XY::new(20, 19)), BLACK);
+    assert_eq!(canvas.pixel(XY::new(20, 16)), WHITE);
+    // what the cursor restores is what was drawn below it
+    cursor.hide(&mut canvas);
+    assert_eq!(canvas.pixel(XY::new(20, 19)), WHITE);
+}
+
+#[test]
+fn all_shapes_fit_their_hotspot() {
+    for shape in [CursorShape::Arrow, CursorShape::TextBeam, CursorShape::ResizeHorizontal,
+        CursorShape::ResizeVertical, CursorShape::Busy]
+    {
+        let cursor = Cursor::new(shape, XY::new(0, 0));
+        // with the hot spot at the origin, the sprite starts at minus the hot spot
+        let rect = cursor.rect();
+        assert!(Rect::new(0, 0, rect.w, rect.h).is_contained(XY::new(-rect.x, -rect.y)), "{:?}", shape);
+    }
+}
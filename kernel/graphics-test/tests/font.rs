@@ -0,0 +1,110 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    font::{char_width, Font, Glyph, GlyphSet, Hankaku},
+};
+
+const HANKAKU: &[u8] = include_bytes!("../../resources/hankaku.bin");
+static HANKAKU_FONT: Hankaku = Hankaku::new(HANKAKU);
+const FG: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const BG: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+const RED: PixelColor = PixelColor { r: 255, g: 0, b: 0 };
+
+/// Renders `c` at `pos` and returns its 8x16 bitmap read back from the canvas.
+fn render(c: char, pos: XY<usize>) -> ([u8; 16], MemoryCanvas) {
+    let mut canvas = MemoryCanvas::new(XY::new(32, 32), RED);
+    Font::new(&HANKAKU_FONT).draw_char(&mut canvas, pos, FG, BG, c);
+    let mut rows = [0; 16];
+    for (dy, row) in rows.iter_mut().enumerate() {
+        for dx in 0..8 {
+            match canvas.pixel(XY::new(pos.x + dx, pos.y + dy)) {
+                FG => *row |= 0x80 >> dx,
+                BG => {}
+                other => panic!("unexpected color {:?} at ({}, {})", other, dx, dy),
+            }
+        }
+    }
+    (rows, canvas)
+}
+
+#[test]
+fn draws_glyph_from_bitmap() {
+    let (rows, _) = render('A', XY::new(0, 0));
+    let offset = 'A' as usize * 16;
+    assert_eq!(rows, HANKAKU[offset..offset + 16]);
+    assert!(rows.iter().any(|&row| row != 0));
+}
+
+#[test]
+fn space_is_blank() {
+    let (rows, _) = render(' ', XY::new(0, 0));
+    assert_eq!(rows, [0; 16]);
+}
+
+#[test]
+fn draws_only_inside_glyph_cell() {
+    let pos = XY::new(5, 7);
+    let (_, canvas) = render('#', pos);
+    for y in 0..32 {
+        for x in 0..32 {
+            let inside = (pos.x..pos.x + 8).contains(&x) && (pos.y..pos.y + 16).contains(&y);
+            assert_eq!(canvas.pixel(XY::new(x, y)) != RED, inside, "pixel ({}, {})", x, y);
+        }
+    }
+}
+
+#[test]
+fn char_size_includes_spacing() {
+    assert_eq!(Font::new(&HANKAKU_FONT).char_size(), XY::new(10, 18));
+}
+
+#[test]
+fn half_width_katakana() {
+    // U+FF71 HALFWIDTH KATAKANA LETTER A is 0xb1 in JIS X 0201
+    let (rows, _) = render('ｱ', XY::new(0, 0));
+    assert_eq!(rows, HANKAKU[0xb1 * 16..0xb2 * 16]);
+}
+
+#[test]
+fn missing_glyphs_draw_a_box() {
+    // beyond the 256 glyphs of the table, which used to be read out of bounds
+    for c in ['\u{100}', '\u{10ffff}'] {
+        let (rows, _) = render(c, XY::new(0, 0));
+        assert_eq!(rows[2], 0b0111_1110);
+        assert_eq!(rows[7], 0b0100_0010);
+        assert_eq!(rows[0], 0);
+    }
+}
+
+/// A font without any glyphs, of a size given in pixels.
+struct Empty(XY<usize>);
+
+impl GlyphSet for Empty {
+    fn glyph_size(&self) -> XY<usize> {
+        self.0
+    }
+    fn glyph(&self, _c: char) -> Option<Glyph<'_>> {
+        None
+    }
+}
+
+#[test]
+fn missing_glyphs_of_tiny_fonts_stay_blank() {
+    for size in [XY::new(0, 0), XY::new(1, 1), XY::new(2, 16), XY::new(8, 3), XY::new(3, 5)] {
+        let font = Empty(size);
+        let mut canvas = MemoryCanvas::new(XY::new(8, 16), RED);
+        Font::new(&font).draw_char(&mut canvas, XY::new(0, 0), FG, BG, 'a');
+        let box_drawn = canvas.pixels().contains(&FG);
+        assert_eq!(box_drawn, size == XY::new(3, 5), "glyph size {:?}", size);
+    }
+}
+
+#[test]
+fn wide_characters() {
+    assert_eq!(char_width('a'), 1);
+    assert_eq!(char_width('ｱ'), 1);
+    assert_eq!(char_width('あ'), 2);
+    assert_eq!(char_width('漢'), 2);
+    assert_eq!(char_width('Ａ'), 2);
+    assert_eq!(char_width('한'), 2);
+}
@@ -0,0 +1,210 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    color::{BlendMode, Rgba},
+    common::{PixelColor, XY},
+    image::{Image, ImageError},
+    splash,
+};
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+const RED: Rgba = Rgba::new(255, 0, 0, 255);
+const GREEN: Rgba = Rgba::new(0, 255, 0, 255);
+const BLUE: Rgba = Rgba::new(0, 0, 255, 255);
+const WHITE: Rgba = Rgba::new(255, 255, 255, 255);
+
+fn png(width: u32, height: u32, bit_depth: u8, color_type: u8, interlace: u8,
+    extra: &[(&[u8; 4], Vec<u8>)], raw: &[u8]) -> Vec<u8>
+{
+    let chunk = |kind: &[u8; 4], body: &[u8]| {
+        let mut chunk = (body.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(kind);
+        chunk.extend_from_slice(body);
+        chunk.extend_from_slice(&[0; 4]); // CRCs are not checked
+        chunk
+    };
+    let mut header = width.to_be_bytes().to_vec();
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[bit_depth, color_type, 0, 0, interlace]);
+    let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+    data.extend(chunk(b"IHDR", &header));
+    for (kind, body) in extra {
+        data.extend(chunk(kind, body));
+    }
+    data.extend(chunk(b"tEXt", b"Comment\0ignored"));
+    data.extend(chunk(b"IDAT", &compress_to_vec_zlib(raw, 6)));
+    data.extend(chunk(b"IEND", &[]));
+    data
+}
+
+#[test]
+fn png_truecolor_with_filters() {
+    // one RGBA row per filter type: none, sub, up, average, paeth
+    let raw = [
+        &[0, 10, 20, 30, 255, 40, 50, 60, 255][..],
+        &[1, 10, 20, 30, 255, 30, 30, 30, 0],
+        &[2, 1, 1, 1, 0, 1, 1, 1, 0],
+        &[3, 6, 11, 16, 128, 3, 3, 3, 0],
+        &[4, 1, 1, 1, 0, 0, 0, 0, 0],
+    ].concat();
+    let image = Image::decode(&png(2, 5, 8, 6, 0, &[], &raw)).unwrap();
+    assert_eq!(image.size(), XY::new(2, 5));
+    assert_eq!(image.pixels(), [
+        Rgba::new(10, 20, 30, 255), Rgba::new(40, 50, 60, 255),
+        Rgba::new(10, 20, 30, 255), Rgba::new(40, 50, 60, 255),
+        Rgba::new(11, 21, 31, 255), Rgba::new(41, 51, 61, 255),
+        // average of left and up: (0 + 11) / 2 + 6 = 11, then (11 + 41) / 2 + 3 = 29
+        Rgba::new(11, 21, 31, 255), Rgba::new(29, 39, 49, 255),
+        // paeth: up for the first pixel, then the closest of left, up and up-left
+        Rgba::new(12, 22, 32, 255), Rgba::new(29, 39, 49, 255),
+    ]);
+}
+
+#[test]
+fn png_palette_with_transparency() {
+    let palette = vec![255, 0, 0, 0, 0, 255];
+    let trns = vec![128];
+    // 1-bit indices 1, 0, 1 packed from the most significant bit
+    let image = Image::decode(&png(3, 1, 1, 3, 0, &[(b"PLTE", palette), (b"tRNS", trns)], &[0, 0b1010_0000])).unwrap();
+    assert_eq!(image.pixels(), [BLUE, RED.with_alpha(128), BLUE]);
+}
+
+#[test]
+fn png_gray() {
+    // 16-bit gray, with 0x1234 transparent
+    let raw = [0, 0xff, 0xff, 0x12, 0x34, 0x80, 0x00];
+    let image = Image::decode(&png(3, 1, 16, 0, 0, &[(b"tRNS", vec![0x12, 0x34])], &raw)).unwrap();
+    assert_eq!(image.pixels(), [WHITE, Rgba::new(0x12, 0x12, 0x12, 0), Rgba::new(0x80, 0x80, 0x80, 255)]);
+    // 2-bit gray scales to 0, 85, 170, 255
+    let image = Image::decode(&png(4, 1, 2, 0, 0, &[], &[0, 0b00_01_10_11])).unwrap();
+    let levels: Vec<u8> = image.pixels().iter().map(|p| p.r).collect();
+    assert_eq!(levels, [0, 85, 170, 255]);
+}
+
+#[test]
+fn png_interlaced_matches_plain() {
+    let (w, h) = (10usize, 9usize);
+    let value = |x: usize, y: usize| (y * w + x) as u8;
+    let plain: Vec<u8> = (0..h).flat_map(|y| std::iter::once(0).chain((0..w).map(move |x| value(x, y)))).collect();
+    let mut interlaced = Vec::new();
+    for (x0, y0, dx, dy) in [(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)] {
+        for y in (y0..h).step_by(dy) {
+            let row: Vec<u8> = (x0..w).step_by(dx).map(|x| value(x, y)).collect();
+            if !row.is_empty() {
+                interlaced.push(0);
+                interlaced.extend(row);
+            }
+        }
+    }
+    let plain = Image::decode(&png(w as u32, h as u32, 8, 0, 0, &[], &plain)).unwrap();
+    let interlaced = Image::decode(&png(w as u32, h as u32, 8, 0, 1, &[], &interlaced)).unwrap();
+    assert_eq!(interlaced, plain);
+}
+
+#[test]
+fn png_errors() {
+    let data = png(2, 1, 8, 2, 0, &[], &[0, 1, 2, 3]);
+    assert_eq!(Image::decode(&data), Err(ImageError::Truncated));
+    assert_eq!(Image::decode(&data[..20]), Err(ImageError::Truncated));
+    assert!(matches!(Image::decode(&png(1, 1, 3, 2, 0, &[], &[0])), Err(ImageError::Malformed(_))));
+    assert_eq!(Image::decode(&png(100_000, 100_000, 8, 0, 0, &[], &[])), Err(ImageError::TooLarge));
+    assert_eq!(Image::decode(&png(1024, 1024, 8, 0, 0, &[], &[])), Err(ImageError::TooLarge));
+    assert_eq!(Image::decode(b"GIF89a"), Err(ImageError::UnknownFormat));
+}
+
+fn bmp(width: i32, height: i32, bits_per_pixel: u16, compression: u32, extra: &[u8], pixels: &[u8]) -> Vec<u8> {
+    let offset = 14 + 40 + extra.len();
+    let mut data = b"BM".to_vec();
+    data.extend(((offset + pixels.len()) as u32).to_le_bytes());
+    data.extend([0; 4]);
+    data.extend((offset as u32).to_le_bytes());
+    data.extend(40u32.to_le_bytes());
+    data.extend(width.to_le_bytes());
+    data.extend(height.to_le_bytes());
+    data.extend(1u16.to_le_bytes());
+    data.extend(bits_per_pixel.to_le_bytes());
+    data.extend(compression.to_le_bytes());
+    data.extend([0; 20]);
+    data.extend(extra);
+    data.extend(pixels);
+    data
+}
+
+#[test]
+fn bmp_24_bit_bottom_up() {
+    // rows of 2 BGR pixels padded to 8 bytes, the bottom row first
+    let pixels = [0, 0, 255, 0, 255, 0, 0, 0, 255, 0, 0, 255, 255, 255, 0, 0];
+    let image = Image::decode(&bmp(2, 2, 24, 0, &[], &pixels)).unwrap();
+    assert_eq!(image.pixels(), [BLUE, WHITE, RED, GREEN]);
+}
+
+#[test]
+fn bmp_palette() {
+    let palette = [0, 0, 255, 0, 255, 0, 0, 0];
+    // 4-bit indices 1 0 1 in a top-down row
+    let image = Image::decode(&bmp(3, -1, 4, 0, &palette, &[0x10, 0x10, 0, 0])).unwrap();
+    assert_eq!(image.pixels(), [BLUE, RED, BLUE]);
+    let bad_index = bmp(1, 1, 4, 0, &palette, &[0x20, 0, 0, 0]);
+    assert!(matches!(Image::decode(&bad_index), Err(ImageError::Malformed(_))));
+}
+
+#[test]
+fn bmp_bitfields() {
+    // 16-bit 5-6-5 after the info header
+    let masks: Vec<u8> = [0xf800u32, 0x07e0, 0x001f].iter().flat_map(|m| m.to_le_bytes()).collect();
+    let image = Image::decode(&bmp(2, 1, 16, 3, &masks, &[0x00, 0xf8, 0xe0, 0x07])).unwrap();
+    assert_eq!(image.pixels(), [RED, GREEN]);
+    // 32-bit with alpha
+    let masks: Vec<u8> = [0xffu32, 0xff00, 0xff_0000, 0xff00_0000].iter().flat_map(|m| m.to_le_bytes()).collect();
+    let image = Image::decode(&bmp(1, 1, 32, 6, &masks, &[10, 20, 30, 40])).unwrap();
+    assert_eq!(image.pixels(), [Rgba::new(10, 20, 30, 40)]);
+}
+
+#[test]
+fn qoi_ops() {
+    let mut data = b"qoif".to_vec();
+    data.extend(3u32.to_be_bytes());
+    data.extend(3u32.to_be_bytes());
+    data.extend([4, 0]);
+    data.extend([
+        0xfe, 100, 100, 100, // RGB
+        0x40 | 3 << 4 | 1 << 2 | 2, // DIFF +1 -1 +0
+        0x80 | 40, 0x80, // LUMA dg +8, dr-dg 0, db-dg -8
+        0xc0 | 1, // RUN of 2
+        0xff, 1, 2, 3, 4, // RGBA
+        hash(100, 100, 100, 255) as u8, // INDEX
+        0xc0 | 1, // RUN beyond the end is cut off
+    ]);
+    data.extend([0, 0, 0, 0, 0, 0, 0, 1]);
+    let image = Image::decode(&data).unwrap();
+    assert_eq!(image.pixels(), [
+        Rgba::new(100, 100, 100, 255),
+        Rgba::new(101, 99, 100, 255),
+        Rgba::new(109, 107, 100, 255),
+        Rgba::new(109, 107, 100, 255),
+        Rgba::new(109, 107, 100, 255),
+        Rgba::new(1, 2, 3, 4),
+        Rgba::new(100, 100, 100, 255),
+        Rgba::new(100, 100, 100, 255),
+        Rgba::new(100, 100, 100, 255),
+    ]);
+    assert_eq!(Image::decode(&data[..20]), Err(ImageError::Truncated));
+}
+
+fn hash(r: usize, g: usize, b: usize, a: usize) -> usize {
+    (r * 3 + g * 5 + b * 7 + a * 11) % 64
+}
+
+#[test]
+fn splash_is_centered() {
+    let mut canvas = MemoryCanvas::new(XY::new(400, 200), PixelColor { r: 1, g: 2, b: 3 });
+    splash::draw(&mut canvas).unwrap();
+    let background = canvas.pixel(XY::new(0, 0));
+    assert_ne!(background, PixelColor { r: 1, g: 2, b: 3 });
+
+    let image = Image::decode(splash::SPLASH_PNG).unwrap();
+    let origin = XY::new((400 - image.size().x) / 2, (200 - image.size().y) / 2);
+    for pos in [XY::new(0, 0), XY::new(image.size().x / 2, image.size().y / 2)] {
+        let expected = BlendMode::SourceOver.blend(image.pixel(pos).premultiply(), background);
+        assert_eq!(canvas.pixel(XY::new(origin.x + pos.x, origin.y + pos.y)), expected);
+    }
+}
@@ -0,0 +1,70 @@
+use graphics_test::graphics::{
+    common::PixelColor,
+    pixel_format::{PixelEncoder, PixelFormat},
+};
+
+const ORANGE: PixelColor = PixelColor { r: 255, g: 128, b: 0 };
+
+fn encode(format: PixelFormat, bytes_per_pixel: usize, color: PixelColor) -> Vec<u8> {
+    PixelEncoder::new(format, bytes_per_pixel).unwrap().encode(color).bytes().to_vec()
+}
+
+#[test]
+fn byte_orders() {
+    assert_eq!(encode(PixelFormat::RGB, 4, ORANGE), [255, 128, 0, 0]);
+    assert_eq!(encode(PixelFormat::BGR, 4, ORANGE), [0, 128, 255, 0]);
+    assert_eq!(encode(PixelFormat::BGR, 3, ORANGE), [0, 128, 255]);
+}
+
+#[test]
+fn gray() {
+    assert_eq!(encode(PixelFormat::U8, 1, PixelColor { r: 255, g: 255, b: 255 }), [255]);
+    assert_eq!(encode(PixelFormat::U8, 1, ORANGE), [151]);
+    // padding bytes stay zero
+    assert_eq!(encode(PixelFormat::U8, 2, ORANGE), [151, 0]);
+}
+
+#[test]
+fn bitmasks() {
+    // 5-6-5: red 11111, green 100000, blue 00000
+    assert_eq!(encode(PixelFormat::RGB565, 2, ORANGE), 0xfc00u16.to_le_bytes());
+    // 10 bits per channel as reported by some GOP implementations
+    let rgb30 = PixelFormat::Bitmask { red: 0x3ff0_0000, green: 0x000f_fc00, blue: 0x0000_03ff };
+    assert_eq!(encode(rgb30, 4, ORANGE), (0x3ff0_0000u32 | 514 << 10).to_le_bytes());
+}
+
+#[test]
+fn decode_round_trips() {
+    for (format, len) in [(PixelFormat::RGB, 4), (PixelFormat::BGR, 3)] {
+        let encoder = PixelEncoder::new(format, len).unwrap();
+        assert_eq!(encoder.decode(encoder.encode(ORANGE).bytes()), ORANGE, "{:?}", format);
+    }
+    // fewer bits keep the extremes but round what is between
+    let encoder = PixelEncoder::new(PixelFormat::RGB565, 2).unwrap();
+    let white = PixelColor { r: 255, g: 255, b: 255 };
+    assert_eq!(encoder.decode(encoder.encode(white).bytes()), white);
+    assert_eq!(encoder.decode(encoder.encode(ORANGE).bytes()), PixelColor { r: 255, g: 130, b: 0 });
+}
+
+#[test]
+fn full_width_channels() {
+    let encoder = PixelEncoder::new(PixelFormat::Gray { mask: u32::MAX }, 4).unwrap();
+    let white = PixelColor { r: 255, g: 255, b: 255 };
+    assert_eq!(encoder.encode(white).bytes(), u32::MAX.to_le_bytes());
+    assert_eq!(encoder.decode(&u32::MAX.to_le_bytes()), white);
+    assert_eq!(encoder.decode(&[0; 4]), PixelColor { r: 0, g: 0, b: 0 });
+}
+
+#[test]
+fn rejects_non_contiguous_masks() {
+    let split_red = PixelFormat::Bitmask { red: 0xf0_000f, green: 0xff00, blue: 0x0f_00f0 };
+    assert!(PixelEncoder::new(split_red, 4).is_none());
+    assert!(PixelEncoder::new(PixelFormat::Gray { mask: 0x8000_0001 }, 4).is_none());
+}
+
+#[test]
+fn rejects_masks_wider_than_the_pixel() {
+    assert!(PixelEncoder::new(PixelFormat::RGB, 2).is_none());
+    assert!(PixelEncoder::new(PixelFormat::U8, 0).is_none());
+    assert!(PixelEncoder::new(PixelFormat::RGB, 8).is_none());
+}
@@ -0,0 +1,103 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+
+const BLACK: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+const WHITE: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+
+/// Rows of the canvas with `#` for white and `.` for other pixels.
+fn picture(canvas: &MemoryCanvas) -> Vec<String> {
+    canvas.pixels()
+        .chunks(canvas.size().x)
+        .map(|row| row.iter().map(|&p| if p == WHITE { '#' } else { '.' }).collect())
+        .collect()
+}
+
+#[test]
+fn fill_rect_clips_to_bounds() {
+    let mut canvas = MemoryCanvas::new(XY::new(5, 4), BLACK);
+    canvas.fill_rect(Rect::new(-2, 2, 4, 10), WHITE);
+    canvas.fill_rect(Rect::new(4, -1, 3, 2), WHITE);
+    canvas.fill_rect(Rect::new(9, 9, 3, 3), WHITE);
+    assert_eq!(picture(&canvas), ["....#", ".....", "##...", "##..."]);
+}
+
+#[test]
+fn draw_rect() {
+    let mut canvas = MemoryCanvas::new(XY::new(6, 5), BLACK);
+    canvas.draw_rect(Rect::new(1, 1, 4, 3), WHITE);
+    assert_eq!(picture(&canvas), ["......", ".####.", ".#..#.", ".####.", "......"]);
+}
+
+#[test]
+fn draw_line() {
+    let mut canvas = MemoryCanvas::new(XY::new(6, 4), BLACK);
+    canvas.draw_line(XY::new(0, 0), XY::new(5, 2), WHITE);
+    assert_eq!(picture(&canvas), ["##....", "..##..", "....##", "......"]);
+
+    // the same pixels in either direction, and off-canvas parts are clipped
+    let mut reversed = MemoryCanvas::new(XY::new(6, 4), BLACK);
+    reversed.draw_line(XY::new(5, 2), XY::new(0, 0), WHITE);
+    assert_eq!(picture(&reversed), picture(&canvas));
+    let mut clipped = MemoryCanvas::new(XY::new(3, 3), BLACK);
+    clipped.draw_line(XY::new(-2, -2), XY::new(10, 10), WHITE);
+    clipped.draw_line(XY::new(2, 5), XY::new(2, -5), WHITE);
+    assert_eq!(picture(&clipped), ["#.#", ".##", "..#"]);
+}
+
+#[test]
+fn circles() {
+    let mut canvas = MemoryCanvas::new(XY::new(7, 7), BLACK);
+    canvas.draw_circle(XY::new(3, 3), 3, WHITE);
+    assert_eq!(picture(&canvas), [
+        "..###..",
+        ".#...#.",
+        "#.....#",
+        "#.....#",
+        "#.....#",
+        ".#...#.",
+        "..###..",
+    ]);
+    let mut filled = MemoryCanvas::new(XY::new(7, 7), BLACK);
+    filled.fill_circle(XY::new(3, 3), 3, WHITE);
+    assert_eq!(picture(&filled), [
+        "..###..",
+        ".#####.",
+        "#######",
+        "#######",
+        "#######",
+        ".#####.",
+        "..###..",
+    ]);
+    // partly outside the canvas
+    let mut clipped = MemoryCanvas::new(XY::new(3, 3), BLACK);
+    clipped.fill_circle(XY::new(0, 0), 3, WHITE);
+    assert_eq!(picture(&clipped), ["###", "###", "###"]);
+}
+
+#[test]
+fn rounded_rects() {
+    let mut canvas = MemoryCanvas::new(XY::new(8, 5), BLACK);
+    canvas.draw_rounded_rect(Rect::new(0, 0, 8, 5), 1, WHITE);
+    assert_eq!(picture(&canvas), [".######.", "#......#", "#......#", "#......#", ".######."]);
+    let mut filled = MemoryCanvas::new(XY::new(8, 5), BLACK);
+    // the radius is clamped to half the height
+    filled.fill_rounded_rect(Rect::new(0, 0, 8, 5), 9, WHITE);
+    assert_eq!(picture(&filled), [".######.", "########", "########", "########", ".######."]);
+}
+
+#[test]
+fn blit_copies_the_visible_part() {
+    let source: Vec<PixelColor> = (0..6).map(|i| PixelColor { r: i, g: 0, b: 0 }).collect();
+    let mut canvas = MemoryCanvas::new(XY::new(3, 2), BLACK);
+    canvas.blit(XY::new(-1, 1), XY::new(3, 2), &source);
+    assert_eq!(canvas.pixels()[..3], [BLACK; 3]);
+    assert_eq!(canvas.pixels()[3..].iter().map(|p| p.r).collect::<Vec<_>>(), [1, 2, 0]);
+    // entirely outside
+    canvas.blit(XY::new(3, 0), XY::new(3, 2), &source);
+    canvas.blit(XY::new(0, -2), XY::new(3, 2), &source);
+    assert_eq!(canvas.pixels()[..3], [BLACK; 3]);
+}
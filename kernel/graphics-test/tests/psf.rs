@@ -0,0 +1,120 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    console::Console,
+    font::{Font, FontError, GlyphSet},
+    frame_buffer::PixelWriter,
+    psf::PsfFont,
+};
+
+/// A 256-glyph PSF1 font of height 8 where glyph `i` is filled with byte `i`,
+/// with a Unicode table mapping "A" and "Å" to glyph 1 and a combining
+/// sequence to glyph 2.
+fn psf1(with_table: bool) -> Vec<u8> {
+    let mut data = vec![0x36, 0x04, if with_table { 0x02 } else { 0x00 }, 8];
+    for i in 0..256 {
+        data.extend([i as u8; 8]);
+    }
+    if with_table {
+        for i in 0..256u16 {
+            let entries: &[u16] = match i {
+                1 => &[0x41, 0xc5],
+                2 => &[0xfffe, 0x41, 0x30a],
+                _ => &[],
+            };
+            for unit in entries.iter().chain(&[0xffff]) {
+                data.extend(unit.to_le_bytes());
+            }
+        }
+    }
+    data
+}
+
+/// A PSF2 font of three 12x24 glyphs filled with byte `i + 1`, with a
+/// Unicode table mapping "a" and "é" to glyph 0 and "漢" to glyph 2.
+fn psf2(with_table: bool) -> Vec<u8> {
+    let bytes_per_glyph = 24 * 2;
+    let mut data = vec![0x72, 0xb5, 0x4a, 0x86];
+    for field in [0, 32, with_table as u32, 3, bytes_per_glyph, 24, 12] {
+        data.extend(field.to_le_bytes());
+    }
+    for i in 0..3 {
+        data.extend(vec![i as u8 + 1; bytes_per_glyph as usize]);
+    }
+    if with_table {
+        data.extend("aé".as_bytes());
+        data.extend([0xfe, b'e', 0xcc, 0x81, 0xff, 0xff]);
+        data.extend("漢".as_bytes());
+        data.push(0xff);
+    }
+    data
+}
+
+#[test]
+fn psf1_with_unicode_table() {
+    let data = psf1(true);
+    let font = PsfFont::parse(&data).unwrap();
+    assert_eq!(font.glyph_size(), XY::new(8, 8));
+    assert_eq!(font.glyph_count(), 256);
+    assert_eq!(font.glyph('A').unwrap().bitmap, [1; 8]);
+    assert_eq!(font.glyph('Å').unwrap().bitmap, [1; 8]);
+    // characters only listed in sequences and unlisted ones have no glyph
+    assert!(font.glyph('\u{30a}').is_none());
+    assert!(font.glyph('B').is_none());
+}
+
+#[test]
+fn psf1_without_table_is_indexed_by_code_point() {
+    let data = psf1(false);
+    let font = PsfFont::parse(&data).unwrap();
+    assert_eq!(font.glyph('B').unwrap().bitmap, [b'B'; 8]);
+    assert!(font.glyph('\u{100}').is_none());
+}
+
+#[test]
+fn psf2_with_unicode_table() {
+    let data = psf2(true);
+    let font = PsfFont::parse(&data).unwrap();
+    assert_eq!(font.glyph_size(), XY::new(12, 24));
+    assert_eq!(font.glyph('a').unwrap().bitmap, [1; 48]);
+    assert_eq!(font.glyph('é').unwrap().bitmap, [1; 48]);
+    assert!(font.glyph('e').is_none());
+    assert_eq!(font.glyph('漢').unwrap().bitmap, [3; 48]);
+}
+
+#[test]
+fn psf2_without_table() {
+    let data = psf2(false);
+    let font = PsfFont::parse(&data).unwrap();
+    assert_eq!(font.glyph('\u{2}').unwrap().bitmap, [3; 48]);
+    assert!(font.glyph('\u{3}').is_none());
+}
+
+#[test]
+fn rejects_malformed_fonts() {
+    assert_eq!(PsfFont::parse(b"BM not a font").err(), Some(FontError::UnknownFormat));
+    let data = psf1(true);
+    assert_eq!(PsfFont::parse(&data[..100]).err(), Some(FontError::Truncated));
+    assert_eq!(PsfFont::parse(&data[..data.len() - 2]).err(), Some(FontError::Truncated));
+    let data = psf2(true);
+    assert_eq!(PsfFont::parse(&data[..40]).err(), Some(FontError::Truncated));
+}
+
+#[test]
+fn char_size_follows_the_font() {
+    let data = psf2(false);
+    let font = PsfFont::parse(&data).unwrap();
+    let font = Font::new(&font);
+    assert_eq!(font.char_size(), XY::new(12, 24));
+    assert_eq!(Console::new(font, XY::new(1920, 1080)).size(), XY::new(160, 45));
+}
+
+#[test]
+fn console_draws_within_cells_without_spacing() {
+    let data = psf2(false);
+    let font = PsfFont::parse(&data).unwrap();
+    let mut canvas = MemoryCanvas::new(XY::new(36, 48), PixelColor { r: 1, g: 2, b: 3 });
+    let mut console = Console::new(Font::new(&font), canvas.size());
+    console.put_string(&mut canvas, "\x1b[4m\u{1}\u{1}\u{1}\u{1}\u{1}\u{1}");
+    assert!(canvas.pixels().iter().all(|&p| p.r == p.g && p.g == p.b));
+}
@@ -0,0 +1,29 @@
+use graphics_test::graphics::{common::XY, rect::Rect};
+
+#[test]
+fn intersection() {
+    let a = Rect::new(0, 0, 10, 10);
+    assert_eq!(a.intersection(Rect::new(5, -3, 10, 5)), Some(Rect::new(5, 0, 5, 2)));
+    assert_eq!(a.intersection(Rect::new(2, 2, 3, 3)), Some(Rect::new(2, 2, 3, 3)));
+    // touching edges do not overlap
+    assert_eq!(a.intersection(Rect::new(10, 0, 5, 5)), None);
+    assert_eq!(a.intersection(Rect::new(3, 3, 0, 4)), None);
+}
+
+#[test]
+fn union() {
+    let a = Rect::new(0, 0, 4, 4);
+    assert_eq!(a.union(Rect::new(6, -2, 2, 2)), Rect::new(0, -2, 8, 6));
+    assert_eq!(a.union(Rect::new(100, 100, 0, 0)), a);
+    assert_eq!(Rect::new(-5, -5, 0, 3).union(a), a);
+}
+
+#[test]
+fn clip_and_translate() {
+    let size = XY::new(8, 6);
+    assert_eq!(Rect::new(-2, 4, 5, 5).clip(size), Some(Rect::new(0, 4, 3, 2)));
+    assert_eq!(Rect::new(8, 0, 5, 5).clip(size), None);
+    let r = Rect::new(1, 2, 3, 4).translate(XY::new(-3, 5));
+    assert_eq!(r, Rect::new(-2, 7, 3, 4));
+    assert_eq!((r.right(), r.bottom()), (1, 11));
+}
@@ -0,0 +1,77 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    cell::Cell,
+    common::{PixelColor, XY},
+    console::Console,
+    font::{Font, Hankaku},
+};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+/// Pixels for a grid of `columns` x `rows` hankaku characters of 10x18 pixels.
+fn pixels(columns: usize, rows: usize) -> XY<usize> {
+    XY::new(columns * 10, rows * 18)
+}
+
+fn console(columns: usize, rows: usize, text: &str) -> Console<'static> {
+    let mut canvas = MemoryCanvas::new(pixels(columns, rows), PixelColor { r: 0, g: 0, b: 0 });
+    let mut console = Console::new(Font::new(&HANKAKU), pixels(columns, rows));
+    console.put_string(&mut canvas, text);
+    console
+}
+
+fn row_text(row: &[Cell]) -> String {
+    row.iter().filter(|cell| !cell.is_continuation()).map(|cell| cell.c).collect::<String>().trim_end().to_string()
+}
+
+fn screen(console: &Console) -> Vec<String> {
+    let size = console.size();
+    (0..size.y).map(|y| row_text(&(0..size.x).map(|x| console.cell(XY::new(x, y))).collect::<Vec<_>>())).collect()
+}
+
+fn history(console: &Console) -> Vec<String> {
+    console.history().map(row_text).collect()
+}
+
+#[test]
+fn wrapped_lines_are_joined_and_wrapped_again() {
+    let mut console = console(10, 3, "abcdefghijklmno");
+    assert_eq!(screen(&console), ["abcdefghij", "klmno", ""]);
+    assert_eq!(console.cursor(), XY::new(5, 1));
+
+    console.reflow(pixels(20, 3));
+    assert_eq!(screen(&console), ["abcdefghijklmno", "", ""]);
+    assert_eq!(console.cursor(), XY::new(15, 0));
+
+    // rows that no longer fit go to the history
+    console.reflow(pixels(4, 3));
+    assert_eq!(history(&console), ["abcd"]);
+    assert_eq!(screen(&console), ["efgh", "ijkl", "mno"]);
+    assert_eq!(console.cursor(), XY::new(3, 2));
+
+    console.reflow(pixels(20, 3));
+    assert!(history(&console).is_empty());
+    assert_eq!(screen(&console), ["abcdefghijklmno", "", ""]);
+}
+
+#[test]
+fn line_breaks_are_kept() {
+    let mut console = console(10, 4, "ab\ncd\n\nef");
+    console.reflow(pixels(1, 8));
+    assert_eq!(screen(&console), ["a", "b", "c", "d", "", "e", "f", ""]);
+    console.reflow(pixels(30, 4));
+    assert_eq!(screen(&console), ["ab", "cd", "", "ef"]);
+    assert_eq!(console.cursor(), XY::new(2, 3));
+}
+
+#[test]
+fn double_width_characters_stay_whole() {
+    let mut console = console(4, 2, "abcあい");
+    // the wide character did not fit into the last column
+    assert_eq!(screen(&console), ["abc", "あい"]);
+    console.reflow(pixels(7, 2));
+    assert_eq!(screen(&console), ["abcあい", ""]);
+    console.reflow(pixels(5, 2));
+    assert_eq!(screen(&console), ["abcあ", "い"]);
+    assert_eq!(console.cursor(), XY::new(2, 1));
+}
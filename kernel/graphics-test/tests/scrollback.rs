@@ -0,0 +1,120 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    console::Console,
+    font::{Font, Hankaku},
+};
+use graphics_test::input::{Key, KeyEvent, Modifiers};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+fn canvas() -> MemoryCanvas {
+    MemoryCanvas::new(XY::new(800, 450), PixelColor { r: 1, g: 2, b: 3 })
+}
+
+/// An 80x25 console that has printed `lines` numbered lines.
+fn console_with_lines(canvas: &mut MemoryCanvas, lines: usize) -> Console<'static> {
+    let mut console = Console::new(Font::new(&HANKAKU), XY::new(800, 450));
+    for i in 0..lines {
+        console.put_string(canvas, &format!("line {}\n", i));
+    }
+    console
+}
+
+fn row_text(row: &[graphics_test::graphics::cell::Cell]) -> String {
+    row.iter().map(|cell| cell.c).collect::<String>().trim_end().to_string()
+}
+
+fn page(console: &mut Console, canvas: &mut MemoryCanvas, key: Key) -> bool {
+    console.handle_key(canvas, KeyEvent::new(key, Modifiers::SHIFT))
+}
+
+#[test]
+fn keeps_rows_scrolled_off_the_top() {
+    let mut canvas = canvas();
+    let console = console_with_lines(&mut canvas, 30);
+    // 30 lines and the empty line the cursor is on leave 6 rows above the screen
+    let history: Vec<_> = console.history().map(row_text).collect();
+    assert_eq!(history, (0..6).map(|i| format!("line {}", i)).collect::<Vec<_>>());
+}
+
+#[test]
+fn scrollback_limit() {
+    let mut canvas = canvas();
+    let mut console = console_with_lines(&mut canvas, 30);
+    console.set_scrollback_limit(2);
+    assert_eq!(console.history().map(row_text).collect::<Vec<_>>(), ["line 4", "line 5"]);
+    console.put_string(&mut canvas, "\n");
+    assert_eq!(console.history().map(row_text).collect::<Vec<_>>(), ["line 5", "line 6"]);
+}
+
+#[test]
+fn scroll_region_does_not_feed_history() {
+    let mut canvas = canvas();
+    let mut console = console_with_lines(&mut canvas, 0);
+    console.put_string(&mut canvas, "\x1b[2;5r\x1b[5;1H\n\n\n");
+    assert_eq!(console.history().count(), 0);
+}
+
+#[test]
+fn dump_history() {
+    let mut canvas = canvas();
+    let console = console_with_lines(&mut canvas, 30);
+    let mut dump = String::new();
+    console.dump_history(&mut dump).unwrap();
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(lines.len(), 6 + 25);
+    assert_eq!(lines[0], "line 0");
+    assert_eq!(lines[29], "line 29");
+    assert_eq!(lines[30], "");
+}
+
+#[test]
+fn paging_with_shift() {
+    let mut canvas = canvas();
+    let mut console = console_with_lines(&mut canvas, 100);
+
+    assert!(!console.handle_key(&mut canvas, KeyEvent::new(Key::PageUp, Modifiers::NONE)));
+    assert_eq!(console.view_offset(), 0);
+    assert!(page(&mut console, &mut canvas, Key::PageUp));
+    assert_eq!(console.view_offset(), 12);
+    assert!(page(&mut console, &mut canvas, Key::PageDown));
+    assert!(page(&mut console, &mut canvas, Key::PageDown));
+    assert_eq!(console.view_offset(), 0);
+
+    for _ in 0..10 {
+        page(&mut console, &mut canvas, Key::PageUp);
+    }
+    assert_eq!(console.view_offset(), 76);
+
+    // typing returns to the bottom
+    assert!(!console.handle_key(&mut canvas, KeyEvent::new(Key::Char('a'), Modifiers::NONE)));
+    assert_eq!(console.view_offset(), 0);
+}
+
+/// Pixels of the left half of the top text row, away from the indicator.
+fn top_left(canvas: &MemoryCanvas) -> Vec<PixelColor> {
+    canvas.pixels().chunks(800).take(18).flat_map(|row| row[..400].to_vec()).collect()
+}
+
+#[test]
+fn view_shows_history_and_indicator() {
+    let mut screen = canvas();
+    let mut console = console_with_lines(&mut screen, 100);
+    let bottom = screen.pixels().to_vec();
+
+    page(&mut console, &mut screen, Key::PageUp);
+    assert!(screen.pixels() != bottom.as_slice());
+    let scrolled = top_left(&screen);
+
+    // the view stays on the same rows while output continues
+    console.put_string(&mut screen, "more\n");
+    assert_eq!(console.view_offset(), 13);
+    assert!(top_left(&screen) == scrolled);
+
+    console.scroll_to_bottom();
+    console.render(&mut screen);
+    let mut repainted = canvas();
+    console.flush(&mut repainted);
+    assert!(screen.pixels() == repainted.pixels());
+}
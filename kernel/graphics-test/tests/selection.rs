@@ -0,0 +1,55 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    console::Console,
+    font::{Font, Hankaku},
+};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+const BLACK: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+const WHITE: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+
+/// A 10x3 console of 10x18 pixel cells.
+fn setup(text: &str) -> (Console<'static>, MemoryCanvas) {
+    let size = XY::new(100, 54);
+    let mut canvas = MemoryCanvas::new(size, BLACK);
+    let mut console = Console::new(Font::new(&HANKAKU), size);
+    console.put_string(&mut canvas, text);
+    (console, canvas)
+}
+
+#[test]
+fn selected_text() {
+    let (mut console, _) = setup("hello world\nfoo");
+    assert_eq!(console.selected_text(), None);
+    // from the "w" on the first row to the "o" on the last, selected backwards
+    console.select(XY::new(1, 2), XY::new(6, 0));
+    assert_eq!(console.selection(), Some((XY::new(6, 0), XY::new(1, 2))));
+    // the wrapped line is joined, the other ends without its trailing blanks
+    assert_eq!(console.selected_text().as_deref(), Some("world\nfo"));
+    console.select(XY::new(2, 1), XY::new(4, 1));
+    assert_eq!(console.selected_text().as_deref(), Some(""));
+    console.clear_selection();
+    assert_eq!(console.selected_text(), None);
+}
+
+#[test]
+fn selection_is_drawn_reversed() {
+    let (mut console, mut canvas) = setup("hello");
+    // the spacing below the glyph of cell (1, 0) shows its background
+    let below = XY::new(15, 17);
+    assert_eq!(canvas.pixel(below), BLACK);
+    console.select(XY::new(0, 0), XY::new(2, 0));
+    console.render(&mut canvas);
+    assert_eq!(canvas.pixel(below), WHITE);
+    assert_eq!(canvas.pixel(XY::new(35, 17)), BLACK);
+
+    // scrolling moves the text away from the selection, which is dropped
+    console.put_string(&mut canvas, "\n\n\n");
+    assert_eq!(console.selection(), None);
+    console.select(XY::new(0, 0), XY::new(2, 0));
+    console.clear_selection();
+    console.render(&mut canvas);
+    assert_eq!(canvas.pixel(below), BLACK);
+}
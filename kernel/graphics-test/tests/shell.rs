@@ -0,0 +1,114 @@
+use graphics_test::{
+    clipboard,
+    graphics::common::XY,
+    input::{Key, KeyEvent, Modifiers},
+    shell::{Request, Shell},
+    terminal::FontKind,
+};
+
+fn key(key: Key) -> KeyEvent {
+    KeyEvent::new(key, Modifiers::NONE)
+}
+
+/// A started shell, with the greeting already taken out of the output.
+fn shell() -> Shell {
+    let mut shell = Shell::new(XY::new(128, 44));
+    shell.start(&mut String::new());
+    shell
+}
+
+/// Runs `line` and returns what the shell asked for and wrote before the next prompt.
+fn run(shell: &mut Shell, line: &str) -> (Option<Request>, String) {
+    let mut out = String::new();
+    let request = shell.type_text(&format!("{}\n", line), &mut out);
+    let output = out.strip_prefix(line).and_then(|rest| rest.strip_prefix('\n')).unwrap();
+    (request, output.strip_suffix("$ ").unwrap().to_string())
+}
+
+#[test]
+fn echoes_typed_characters() {
+    let mut shell = shell();
+    let mut out = String::new();
+    for c in "hi".chars() {
+        shell.handle_key(key(Key::Char(c)), &mut out);
+    }
+    // control characters and shortcuts are not part of the line
+    shell.handle_key(KeyEvent::new(Key::Char('c'), Modifiers::CTRL), &mut out);
+    shell.handle_key(key(Key::Char('\x07')), &mut out);
+    assert_eq!(out, "hi");
+}
+
+#[test]
+fn backspace_erases_whole_characters() {
+    let mut shell = shell();
+    let mut out = String::new();
+    shell.type_text("aあ", &mut out);
+    out.clear();
+
+    // a double-width character is backed over twice
+    shell.handle_key(key(Key::Backspace), &mut out);
+    assert_eq!(out, "\x08 \x08\x08 \x08");
+    out.clear();
+    shell.handle_key(key(Key::Backspace), &mut out);
+    assert_eq!(out, "\x08 \x08");
+    out.clear();
+    // nothing left to erase
+    shell.handle_key(key(Key::Backspace), &mut out);
+    assert_eq!(out, "");
+
+    shell.type_text("echo ok\n", &mut out);
+    assert_eq!(out, "echo ok\nok\n$ ");
+}
+
+#[test]
+fn type_text_runs_each_line() {
+    let mut shell = shell();
+    let mut out = String::new();
+    let request = shell.type_text("echo a\tb\recho c\nfont truetype\nech", &mut out);
+    assert_eq!(out, "echo a b\na b\n$ echo c\nc\n$ font truetype\n$ ech");
+    assert_eq!(request, Some(Request::Font(FontKind::TrueType)));
+    // the unfinished line is kept
+    assert_eq!(run(&mut shell, "o d").1, "d\n");
+}
+
+#[test]
+fn unknown_commands() {
+    let mut shell = shell();
+    assert_eq!(run(&mut shell, "frobnicate now"), (None, "frobnicate: command not found\n".to_string()));
+    assert_eq!(run(&mut shell, "   "), (None, String::new()));
+}
+
+#[test]
+fn font_arguments() {
+    let mut shell = shell();
+    assert_eq!(run(&mut shell, "font bitmap").0, Some(Request::Font(FontKind::Bitmap)));
+    assert_eq!(run(&mut shell, "font truetype").0, Some(Request::Font(FontKind::TrueType)));
+    for line in ["font", "font comic", "font bitmap truetype"] {
+        assert_eq!(run(&mut shell, line), (None, "font: expected bitmap or truetype\n".to_string()), "{}", line);
+    }
+}
+
+#[test]
+fn size_arguments() {
+    let mut shell = shell();
+    assert_eq!(run(&mut shell, "size 100 30").0, Some(Request::Resize(XY::new(100, 30))));
+    assert_eq!(run(&mut shell, "size 128 44").0, Some(Request::Resize(XY::new(128, 44))));
+
+    for line in ["size 0 30", "size 80 -1", "size eighty 24", "size 99999999999999999999999 1"] {
+        assert_eq!(run(&mut shell, line), (None, "size: expected two positive numbers\n".to_string()), "{}", line);
+    }
+    for line in ["size 129 44", "size 80 45", "size 1000 1000", "size 18446744073709551615 1"] {
+        assert_eq!(run(&mut shell, line), (None, "size: at most 128 44 fit on the screen\n".to_string()), "{}", line);
+    }
+
+    shell.set_max_size(XY::new(64, 26));
+    assert_eq!(run(&mut shell, "size 80 24").0, None);
+    assert_eq!(run(&mut shell, "size 64 26").0, Some(Request::Resize(XY::new(64, 26))));
+}
+
+#[test]
+fn clip_prints_the_clipboard() {
+    let mut shell = shell();
+    clipboard::copy("copied text");
+    assert_eq!(run(&mut shell, "clip"), (None, "copied text\n".to_string()));
+}
@@ -0,0 +1,101 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    font::{Font, Hankaku},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+    text_layout::{measure, Alignment, ScaledFont, TextFont, TextLayout},
+    truetype::{TrueTypeFont, ROBOTO_REGULAR},
+};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+// Hankaku characters advance 10 pixels and lines 18.
+const FG: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const BG: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+
+fn wrapped<'t>(font: &dyn TextFont, text: &'t str, width: usize) -> Vec<&'t str> {
+    let layout = TextLayout::new(font, text, Some(width));
+    layout.lines().iter().map(|line| layout.line_text(line)).collect()
+}
+
+/// The leftmost and rightmost column with a pixel in `FG`.
+fn ink_columns(canvas: &MemoryCanvas) -> (usize, usize) {
+    let size = canvas.size();
+    let columns: Vec<usize> = (0..size.x)
+        .filter(|&x| (0..size.y).any(|y| canvas.pixel(XY::new(x, y)) == FG))
+        .collect();
+    (columns[0], *columns.last().unwrap())
+}
+
+#[test]
+fn measures_lines() {
+    let font = Font::new(&HANKAKU);
+    assert_eq!(measure(&font, "abc"), XY::new(30, 18));
+    assert_eq!(measure(&font, "ab\nabcd"), XY::new(40, 36));
+    assert_eq!(measure(&font, "あ"), XY::new(20, 18));
+    assert_eq!(measure(&font, ""), XY::new(0, 18));
+}
+
+#[test]
+fn wraps_at_spaces() {
+    let font = Font::new(&HANKAKU);
+    assert_eq!(wrapped(&font, "the quick brown fox", 100), ["the quick", "brown fox"]);
+    assert_eq!(wrapped(&font, "a  b", 30), ["a", "b"]);
+    assert_eq!(wrapped(&font, "a  b", 40), ["a  b"]);
+    // words too long for a line are broken where they have to
+    assert_eq!(wrapped(&font, "abcdefghijklmno xy", 50), ["abcde", "fghij", "klmno", "xy"]);
+    assert_eq!(wrapped(&font, "abc", 5), ["a", "b", "c"]);
+    assert_eq!(wrapped(&font, "a\n\n  \nb c", 100), ["a", "", "", "b c"]);
+
+    let layout = TextLayout::new(&font, "one two three", Some(80));
+    assert_eq!(layout.size(), XY::new(70, 36));
+    assert_eq!(layout.lines()[1].range, 8..13);
+}
+
+#[test]
+fn aligns_lines() {
+    let mut font = Font::new(&HANKAKU);
+    let layout = TextLayout::new(&font, "HH", None);
+    let rect = Rect::new(0, 0, 100, 18);
+    for (alignment, start) in [(Alignment::Left, 0), (Alignment::Center, 40), (Alignment::Right, 80)] {
+        let mut canvas = MemoryCanvas::new(XY::new(100, 18), BG);
+        layout.draw(&mut font, &mut canvas, rect, alignment, FG, BG);
+        let (left, right) = ink_columns(&canvas);
+        assert!(left >= start && right < start + 20, "{:?}: ink in {}..={}", alignment, left, right);
+    }
+}
+
+#[test]
+fn clips_to_the_rect() {
+    let mut font = Font::new(&HANKAKU);
+    let layout = TextLayout::new(&font, "HHHHHHHHHH\nHHHHHHHHHH", None);
+    let mut canvas = MemoryCanvas::new(XY::new(100, 40), BG);
+    let rect = Rect::new(5, 4, 20, 20);
+    layout.draw(&mut font, &mut canvas, rect, Alignment::Center, FG, BG);
+    let size = canvas.size();
+    for y in 0..size.y {
+        for x in 0..size.x {
+            if !rect.is_contained(XY::new(x as isize, y as isize)) {
+                assert_eq!(canvas.pixel(XY::new(x, y)), BG, "drawn outside at {}, {}", x, y);
+            }
+        }
+    }
+    assert!(canvas.pixels().contains(&FG));
+}
+
+#[test]
+fn truetype_text() {
+    let mut roboto = TrueTypeFont::new(ROBOTO_REGULAR).unwrap();
+    let expected = libm::ceilf(roboto.measure("Hello wonderful", 20.0)) as usize;
+    let mut font = ScaledFont::new(&mut roboto, 20.0);
+    assert_eq!(font.measure("Hello wonderful"), expected);
+    assert_eq!(wrapped(&font, "Hello wonderful world", expected), ["Hello wonderful", "world"]);
+    assert_eq!(wrapped(&font, "Hello wonderful world", expected - 1), ["Hello", "wonderful", "world"]);
+
+    let layout = TextLayout::new(&font, "Hi", None);
+    let mut canvas = MemoryCanvas::new(XY::new(100, 30), BG);
+    layout.draw(&mut font, &mut canvas, Rect::new(0, 0, 100, 30), Alignment::Right, FG, BG);
+    let (left, right) = ink_columns(&canvas);
+    assert!(left >= 100 - layout.size().x && right < 100);
+}
@@ -0,0 +1,102 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    font::{Font, GlyphSet, Hankaku},
+    truetype::{CellGlyphs, TrueTypeFont, ROBOTO_REGULAR},
+};
+
+const FG: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const BG: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+
+fn roboto() -> TrueTypeFont<'static> {
+    TrueTypeFont::new(ROBOTO_REGULAR).unwrap()
+}
+
+#[test]
+fn rejects_invalid_fonts() {
+    assert!(TrueTypeFont::new(b"not a font").is_none());
+}
+
+#[test]
+fn proportional_advance() {
+    let font = roboto();
+    assert!(font.advance('i', 16.0) < font.advance('m', 16.0));
+    assert!((font.advance('m', 32.0) - 2.0 * font.advance('m', 16.0)).abs() < 0.01);
+}
+
+#[test]
+fn kerning_from_gpos() {
+    let font = roboto();
+    assert!(font.kerning('A', 'V', 32.0) < 0.0);
+    assert_eq!(font.kerning('l', 'l', 32.0), 0.0);
+    let unkerned = font.advance('A', 32.0) + font.advance('V', 32.0);
+    assert!(font.measure("AV", 32.0) < unkerned);
+}
+
+#[test]
+fn line_metrics_scale_with_size() {
+    let font = roboto();
+    let small = font.line_metrics(10.0);
+    let large = font.line_metrics(20.0);
+    assert!(small.ascent > 0.0 && small.descent < 0.0);
+    assert!((large.line_height() - 2.0 * small.line_height()).abs() < 0.01);
+}
+
+#[test]
+fn draws_anti_aliased_text() {
+    let mut canvas = MemoryCanvas::new(XY::new(200, 50), BG);
+    let mut font = roboto();
+    let width = font.draw_text(&mut canvas, XY::new(10, 5), 24.0, FG, BG, "Hello");
+    assert_eq!(width, font.measure("Hello", 24.0));
+
+    let pixels = canvas.pixels();
+    assert!(pixels.contains(&FG));
+    assert!(pixels.iter().any(|&p| p != FG && p != BG && p.r == p.g && p.g == p.b));
+    // nothing is drawn left of the start or beyond the measured width
+    for y in 0..50 {
+        assert_eq!(canvas.pixel(XY::new(9, y)), BG);
+        assert_eq!(canvas.pixel(XY::new(12 + width as usize, y)), BG);
+    }
+}
+
+#[test]
+fn cached_glyphs_draw_identically() {
+    let mut font = roboto();
+    let mut first = MemoryCanvas::new(XY::new(100, 40), BG);
+    let mut second = MemoryCanvas::new(XY::new(100, 40), BG);
+    font.draw_text(&mut first, XY::new(0, 0), 20.0, FG, BG, "aaa");
+    font.draw_text(&mut second, XY::new(0, 0), 20.0, FG, BG, "aaa");
+    assert!(first.pixels() == second.pixels());
+}
+
+#[test]
+fn clips_to_the_canvas() {
+    let mut canvas = MemoryCanvas::new(XY::new(20, 10), BG);
+    roboto().draw_text(&mut canvas, XY::new(-5, -8), 40.0, FG, BG, "Wide text");
+    assert!(canvas.pixels().contains(&FG));
+}
+
+#[test]
+fn cell_glyphs_for_the_console() {
+    static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+    let font = roboto();
+    let glyphs = CellGlyphs::new(&font, 16.0);
+    let size = glyphs.glyph_size();
+    assert_eq!(size.x, libm::ceilf(font.advance('W', 16.0)) as usize);
+    assert_eq!(size.y, libm::ceilf(font.line_metrics(16.0).line_height()) as usize);
+
+    let set = |c| {
+        let glyph = glyphs.glyph(c).unwrap();
+        (0..size.y).flat_map(|y| (0..size.x).map(move |x| XY::new(x, y))).filter(|&p| glyph.pixel(p)).count()
+    };
+    assert_eq!(set(' '), 0);
+    assert!(set('M') > set('.'));
+    assert!(set('\u{e9}') > 0);
+    assert!(glyphs.glyph('\u{3042}').is_none());
+
+    // the bitmap font fills in the characters the cells lack
+    let font = Font::new(&glyphs).with_fallback(&HANKAKU);
+    let mut canvas = MemoryCanvas::new(size, BG);
+    font.draw_char(&mut canvas, XY::new(0, 0), FG, BG, '\u{ff71}');
+    assert!(canvas.pixels().contains(&FG));
+}
@@ -0,0 +1,90 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    common::{PixelColor, XY},
+    console::Console,
+    font::{Font, Hankaku},
+    virtual_console::{VirtualConsoles, CONSOLE_COUNT, KERNEL_LOG},
+};
+use graphics_test::input::{Key, KeyEvent, Modifiers};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+fn canvas() -> MemoryCanvas {
+    MemoryCanvas::new(XY::new(800, 450), PixelColor { r: 1, g: 2, b: 3 })
+}
+
+fn alt_f(n: u8) -> KeyEvent {
+    KeyEvent::new(Key::F(n), Modifiers::ALT)
+}
+
+/// What `console` looks like when it is the only one on the screen.
+fn alone(console: &mut Console) -> Vec<PixelColor> {
+    let mut canvas = canvas();
+    let visible = console.is_visible();
+    console.set_visible(true);
+    console.flush(&mut canvas);
+    console.set_visible(visible);
+    canvas.pixels().to_vec()
+}
+
+#[test]
+fn hidden_consoles_do_not_draw() {
+    let mut screen = canvas();
+    let mut consoles = VirtualConsoles::new(Font::new(&HANKAKU), XY::new(800, 450));
+    consoles.active_mut().flush(&mut screen);
+    consoles.get_mut(KERNEL_LOG).put_string(&mut screen, "kernel");
+    let before = screen.pixels().to_vec();
+
+    consoles.get_mut(1).put_string(&mut screen, "shell\n".repeat(40).as_str());
+    assert!(screen.pixels() == before.as_slice());
+    assert!(screen.pixels() == alone(consoles.get_mut(KERNEL_LOG)).as_slice());
+}
+
+#[test]
+fn alt_function_keys_switch() {
+    let mut screen = canvas();
+    let mut consoles = VirtualConsoles::new(Font::new(&HANKAKU), XY::new(800, 450));
+    consoles.active_mut().flush(&mut screen);
+    for i in 0..CONSOLE_COUNT {
+        consoles.get_mut(i).put_string(&mut screen, &format!("console {}\n", i).repeat(i * 10));
+    }
+
+    assert!(consoles.handle_key(&mut screen, alt_f(3)));
+    assert_eq!(consoles.active(), 2);
+    assert!(screen.pixels() == alone(consoles.get_mut(2)).as_slice());
+
+    // output to the shown console draws incrementally again
+    consoles.get_mut(2).put_string(&mut screen, "more");
+    assert!(screen.pixels() == alone(consoles.get_mut(2)).as_slice());
+
+    assert!(consoles.handle_key(&mut screen, alt_f(1)));
+    assert_eq!(consoles.active(), KERNEL_LOG);
+    assert!(screen.pixels() == alone(consoles.get_mut(KERNEL_LOG)).as_slice());
+
+    assert!(!consoles.handle_key(&mut screen, alt_f(7)));
+    assert!(!consoles.handle_key(&mut screen, KeyEvent::new(Key::F(2), Modifiers::NONE)));
+    assert_eq!(consoles.active(), KERNEL_LOG);
+}
+
+#[test]
+fn scrollback_is_per_console() {
+    let mut screen = canvas();
+    let mut consoles = VirtualConsoles::new(Font::new(&HANKAKU), XY::new(800, 450));
+    consoles.get_mut(1).put_string(&mut screen, "x\n".repeat(40).as_str());
+    assert_eq!(consoles.get(KERNEL_LOG).history().count(), 0);
+
+    consoles.handle_key(&mut screen, alt_f(2));
+    assert!(consoles.handle_key(&mut screen, KeyEvent::new(Key::PageUp, Modifiers::SHIFT)));
+    assert_eq!(consoles.get(1).view_offset(), 12);
+    assert_eq!(consoles.get(KERNEL_LOG).view_offset(), 0);
+}
+
+#[test]
+fn resize_applies_to_all() {
+    let mut screen = canvas();
+    let mut consoles = VirtualConsoles::new(Font::new(&HANKAKU), XY::new(1600, 900));
+    consoles.resize(&mut screen);
+    for i in 0..CONSOLE_COUNT {
+        assert_eq!(consoles.get(i).size(), XY::new(80, 25));
+    }
+}
@@ -0,0 +1,82 @@
+use graphics_test::graphics::vt100::{Action, Parser};
+
+fn parse(s: &str) -> Vec<Action> {
+    let mut parser = Parser::new();
+    s.chars().filter_map(|c| parser.advance(c)).collect()
+}
+
+fn csi(s: &str) -> (Option<char>, Vec<u16>, char) {
+    match parse(s).as_slice() {
+        [Action::Csi(csi)] => (csi.private, csi.params().to_vec(), csi.final_char),
+        other => panic!("expected a single CSI sequence, got {:?}", other),
+    }
+}
+
+#[test]
+fn prints_and_controls() {
+    assert_eq!(
+        parse("a\r\n\t"),
+        [Action::Print('a'), Action::Control('\r'), Action::Control('\n'), Action::Control('\t')]
+    );
+}
+
+#[test]
+fn csi_params() {
+    assert_eq!(csi("\x1b[m"), (None, vec![], 'm'));
+    assert_eq!(csi("\x1b[1;31m"), (None, vec![1, 31], 'm'));
+    assert_eq!(csi("\x1b[;5H"), (None, vec![0, 5], 'H'));
+    assert_eq!(csi("\x1b[38:2:1:2:3m"), (None, vec![38, 2, 1, 2, 3], 'm'));
+    assert_eq!(csi("\x1b[?25l"), (Some('?'), vec![25], 'l'));
+    assert_eq!(csi("\x1b[99999A"), (None, vec![u16::MAX], 'A'));
+}
+
+#[test]
+fn param_defaults() {
+    let mut parser = Parser::new();
+    let Some(Action::Csi(csi)) = "\x1b[0;7H".chars().filter_map(|c| parser.advance(c)).last() else {
+        panic!("no CSI sequence");
+    };
+    assert_eq!(csi.param(0, 1), 1);
+    assert_eq!(csi.param(1, 1), 7);
+    assert_eq!(csi.param(2, 1), 1);
+}
+
+#[test]
+fn csi_intermediates() {
+    match parse("\x1b[4 q").as_slice() {
+        [Action::Csi(csi)] => assert_eq!((csi.intermediate, csi.params(), csi.final_char), (Some(' '), &[4][..], 'q')),
+        other => panic!("expected a single CSI sequence, got {:?}", other),
+    }
+    // an intermediate does not carry over to the next sequence
+    match parse("\x1b[ q\x1b[q").as_slice() {
+        [_, Action::Csi(csi)] => assert_eq!(csi.intermediate, None),
+        other => panic!("expected two CSI sequences, got {:?}", other),
+    }
+}
+
+#[test]
+fn esc_sequences() {
+    assert_eq!(parse("\x1b7x\x1b8"), [Action::Esc('7'), Action::Print('x'), Action::Esc('8')]);
+    // character set selection is consumed without effect
+    assert_eq!(parse("\x1b(Bx"), [Action::Print('x')]);
+}
+
+#[test]
+fn osc_strings_are_ignored() {
+    assert_eq!(parse("\x1b]0;title\x07a"), [Action::Print('a')]);
+    assert_eq!(parse("\x1b]0;title\x1b\\a"), [Action::Print('a')]);
+}
+
+#[test]
+fn malformed_sequences_are_skipped() {
+    assert_eq!(parse("\x1b[1$$pa"), [Action::Print('a')]);
+    assert_eq!(parse("\x1b[$1pa"), [Action::Print('a')]);
+    assert_eq!(parse("\x1b[1\x18a"), [Action::Print('a')]);
+    let too_many = format!("\x1b[{}ma", "1;".repeat(20));
+    assert_eq!(parse(&too_many), [Action::Print('a')]);
+}
+
+#[test]
+fn controls_inside_csi_are_executed() {
+    assert_eq!(parse("\x1b[1\r2A"), [Action::Control('\r'), parse("\x1b[12A").remove(0)]);
+}
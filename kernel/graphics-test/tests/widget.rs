@@ -0,0 +1,182 @@
+use graphics_test::graphics::{
+    back_buffer::BackBuffer,
+    common::{PixelColor, XY},
+    font::{Font, Hankaku},
+    rect::Rect,
+    widget::{Action, Button, Label, Layout, ListView, TextField, Ui},
+};
+use graphics_test::input::{Key, KeyEvent, Modifiers, MouseButtons, MouseEvent};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+// Hankaku characters take 10 pixels including spacing, their glyphs 8x16.
+
+fn key(key: Key) -> KeyEvent {
+    KeyEvent::new(key, Modifiers::NONE)
+}
+
+fn click(ui: &mut Ui, pos: XY<isize>) -> Option<Action> {
+    let pressed = ui.handle_mouse(MouseEvent::new(pos, MouseButtons::LEFT));
+    let released = ui.handle_mouse(MouseEvent::new(pos, MouseButtons::NONE));
+    pressed.or(released).map(|(_, action)| action)
+}
+
+fn surface() -> BackBuffer {
+    BackBuffer::new(XY::new(100, 100), PixelColor { r: 0, g: 0, b: 0 })
+}
+
+#[test]
+fn layout() {
+    let mut ui = Ui::new(Font::new(&HANKAKU), Layout::column().with_padding(4).with_spacing(2));
+    let root = ui.root();
+    let label = ui.add(root, Label::new("Hi"));
+    let button = ui.add(root, Button::new("OK"));
+    let field = ui.add(root, TextField::new(5));
+    let row = ui.add_container(root, Layout::row());
+    let left = ui.add(row, Button::new("A"));
+    let middle = ui.add(row, Label::new("B"));
+    let right = ui.add(row, Button::new("C"));
+    ui.set_grow(middle, true);
+
+    assert_eq!(ui.preferred_size(), XY::new(8 + 62, 8 + 16 + 24 + 22 + 24 + 3 * 2));
+    ui.resize(XY::new(100, 100));
+    ui.draw(&mut surface());
+    assert_eq!(ui.rect(label), Rect::new(4, 4, 92, 16));
+    assert_eq!(ui.rect(button), Rect::new(4, 22, 92, 24));
+    assert_eq!(ui.rect(field), Rect::new(4, 48, 92, 22));
+    assert_eq!(ui.rect(row), Rect::new(4, 72, 92, 24));
+    assert_eq!(ui.rect(left), Rect::new(4, 72, 26, 24));
+    assert_eq!(ui.rect(middle), Rect::new(30, 72, 40, 24));
+    assert_eq!(ui.rect(right), Rect::new(70, 72, 26, 24));
+}
+
+#[test]
+fn button_clicks() {
+    let mut ui = Ui::new(Font::new(&HANKAKU), Layout::row());
+    let root = ui.root();
+    let button = ui.add(root, Button::new("OK"));
+    ui.resize(XY::new(100, 100));
+
+    ui.handle_mouse(MouseEvent::new(XY::new(5, 5), MouseButtons::LEFT));
+    assert!(ui.widget::<Button>(button).unwrap().is_pressed());
+    assert_eq!(ui.focus(), Some(button));
+    assert_eq!(ui.handle_mouse(MouseEvent::new(XY::new(6, 6), MouseButtons::NONE)), Some((button, Action::Clicked)));
+    // released after leaving the button
+    ui.handle_mouse(MouseEvent::new(XY::new(5, 5), MouseButtons::LEFT));
+    assert_eq!(ui.handle_mouse(MouseEvent::new(XY::new(99, 5), MouseButtons::NONE)), None);
+    assert!(!ui.widget::<Button>(button).unwrap().is_pressed());
+    assert_eq!(ui.handle_key(key(Key::Char(' '))), Some((button, Action::Clicked)));
+}
+
+#[test]
+fn focus_moves_with_tab() {
+    let mut ui = Ui::new(Font::new(&HANKAKU), Layout::column());
+    let root = ui.root();
+    ui.add(root, Label::new("Name"));
+    let field = ui.add(root, TextField::new(10));
+    let row = ui.add_container(root, Layout::row());
+    let ok = ui.add(row, Button::new("OK"));
+    let cancel = ui.add(row, Button::new("Cancel"));
+    ui.resize(XY::new(200, 100));
+
+    assert_eq!(ui.focus(), None);
+    assert_eq!(ui.handle_key(key(Key::Char('x'))), None);
+    let tab = key(Key::Tab);
+    let order: Vec<_> = (0..4).map(|_| { ui.handle_key(tab); ui.focus().unwrap() }).collect();
+    assert_eq!(order, [field, ok, cancel, field]);
+    ui.handle_key(KeyEvent::new(Key::Tab, Modifiers::SHIFT));
+    assert_eq!(ui.focus(), Some(cancel));
+
+    // clicking a label leaves the focus where it is
+    click(&mut ui, XY::new(1, 1));
+    assert_eq!(ui.focus(), Some(cancel));
+    let field_rect = ui.rect(field);
+    click(&mut ui, XY::new(field_rect.x + 2, field_rect.y + 2));
+    assert_eq!(ui.focus(), Some(field));
+    assert_eq!(ui.handle_key(key(Key::Char('x'))), Some((field, Action::Changed)));
+    assert_eq!(ui.widget::<TextField>(field).unwrap().text(), "x");
+    assert!(ui.widget::<Button>(field).is_none());
+}
+
+#[test]
+fn text_field_editing() {
+    let mut ui = Ui::new(Font::new(&HANKAKU), Layout::row());
+    let root = ui.root();
+    let id = ui.add(root, TextField::new(10));
+    ui.resize(XY::new(86, 22));
+    ui.set_focus(Some(id));
+
+    for c in "hello".chars() {
+        ui.handle_key(key(Key::Char(c)));
+    }
+    for k in [Key::Left, Key::Left, Key::Backspace, Key::Home, Key::Delete, Key::End, Key::Char('!')] {
+        ui.handle_key(key(k));
+    }
+    let field = ui.widget::<TextField>(id).unwrap();
+    assert_eq!(field.text(), "elo!");
+    assert_eq!(field.caret(), 4);
+    assert_eq!(ui.handle_key(key(Key::Backspace)), Some((id, Action::Changed)));
+    assert_eq!(ui.handle_key(key(Key::Right)), None);
+    assert_eq!(ui.handle_key(key(Key::Enter)), Some((id, Action::Submitted)));
+    assert_eq!(ui.handle_key(KeyEvent::new(Key::Char('c'), Modifiers::CTRL)), None);
+
+    // the caret goes to the boundary nearest to the pointer
+    click(&mut ui, XY::new(3 + 10 + 6, 5));
+    assert_eq!(ui.widget::<TextField>(id).unwrap().caret(), 2);
+    click(&mut ui, XY::new(80, 5));
+    assert_eq!(ui.widget::<TextField>(id).unwrap().caret(), 3);
+}
+
+#[test]
+fn list_scrolls() {
+    let items = (0..10).map(|i| format!("item {i}")).collect();
+    let mut ui = Ui::new(Font::new(&HANKAKU), Layout::row());
+    let root = ui.root();
+    let id = ui.add(root, ListView::new(items, 4, 8));
+    let size = ui.preferred_size();
+    assert_eq!(size, XY::new(80 + 4 + 10 + 2, 4 * 20 + 2));
+    ui.resize(size);
+    ui.set_focus(Some(id));
+
+    assert_eq!(ui.handle_key(key(Key::Down)), Some((id, Action::Selected(0))));
+    assert_eq!(ui.handle_key(key(Key::PageDown)), Some((id, Action::Selected(4))));
+    assert_eq!(ui.widget::<ListView>(id).unwrap().top(), 1);
+    assert_eq!(ui.handle_key(key(Key::End)), Some((id, Action::Selected(9))));
+    assert_eq!(ui.widget::<ListView>(id).unwrap().top(), 6);
+    assert_eq!(ui.handle_key(key(Key::Enter)), Some((id, Action::Submitted)));
+
+    // the second row on screen
+    assert_eq!(click(&mut ui, XY::new(10, 1 + 20 + 5)), Some(Action::Selected(7)));
+    // the scroll bar above its thumb pages up
+    assert_eq!(click(&mut ui, XY::new(size.x as isize - 4, 2)), None);
+    assert_eq!(ui.widget::<ListView>(id).unwrap().top(), 2);
+    assert_eq!(ui.widget::<ListView>(id).unwrap().selected(), Some(7));
+}
+
+#[test]
+fn draws_only_changes() {
+    let mut ui = Ui::new(Font::new(&HANKAKU), Layout::column().with_padding(4));
+    let root = ui.root();
+    let label = ui.add(root, Label::new("Hello"));
+    let button = ui.add(root, Button::new("OK"));
+    ui.resize(XY::new(100, 100));
+    let mut surface = surface();
+    ui.draw(&mut surface);
+    assert_eq!(surface.take_dirty(), [Rect::new(0, 0, 100, 100)]);
+    ui.draw(&mut surface);
+    assert!(surface.take_dirty().is_empty());
+
+    ui.widget_mut::<Label>(label).unwrap().set_text("World");
+    ui.draw(&mut surface);
+    assert_eq!(surface.take_dirty(), [ui.rect(label)]);
+
+    // a press redraws the button only
+    ui.handle_mouse(MouseEvent::new(XY::new(10, 25), MouseButtons::LEFT));
+    ui.draw(&mut surface);
+    assert_eq!(surface.take_dirty(), [ui.rect(button)]);
+
+    // longer text needs a new layout
+    ui.widget_mut::<Label>(label).unwrap().set_text("Hello, world");
+    ui.draw(&mut surface);
+    assert_eq!(surface.take_dirty(), [Rect::new(0, 0, 100, 100)]);
+}
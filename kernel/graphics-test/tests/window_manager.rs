@@ -0,0 +1,137 @@
+use graphics_test::graphics::{
+    canvas::MemoryCanvas,
+    clip::ClippedWriter,
+    common::{PixelColor, XY},
+    font::{Font, Hankaku},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+    window_manager::{WindowManager, BORDER_WIDTH, TITLE_BAR_HEIGHT},
+};
+use graphics_test::input::{MouseButtons, MouseEvent};
+
+static HANKAKU: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
+
+const RED: PixelColor = PixelColor { r: 255, g: 0, b: 0 };
+const GREEN: PixelColor = PixelColor { r: 0, g: 255, b: 0 };
+const SCREEN: XY<usize> = XY { x: 320, y: 200 };
+
+fn screen() -> MemoryCanvas {
+    MemoryCanvas::new(SCREEN, PixelColor { r: 0, g: 0, b: 0 })
+}
+
+/// What a fresh window manager with the same windows paints from scratch.
+fn repainted(wm: &mut WindowManager) -> MemoryCanvas {
+    let mut canvas = screen();
+    wm.damage(Rect::from_size(SCREEN));
+    wm.compose(&mut canvas);
+    canvas
+}
+
+fn offset(pos: XY<isize>) -> XY<usize> {
+    XY::new(pos.x as usize + BORDER_WIDTH, pos.y as usize + BORDER_WIDTH + TITLE_BAR_HEIGHT)
+}
+
+#[test]
+fn clipped_writer() {
+    let mut canvas = MemoryCanvas::new(XY::new(6, 3), GREEN);
+    let mut clipped = ClippedWriter::new(&mut canvas, Rect::new(2, 1, 3, 5));
+    clipped.fill_rect(Rect::new(0, 0, 6, 3), RED);
+    clipped.draw_pixel(XY::new(0, 2), RED);
+    let reds: Vec<bool> = canvas.pixels().iter().map(|&p| p == RED).collect();
+    assert_eq!(reds, [
+        false, false, false, false, false, false,
+        false, false, true, true, true, false,
+        false, false, true, true, true, false,
+    ]);
+}
+
+#[test]
+fn windows_stack_in_z_order() {
+    let mut wm = WindowManager::new(SCREEN, Font::new(&HANKAKU));
+    let back = wm.create_window("back", XY::new(10, 10), XY::new(100, 60));
+    let front = wm.create_window("front", XY::new(50, 30), XY::new(100, 60));
+    wm.window_mut(back).unwrap().surface_mut().fill_rect(Rect::new(0, 0, 100, 60), RED);
+    wm.window_mut(front).unwrap().surface_mut().fill_rect(Rect::new(0, 0, 100, 60), GREEN);
+    let mut canvas = screen();
+    wm.compose(&mut canvas);
+
+    let back_origin = offset(XY::new(10, 10));
+    let front_origin = offset(XY::new(50, 30));
+    assert_eq!(canvas.pixel(back_origin), RED);
+    assert_eq!(canvas.pixel(front_origin), GREEN);
+    assert_eq!(wm.z_order().collect::<Vec<_>>(), [back, front]);
+    assert_eq!(wm.focused(), Some(front));
+
+    // raising the back window covers the overlap
+    wm.focus(back);
+    wm.compose(&mut canvas);
+    assert_eq!(canvas.pixel(front_origin), RED);
+    assert!(canvas.pixels() == repainted(&mut wm).pixels());
+}
+
+#[test]
+fn only_damage_is_repainted() {
+    let mut wm = WindowManager::new(SCREEN, Font::new(&HANKAKU));
+    let id = wm.create_window("w", XY::new(0, 0), XY::new(50, 50));
+    let mut canvas = screen();
+    wm.compose(&mut canvas);
+    // scribble outside the window, which compose must leave alone unless damaged
+    canvas.fill_rect(Rect::new(200, 100, 5, 5), RED);
+    wm.window_mut(id).unwrap().surface_mut().draw_pixel(XY::new(3, 4), GREEN);
+    wm.compose(&mut canvas);
+    assert_eq!(canvas.pixel(XY::new(200, 100)), RED);
+    assert_eq!(canvas.pixel(XY::new(3 + BORDER_WIDTH, 4 + BORDER_WIDTH + TITLE_BAR_HEIGHT)), GREEN);
+}
+
+#[test]
+fn drag_by_title_bar() {
+    let mut wm = WindowManager::new(SCREEN, Font::new(&HANKAKU));
+    let a = wm.create_window("a", XY::new(10, 10), XY::new(60, 40));
+    let b = wm.create_window("b", XY::new(150, 10), XY::new(60, 40));
+    wm.window_mut(a).unwrap().surface_mut().fill_rect(Rect::new(0, 0, 60, 40), RED);
+    let mut canvas = screen();
+    wm.compose(&mut canvas);
+
+    let press = |x, y| MouseEvent::new(XY::new(x, y), MouseButtons::LEFT);
+    assert_eq!(wm.handle_mouse(press(20, 15)), None);
+    assert_eq!(wm.focused(), Some(a));
+    assert_eq!(wm.handle_mouse(press(60, 95)), None);
+    assert_eq!(wm.handle_mouse(MouseEvent::new(XY::new(70, 100), MouseButtons::NONE)), None);
+    assert_eq!(wm.window(a).unwrap().frame().x, 50);
+    assert_eq!(wm.window(a).unwrap().frame().y, 90);
+    wm.compose(&mut canvas);
+    assert!(canvas.pixels() == repainted(&mut wm).pixels());
+    assert_eq!(canvas.pixel(offset(XY::new(50, 90))), RED);
+
+    // clicks in the client area are passed on relative to it, after focusing
+    let client = offset(XY::new(150, 10));
+    let click = press(client.x as isize + 5, client.y as isize + 7);
+    assert_eq!(wm.handle_mouse(click), Some((b, MouseEvent::new(XY::new(5, 7), MouseButtons::LEFT))));
+    assert_eq!(wm.focused(), Some(b));
+    assert_eq!(wm.handle_mouse(press(300, 190)), None);
+}
+
+#[test]
+fn close_and_resize() {
+    let mut wm = WindowManager::new(SCREEN, Font::new(&HANKAKU));
+    let a = wm.create_window("a", XY::new(10, 10), XY::new(60, 40));
+    let b = wm.create_window("b", XY::new(20, 20), XY::new(60, 40));
+    let mut canvas = screen();
+    wm.compose(&mut canvas);
+    wm.close_window(b);
+    assert_eq!(wm.focused(), Some(a));
+    wm.resize_window(a, XY::new(30, 20));
+    assert_eq!(wm.window(a).unwrap().client_area(), Rect::new(11, 31, 30, 20));
+    wm.compose(&mut canvas);
+    assert!(canvas.pixels() == repainted(&mut wm).pixels());
+}
+
+#[test]
+fn largest_window_fits_on_the_screen() {
+    let mut wm = WindowManager::new(SCREEN, Font::new(&HANKAKU));
+    let size = wm.max_client_size();
+    assert_eq!(size, XY::new(318, 178));
+    let id = wm.create_window("full", XY::new(0, 0), size);
+    assert_eq!(wm.window(id).unwrap().frame(), Rect::from_size(SCREEN));
+    assert_eq!(WindowManager::new(XY::new(1, 1), Font::new(&HANKAKU)).max_client_size(), XY::new(0, 0));
+}
@@ -0,0 +1,130 @@
+//! A window showing off the widget toolkit: a name to enter, buttons acting
+//! on it and a list of the names greeted so far.
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+use spin::{Mutex, Once};
+
+use crate::{
+    graphics::{
+        common::XY,
+        console,
+        font::Font,
+        widget::{Action, Button, Label, Layout, ListView, TextField, Ui, WidgetId},
+        window_manager::{self, WindowId},
+    },
+    input::{KeyEvent, MouseEvent},
+};
+
+// spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
+static DEMO: Once<Mutex<Demo>> = Once::new();
+
+struct Demo {
+    window: WindowId,
+    ui: Ui<'static>,
+    name: WidgetId,
+    greet: WidgetId,
+    clear: WidgetId,
+    greeted: WidgetId,
+    message: WidgetId,
+}
+
+/// Opens the demo window; does nothing before `window_manager::init`.
+pub fn launch() {
+    if window_manager::WINDOW_MANAGER.get().is_none() {
+        return;
+    }
+    DEMO.call_once(|| Mutex::new(Demo::new()));
+    draw();
+}
+
+/// The demo's window once it is launched.
+pub fn window() -> Option<WindowId> {
+    Some(DEMO.get()?.lock().window)
+}
+
+pub fn handle_mouse(event: MouseEvent) {
+    let Some(demo) = DEMO.get() else { return };
+    let action = demo.lock().ui.handle_mouse(event);
+    if let Some((id, action)) = action {
+        demo.lock().act(id, action);
+    }
+    draw();
+}
+
+pub fn handle_key(event: KeyEvent) {
+    let Some(demo) = DEMO.get() else { return };
+    let action = demo.lock().ui.handle_key(event);
+    if let Some((id, action)) = action {
+        demo.lock().act(id, action);
+    }
+    draw();
+}
+
+fn draw() {
+    let Some(demo) = DEMO.get() else { return };
+    let mut demo = demo.lock();
+    let Demo { window, ui, .. } = &mut *demo;
+    window_manager::draw_window(*window, &mut |w| ui.draw(w));
+}
+
+impl Demo {
+    fn new() -> Self {
+        let mut ui = Ui::new(Font::new(&console::SHINONOME_FONT), Layout::column().with_padding(8).with_spacing(6));
+        let root = ui.root();
+        let row = ui.add_container(root, Layout::row().with_spacing(6));
+        ui.add(row, Label::new("Name:"));
+        let name = ui.add(row, TextField::new(16));
+        ui.set_grow(name, true);
+        let buttons = ui.add_container(root, Layout::row().with_spacing(6));
+        let greet = ui.add(buttons, Button::new("Greet"));
+        let clear = ui.add(buttons, Button::new("Clear"));
+        ui.add(root, Label::new("Greeted so far:"));
+        let greeted = ui.add(root, ListView::new(Vec::new(), 6, 24));
+        ui.set_grow(greeted, true);
+        let message = ui.add(root, Label::new("Type a name and press Enter."));
+        ui.set_focus(Some(name));
+
+        let size = ui.preferred_size();
+        ui.resize(size);
+        let mut window = None;
+        window_manager::lock_window_manager(|mut wm| {
+            let screen = wm.size();
+            let pos = XY::new(screen.x.saturating_sub(size.x + 40) as isize, 40);
+            window = Some(wm.create_window("Widgets", pos, size));
+        });
+        let window = window.expect("the window manager is running");
+        Self { window, ui, name, greet, clear, greeted, message }
+    }
+
+    fn act(&mut self, id: WidgetId, action: Action) {
+        let ui = &mut self.ui;
+        match action {
+            Action::Clicked if id == self.greet => self.greet(),
+            Action::Submitted if id == self.name => self.greet(),
+            Action::Clicked if id == self.clear => {
+                ui.widget_mut::<TextField>(self.name).unwrap().set_text("");
+                ui.widget_mut::<ListView>(self.greeted).unwrap().set_items(vec![]);
+                ui.widget_mut::<Label>(self.message).unwrap().set_text("Cleared.");
+            }
+            Action::Selected(i) if id == self.greeted => {
+                let list = ui.widget::<ListView>(self.greeted).unwrap();
+                let text = format!("Selected {}.", list.items()[i]);
+                ui.widget_mut::<Label>(self.message).unwrap().set_text(&text);
+            }
+            _ => {}
+        }
+    }
+
+    fn greet(&mut self) {
+        let ui = &mut self.ui;
+        let name: String = ui.widget::<TextField>(self.name).unwrap().text().trim().into();
+        if name.is_empty() {
+            ui.widget_mut::<Label>(self.message).unwrap().set_text("Type a name first.");
+            return;
+        }
+        ui.widget_mut::<Label>(self.message).unwrap().set_text(&format!("Hello, {name}!"));
+        ui.widget_mut::<ListView>(self.greeted).unwrap().push(name);
+        ui.widget_mut::<TextField>(self.name).unwrap().set_text("");
+    }
+}
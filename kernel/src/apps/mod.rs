@@ -0,0 +1,38 @@
+//! Applications running in windows, and the routing of input to them.
+//!
+//! Mouse events go to the window under the pointer and key presses to the
+//! focused window, whose owner handles them.
+
+pub mod demo;
+pub mod shell;
+pub mod terminal;
+
+use crate::{
+    graphics::{console, window_manager},
+    input::{KeyEvent, MouseEvent},
+};
+
+/// Moves the cursor and passes the event to the window manager and then to
+/// the application whose window it concerns.
+pub fn handle_mouse(event: MouseEvent) {
+    let Some((id, event)) = window_manager::handle_mouse(event) else { return };
+    if demo::window() == Some(id) {
+        demo::handle_mouse(event);
+    } else if terminal::window() == Some(id) {
+        terminal::handle_mouse(event);
+    }
+}
+
+/// Passes a key press to the application in the focused window.
+pub fn handle_key(event: KeyEvent) {
+    let mut focused = None;
+    window_manager::lock_window_manager(|wm| focused = wm.focused());
+    let Some(id) = focused else { return };
+    if window_manager::console_window() == Some(id) {
+        console::handle_key(event);
+    } else if demo::window() == Some(id) {
+        demo::handle_key(event);
+    } else if terminal::window() == Some(id) {
+        terminal::handle_key(event);
+    }
+}
@@ -0,0 +1,151 @@
+//! A minimal command line with a few built-in commands, run by the terminal.
+//!
+//! The shell only produces text, escape sequences included, and leaves
+//! displaying it to its host. Commands changing the terminal itself are
+//! returned as `Request`s.
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+use super::terminal::FontKind;
+use crate::{clipboard, graphics::{common::XY, font}, input::{Key, KeyEvent}};
+
+const PROMPT: &str = "$ ";
+
+/// Something the shell asks of the terminal it runs in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Request {
+    Font(FontKind),
+    /// Resize the terminal to the columns and rows.
+    Resize(XY<usize>),
+}
+
+pub struct Shell {
+    /// The command being typed.
+    line: String,
+    /// The most columns and rows `size` may ask for.
+    max_size: XY<usize>,
+}
+
+impl Shell {
+    /// A shell whose terminal can be resized up to `max_size` columns and rows.
+    pub fn new(max_size: XY<usize>) -> Self {
+        Self { line: String::new(), max_size }
+    }
+
+    /// Changes the limit for `size`, e.g. after the terminal switched to a font of another size.
+    pub fn set_max_size(&mut self, max_size: XY<usize>) {
+        self.max_size = max_size;
+    }
+
+    /// Writes the greeting and the first prompt.
+    pub fn start(&mut self, out: &mut String) {
+        out.push_str("AIOS shell. Type \"help\" for the commands.\n");
+        out.push_str(PROMPT);
+    }
+
+    /// Edits the command line, echoing into `out`, and runs it on Enter.
+    pub fn handle_key(&mut self, event: KeyEvent, out: &mut String) -> Option<Request> {
+        match event.key {
+            Key::Char(c) if !c.is_control() && !event.modifiers.ctrl && !event.modifiers.alt => {
+                self.line.push(c);
+                out.push(c);
+            }
+            Key::Backspace => {
+                if let Some(c) = self.line.pop() {
+                    // back over the character, blank it and back again
+                    for _ in 0..font::char_width(c) {
+                        out.push_str("\x08 \x08");
+                    }
+                }
+            }
+            Key::Enter => return self.run(out),
+            _ => {}
+        }
+        None
+    }
+
+    /// Types `text` as if it was entered key by key, running each complete line.
+    pub fn type_text(&mut self, text: &str, out: &mut String) -> Option<Request> {
+        let mut request = None;
+        for c in text.chars() {
+            let key = match c {
+                '\n' | '\r' => Key::Enter,
+                '\t' => Key::Char(' '),
+                c => Key::Char(c),
+            };
+            request = self.handle_key(KeyEvent::new(key, Default::default()), out).or(request);
+        }
+        request
+    }
+
+    fn run(&mut self, out: &mut String) -> Option<Request> {
+        out.push('\n');
+        let line = core::mem::take(&mut self.line);
+        let args: Vec<&str> = line.split_whitespace().collect();
+        let request = match args.as_slice() {
+            [] => None,
+            ["help"] => {
+                out.push_str("help                  show this list\n");
+                out.push_str("echo TEXT...          print TEXT\n");
+                out.push_str("clear                 clear the screen\n");
+                out.push_str("colors                show the 16 standard colors\n");
+                out.push_str("clip                  print the clipboard\n");
+                out.push_str("font bitmap|truetype  switch the font\n");
+                out.push_str("size COLUMNS ROWS     resize the terminal\n");
+                out.push_str("Select text with the mouse, Ctrl+Shift+C copies and\n");
+                out.push_str("Ctrl+Shift+V or the middle button pastes.\n");
+                None
+            }
+            ["echo", words @ ..] => {
+                out.push_str(&words.join(" "));
+                out.push('\n');
+                None
+            }
+            ["clear"] => {
+                out.push_str("\x1b[2J\x1b[H");
+                None
+            }
+            ["colors"] => {
+                for i in 0..16 {
+                    // dark text on the light colors
+                    let fg = if i == 7 || i >= 10 { 30 } else { 97 };
+                    let bg = if i < 8 { 40 + i } else { 100 + i - 8 };
+                    let _ = write!(out, "\x1b[{};{}m {:2} \x1b[0m", fg, bg, i);
+                    if i % 8 == 7 {
+                        out.push('\n');
+                    }
+                }
+                None
+            }
+            ["clip"] => {
+                out.push_str(&clipboard::paste());
+                out.push('\n');
+                None
+            }
+            ["font", "bitmap"] => Some(Request::Font(FontKind::Bitmap)),
+            ["font", "truetype"] => Some(Request::Font(FontKind::TrueType)),
+            ["font", ..] => {
+                out.push_str("font: expected bitmap or truetype\n");
+                None
+            }
+            ["size", columns, rows] => match (columns.parse(), rows.parse()) {
+                (Ok(columns), Ok(rows)) if columns > self.max_size.x || rows > self.max_size.y => {
+                    let _ = writeln!(out, "size: at most {} {} fit on the screen", self.max_size.x, self.max_size.y);
+                    None
+                }
+                (Ok(columns), Ok(rows)) if columns > 0 && rows > 0 => Some(Request::Resize(XY::new(columns, rows))),
+                _ => {
+                    out.push_str("size: expected two positive numbers\n");
+                    None
+                }
+            },
+            [command, ..] => {
+                let _ = writeln!(out, "{}: command not found", command);
+                None
+            }
+        };
+        out.push_str(PROMPT);
+        request
+    }
+}
@@ -0,0 +1,259 @@
+//! A terminal window running the shell.
+//!
+//! Output is rendered by the same `Console` as the kernel's consoles, so the
+//! VT100 escape sequences work alike, in the bitmap font or in a TrueType
+//! font rendered into cells. Text selected with the mouse can be copied to
+//! the kernel clipboard and pasted back as input.
+
+use alloc::string::String;
+
+use spin::{Mutex, Once};
+
+use super::shell::{Request, Shell};
+use crate::{
+    clipboard,
+    graphics::{
+        common::XY,
+        console::{self, Console},
+        font::Font,
+        frame_buffer::PixelWriter,
+        truetype::{CellGlyphs, TrueTypeFont, ROBOTO_REGULAR},
+        window_manager::{self, WindowId},
+    },
+    input::{Key, KeyEvent, MouseButtons, MouseEvent},
+};
+
+/// Size of the terminal when launched, in columns and rows, if it fits on the screen.
+const INITIAL_SIZE: XY<usize> = XY { x: 80, y: 24 };
+/// Pixels per em of the TrueType font.
+const TRUETYPE_SIZE: f32 = 15.0;
+
+// spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
+static TERMINAL: Once<Mutex<Terminal>> = Once::new();
+/// Rendered on first use, as rasterizing takes a while.
+static TRUETYPE_GLYPHS: Once<CellGlyphs> = Once::new();
+
+/// The fonts the terminal can show text in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FontKind {
+    Bitmap,
+    TrueType,
+}
+
+impl FontKind {
+    fn font(self) -> Font<'static> {
+        match self {
+            FontKind::Bitmap => Font::new(&console::SHINONOME_FONT),
+            FontKind::TrueType => {
+                let glyphs = TRUETYPE_GLYPHS.call_once(|| {
+                    let font = TrueTypeFont::new(ROBOTO_REGULAR).expect("the bundled font is valid");
+                    CellGlyphs::new(&font, TRUETYPE_SIZE)
+                });
+                // the bitmap font has the characters the TrueType one leaves out
+                Font::new(glyphs).with_fallback(&console::SHINONOME_FONT)
+            }
+        }
+    }
+}
+
+struct Terminal {
+    window: WindowId,
+    console: Console<'static>,
+    shell: Shell,
+    font: Font<'static>,
+    /// Where the selection started while the left button is held.
+    anchor: Option<XY<usize>>,
+    buttons: MouseButtons,
+}
+
+/// Opens a terminal window; does nothing before `window_manager::init`.
+pub fn launch() {
+    if window_manager::WINDOW_MANAGER.get().is_none() {
+        return;
+    }
+    let terminal = TERMINAL.call_once(|| Mutex::new(Terminal::new()));
+    let mut output = String::new();
+    let mut terminal = terminal.lock();
+    terminal.shell.start(&mut output);
+    terminal.draw(|console, w| console.put_string(w, &output));
+}
+
+/// The terminal's window once it is launched.
+pub fn window() -> Option<WindowId> {
+    Some(TERMINAL.get()?.lock().window)
+}
+
+/// Selects text while the left button is held, pasting with the middle button.
+pub fn handle_mouse(event: MouseEvent) {
+    let Some(terminal) = TERMINAL.get() else { return };
+    terminal.lock().handle_mouse(event);
+}
+
+/// Ctrl+Shift+C copies the selection and Ctrl+Shift+V pastes; Shift+PageUp
+/// and Shift+PageDown page through the history. Other keys go to the shell.
+pub fn handle_key(event: KeyEvent) {
+    let Some(terminal) = TERMINAL.get() else { return };
+    terminal.lock().handle_key(event);
+}
+
+/// Toggles the terminal's cursor if it blinks; called from a timer every
+/// `console::CURSOR_BLINK_MS`.
+pub fn blink_cursor() {
+    let Some(terminal) = TERMINAL.get() else { return };
+    terminal.lock().draw(|console, w| console.blink_cursor(w));
+}
+
+/// Resizes the terminal to `size` columns and rows, wrapping its text anew.
+pub fn resize(size: XY<usize>) {
+    let Some(terminal) = TERMINAL.get() else { return };
+    terminal.lock().resize(size);
+}
+
+impl Terminal {
+    fn new() -> Self {
+        let font = FontKind::Bitmap.font();
+        let max_size = max_grid_size(&font);
+        let pixel_size = grid_pixels(&font, clamp_grid_size(INITIAL_SIZE, max_size));
+        let mut window = None;
+        window_manager::lock_window_manager(|mut wm| {
+            let screen = wm.size();
+            let pos = XY::new(24, (screen.y.saturating_sub(pixel_size.y) / 2) as isize);
+            window = Some(wm.create_window("Terminal", pos, pixel_size));
+        });
+        let window = window.expect("the window manager is running");
+        Self {
+            window,
+            console: Console::new(font, pixel_size),
+            shell: Shell::new(max_size),
+            font,
+            anchor: None,
+            buttons: MouseButtons::NONE,
+        }
+    }
+
+    /// Runs `f` with the console and the window's surface, and shows the result.
+    fn draw(&mut self, mut f: impl FnMut(&mut Console<'static>, &mut dyn PixelWriter)) {
+        let console = &mut self.console;
+        window_manager::draw_window(self.window, &mut |w| f(console, w));
+    }
+
+    /// The cell under `pos`, relative to the client area.
+    fn cell_at(&self, pos: XY<isize>) -> XY<usize> {
+        let char_size = self.font.char_size();
+        let size = self.console.size();
+        let cell = |p: isize, char_size: usize, cells: usize| (p.max(0) as usize / char_size).min(cells - 1);
+        XY::new(cell(pos.x, char_size.x, size.x), cell(pos.y, char_size.y, size.y))
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent) {
+        let cell = self.cell_at(event.pos);
+        let (old, new) = (self.buttons, event.buttons);
+        self.buttons = new;
+        if new.left && !old.left {
+            self.anchor = Some(cell);
+            self.draw(|console, w| {
+                console.clear_selection();
+                console.render(w);
+            });
+        } else if let (true, Some(anchor)) = (event.buttons.left, self.anchor) {
+            self.draw(|console, w| {
+                console.select(anchor, cell);
+                console.render(w);
+            });
+        }
+        if !event.buttons.left {
+            self.anchor = None;
+        }
+        if new.middle && !old.middle {
+            self.paste();
+        }
+    }
+
+    fn handle_key(&mut self, event: KeyEvent) {
+        let modifiers = event.modifiers;
+        if let (Key::Char(c), true, true) = (event.key, modifiers.ctrl, modifiers.shift) {
+            match c.to_ascii_lowercase() {
+                'c' => {
+                    if let Some(text) = self.console.selected_text() {
+                        clipboard::copy(&text);
+                    }
+                }
+                'v' => self.paste(),
+                _ => {}
+            }
+            return;
+        }
+        let mut consumed = false;
+        self.draw(|console, w| consumed = console.handle_key(w, event));
+        if consumed {
+            return;
+        }
+        let mut output = String::new();
+        let request = self.shell.handle_key(event, &mut output);
+        self.output(&output, request);
+    }
+
+    fn paste(&mut self) {
+        let mut output = String::new();
+        let request = self.shell.type_text(&clipboard::paste(), &mut output);
+        self.output(&output, request);
+    }
+
+    /// Shows what the shell wrote, and then does what it asked for.
+    fn output(&mut self, output: &str, request: Option<Request>) {
+        if !output.is_empty() {
+            self.draw(|console, w| console.put_string(w, output));
+        }
+        match request {
+            Some(Request::Font(kind)) => self.set_font(kind.font()),
+            Some(Request::Resize(size)) => self.resize(size),
+            None => {}
+        }
+    }
+
+    /// Switches to `font`, resizing the window to keep the columns and rows
+    /// as far as they still fit on the screen.
+    fn set_font(&mut self, font: Font<'static>) {
+        self.font = font;
+        let max_size = max_grid_size(&font);
+        self.shell.set_max_size(max_size);
+        let pixel_size = grid_pixels(&font, clamp_grid_size(self.console.size(), max_size));
+        self.console.set_font(font, pixel_size);
+        self.resize_window(pixel_size);
+    }
+
+    /// Resizes to `size` columns and rows, as far as they fit on the screen.
+    fn resize(&mut self, size: XY<usize>) {
+        let size = clamp_grid_size(size, max_grid_size(&self.font));
+        let pixel_size = grid_pixels(&self.font, size);
+        self.console.reflow(pixel_size);
+        self.resize_window(pixel_size);
+    }
+
+    /// Gives the window a surface of `pixel_size` and repaints the console into it.
+    fn resize_window(&mut self, pixel_size: XY<usize>) {
+        let window = self.window;
+        window_manager::lock_window_manager(|mut wm| wm.resize_window(window, pixel_size));
+        self.anchor = None;
+        self.draw(|console, w| console.flush(w));
+    }
+}
+
+/// The most columns and rows of `font` that fit on the screen in a window.
+fn max_grid_size(font: &Font) -> XY<usize> {
+    let mut client = XY::new(0, 0);
+    window_manager::lock_window_manager(|wm| client = wm.max_client_size());
+    let char_size = font.char_size();
+    XY::new(client.x / char_size.x, client.y / char_size.y)
+}
+
+/// `size` limited to `max_size`, keeping at least one column and row.
+fn clamp_grid_size(size: XY<usize>, max_size: XY<usize>) -> XY<usize> {
+    XY::new(size.x.min(max_size.x).max(1), size.y.min(max_size.y).max(1))
+}
+
+/// Pixels taken by `size` columns and rows of `font`.
+fn grid_pixels(font: &Font, size: XY<usize>) -> XY<usize> {
+    let char_size = font.char_size();
+    XY::new(size.x * char_size.x, size.y * char_size.y)
+}
@@ -0,0 +1,19 @@
+//! Text shared between applications by copy and paste.
+
+use alloc::string::String;
+
+use spin::Mutex;
+
+static CLIPBOARD: Mutex<String> = Mutex::new(String::new());
+
+/// Replaces the clipboard's contents with `text`.
+pub fn copy(text: &str) {
+    let mut clipboard = CLIPBOARD.lock();
+    clipboard.clear();
+    clipboard.push_str(text);
+}
+
+/// A copy of the clipboard's contents.
+pub fn paste() -> String {
+    CLIPBOARD.lock().clone()
+}
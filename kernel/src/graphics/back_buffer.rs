@@ -0,0 +1,95 @@
+//! A shadow copy of the screen in RAM.
+//!
+//! Drawing into video memory pixel by pixel is slow, and reading it back for
+//! scrolling is slower still. Everything is drawn into a `BackBuffer` instead,
+//! which remembers the areas it changed and copies only those to the real
+//! framebuffer on `flush`.
+
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{canvas::MemoryCanvas, common::{PixelColor, XY}, frame_buffer::PixelWriter, rect::Rect};
+
+/// Dirty rectangles kept apart before they are merged into their bounding box.
+const MAX_DIRTY_RECTS: usize = 32;
+
+pub struct BackBuffer {
+    canvas: MemoryCanvas,
+    /// Areas changed since the last flush, none of them touching another.
+    dirty: Vec<Rect>,
+}
+
+impl BackBuffer {
+    pub fn new(size: XY<usize>, color: PixelColor) -> Self {
+        Self { canvas: MemoryCanvas::new(size, color), dirty: Vec::new() }
+    }
+    pub fn canvas(&self) -> &MemoryCanvas {
+        &self.canvas
+    }
+    pub fn dirty_rects(&self) -> &[Rect] {
+        &self.dirty
+    }
+    /// Marks `rect` to be copied by the next flush, e.g. after the target was
+    /// overwritten by something else.
+    pub fn mark_dirty(&mut self, rect: Rect) {
+        let Some(mut rect) = rect.clip(self.canvas.size()) else { return };
+        // absorb everything the new area overlaps or touches, which may grow it into others
+        while let Some(i) = self.dirty.iter().position(|&other| touches(rect, other)) {
+            rect = rect.union(self.dirty.swap_remove(i));
+        }
+        self.dirty.push(rect);
+        if self.dirty.len() > MAX_DIRTY_RECTS {
+            let bounds = self.dirty.iter().fold(rect, |acc, &r| acc.union(r));
+            self.dirty.clear();
+            self.dirty.push(bounds);
+        }
+    }
+    /// Returns the changed areas and forgets them, for a caller copying them itself.
+    pub fn take_dirty(&mut self) -> Vec<Rect> {
+        mem::take(&mut self.dirty)
+    }
+    /// Copies the changed areas to `target`, row by row, and forgets them.
+    pub fn flush(&mut self, target: &mut dyn PixelWriter) {
+        let width = self.canvas.size().x;
+        for rect in self.dirty.drain(..) {
+            let Some(rect) = rect.clip(target.size()) else { continue };
+            for y in rect.y as usize..rect.bottom() as usize {
+                let start = y * width + rect.x as usize;
+                target.write_row(XY::new(rect.x as usize, y), &self.canvas.pixels()[start..start + rect.w]);
+            }
+        }
+    }
+}
+
+/// Whether `a` and `b` overlap or share an edge, so that their union covers little else.
+fn touches(a: Rect, b: Rect) -> bool {
+    a.x <= b.right() && b.x <= a.right() && a.y <= b.bottom() && b.y <= a.bottom()
+}
+
+impl PixelWriter for BackBuffer {
+    fn size(&self) -> XY<usize> {
+        self.canvas.size()
+    }
+    fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor) {
+        self.canvas.draw_pixel(pos, color);
+        self.mark_dirty(Rect::new(pos.x as isize, pos.y as isize, 1, 1));
+    }
+    fn read_pixel(&self, pos: XY<usize>) -> PixelColor {
+        self.canvas.pixel(pos)
+    }
+    fn copy_rect(&mut self, src: Rect, dst: XY<usize>) {
+        self.canvas.copy_rect(src, dst);
+        self.mark_dirty(Rect::new(dst.x as isize, dst.y as isize, src.w, src.h));
+    }
+    fn fill_row(&mut self, pos: XY<usize>, len: usize, color: PixelColor) {
+        self.canvas.fill_row(pos, len, color);
+        self.mark_dirty(Rect::new(pos.x as isize, pos.y as isize, len, 1));
+    }
+    fn write_row(&mut self, pos: XY<usize>, pixels: &[PixelColor]) {
+        self.canvas.write_row(pos, pixels);
+        self.mark_dirty(Rect::new(pos.x as isize, pos.y as isize, pixels.len(), 1));
+    }
+    fn read_row(&self, pos: XY<usize>, pixels: &mut [PixelColor]) {
+        self.canvas.read_row(pos, pixels);
+    }
+}
@@ -0,0 +1,140 @@
+//! Fonts in the Glyph Bitmap Distribution Format, such as the Shinonome
+//! zenkaku font converted to Unicode encoding.
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::str;
+
+use super::common::XY;
+use super::font::{char_width, FontError, Glyph, GlyphSet};
+
+/// Sizes and offsets beyond this many pixels are taken as malformed, which
+/// also keeps the arithmetic placing glyphs far from overflowing.
+const MAX_EXTENT: i32 = 1024;
+
+/// A BDF font parsed into glyph bitmaps the size of its bounding box.
+pub struct BdfFont {
+    glyph_size: XY<usize>,
+    /// Width and bitmap of each glyph, rows padded to whole bytes.
+    glyphs: BTreeMap<char, (usize, Vec<u8>)>,
+}
+
+/// The glyph being read between `STARTCHAR` and `ENDCHAR`.
+#[derive(Default)]
+struct CharState {
+    encoding: Option<u32>,
+    advance: Option<usize>,
+    /// `BBX`: width, height and offset of the bitmap from the origin.
+    bbx: [i32; 4],
+    rows: Vec<u32>,
+    in_bitmap: bool,
+}
+
+impl BdfFont {
+    /// Parses a BDF font whose `ENCODING`s are Unicode code points, as
+    /// declared by `CHARSET_REGISTRY "ISO10646"`.
+    pub fn parse(data: &[u8]) -> Result<Self, FontError> {
+        let text = str::from_utf8(data).map_err(|_| FontError::Unsupported("BDF font is not ASCII text"))?;
+        let mut bounding_box = None;
+        let mut glyphs = BTreeMap::new();
+        let mut current: Option<CharState> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let malformed = FontError::Malformed { line: line_no };
+            let (keyword, args) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+
+            if let Some(state) = current.as_mut().filter(|state| state.in_bitmap && keyword != "ENDCHAR") {
+                if keyword.len() > 8 {
+                    return Err(FontError::Unsupported("BDF glyphs wider than 32 pixels"));
+                }
+                state.rows.push(u32::from_str_radix(keyword, 16).map_err(|_| malformed)? << (32 - 4 * keyword.len() as u32));
+                continue;
+            }
+            match keyword {
+                "FONTBOUNDINGBOX" => bounding_box = Some(bounding_box_of(args).ok_or(malformed)?),
+                "CHARSET_REGISTRY" => {
+                    if !args.trim_matches('"').eq_ignore_ascii_case("ISO10646") {
+                        return Err(FontError::Unsupported("BDF font is not Unicode (ISO10646) encoded"));
+                    }
+                }
+                "STARTCHAR" => current = Some(CharState::default()),
+                "ENCODING" => {
+                    let state = current.as_mut().ok_or(malformed)?;
+                    // -1 marks glyphs without a standard encoding
+                    state.encoding = args.split(' ').next().and_then(|n| n.parse().ok());
+                }
+                "DWIDTH" => {
+                    let [advance, _] = numbers::<2>(args).filter(|&[x, _]| (0..=MAX_EXTENT).contains(&x)).ok_or(malformed)?;
+                    current.as_mut().ok_or(malformed)?.advance = Some(advance as usize);
+                }
+                "BBX" => current.as_mut().ok_or(malformed)?.bbx = bounding_box_of(args).ok_or(malformed)?,
+                "BITMAP" => current.as_mut().ok_or(malformed)?.in_bitmap = true,
+                "ENDCHAR" => {
+                    let state = current.take().ok_or(malformed)?;
+                    let bounding_box = bounding_box.ok_or(malformed)?;
+                    if let Some(c) = state.encoding.and_then(char::from_u32) {
+                        glyphs.insert(c, place_glyph(&state, bounding_box));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let [w, h, ..] = bounding_box.ok_or(FontError::Malformed { line: text.lines().count() })?;
+        // a font of only full-width glyphs implies half its bounding box as the single width
+        let all_wide = glyphs.keys().all(|&c| char_width(c) == 2);
+        let glyph_size = XY::new(if all_wide { w as usize / 2 } else { w as usize }, h as usize);
+        Ok(Self { glyph_size, glyphs })
+    }
+}
+
+impl GlyphSet for BdfFont {
+    fn glyph_size(&self) -> XY<usize> {
+        self.glyph_size
+    }
+    fn glyph(&self, c: char) -> Option<Glyph<'_>> {
+        let (width, bitmap) = self.glyphs.get(&c)?;
+        Some(Glyph { size: XY::new(*width, self.glyph_size.y), bitmap })
+    }
+}
+
+/// Copies the bitmap of `state` into a glyph as wide as its advance and as
+/// high as the font's bounding box, aligned on the common baseline.
+fn place_glyph(state: &CharState, bounding_box: [i32; 4]) -> (usize, Vec<u8>) {
+    let [font_w, font_h, font_x, font_y] = bounding_box;
+    let [w, h, x_off, y_off] = state.bbx;
+    let width = state.advance.unwrap_or(font_w as usize);
+    let stride = width.div_ceil(8);
+    let mut bitmap = vec![0; stride * font_h as usize];
+    // rows are counted from the top of the font's bounding box
+    let top = (font_h + font_y) - (h + y_off);
+    for (row, bits) in state.rows.iter().take(h.max(0) as usize).enumerate() {
+        for col in 0..w.clamp(0, 32) {
+            if bits & (0x8000_0000 >> col) == 0 {
+                continue;
+            }
+            let (x, y) = (x_off - font_x + col, top + row as i32);
+            if (0..width as i32).contains(&x) && (0..font_h).contains(&y) {
+                bitmap[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+    (width, bitmap)
+}
+
+/// Width, height and offsets as in `FONTBOUNDINGBOX` and `BBX`, if none of them is negative or absurdly large.
+fn bounding_box_of(args: &str) -> Option<[i32; 4]> {
+    let [w, h, x, y] = numbers::<4>(args)?;
+    let size_ok = (0..=MAX_EXTENT).contains(&w) && (0..=MAX_EXTENT).contains(&h);
+    let offset_ok = (-MAX_EXTENT..=MAX_EXTENT).contains(&x) && (-MAX_EXTENT..=MAX_EXTENT).contains(&y);
+    (size_ok && offset_ok).then_some([w, h, x, y])
+}
+
+fn numbers<const N: usize>(args: &str) -> Option<[i32; N]> {
+    let mut numbers = [0; N];
+    let mut args = args.split_ascii_whitespace();
+    for n in numbers.iter_mut() {
+        *n = args.next()?.parse().ok()?;
+    }
+    Some(numbers)
+}
@@ -0,0 +1,51 @@
+use alloc::{vec, vec::Vec};
+
+use super::{common::{PixelColor, XY}, frame_buffer::{self, PixelWriter}, rect::Rect};
+
+/// A heap-backed drawing target holding one `PixelColor` per pixel.
+///
+/// Used as an off-screen surface and to inspect drawing results in tests.
+pub struct MemoryCanvas {
+    size: XY<usize>,
+    pixels: Vec<PixelColor>,
+}
+
+impl MemoryCanvas {
+    pub fn new(size: XY<usize>, color: PixelColor) -> Self {
+        Self { size, pixels: vec![color; size.x * size.y] }
+    }
+    pub fn pixel(&self, pos: XY<usize>) -> PixelColor {
+        self.pixels[pos.y * self.size.x + pos.x]
+    }
+    /// All pixels in row-major order.
+    pub fn pixels(&self) -> &[PixelColor] {
+        &self.pixels
+    }
+}
+
+impl PixelWriter for MemoryCanvas {
+    fn size(&self) -> XY<usize> {
+        self.size
+    }
+    fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor) {
+        self.pixels[pos.y * self.size.x + pos.x] = color;
+    }
+    fn read_pixel(&self, pos: XY<usize>) -> PixelColor {
+        self.pixel(pos)
+    }
+    fn copy_rect(&mut self, src: Rect, dst: XY<usize>) {
+        frame_buffer::copy_rect_within(&mut self.pixels, self.size.x, 1, src, dst);
+    }
+    fn fill_row(&mut self, pos: XY<usize>, len: usize, color: PixelColor) {
+        let start = pos.y * self.size.x + pos.x;
+        self.pixels[start..start + len].fill(color);
+    }
+    fn write_row(&mut self, pos: XY<usize>, pixels: &[PixelColor]) {
+        let start = pos.y * self.size.x + pos.x;
+        self.pixels[start..start + pixels.len()].copy_from_slice(pixels);
+    }
+    fn read_row(&self, pos: XY<usize>, pixels: &mut [PixelColor]) {
+        let start = pos.y * self.size.x + pos.x;
+        pixels.copy_from_slice(&self.pixels[start..start + pixels.len()]);
+    }
+}
@@ -0,0 +1,103 @@
+use super::common::PixelColor;
+
+/// A color as selected by SGR escape sequences.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Color {
+    /// The console's default foreground or background color.
+    Default,
+    /// An entry of the 256-color palette; 0..16 are the standard and bright colors.
+    Indexed(u8),
+    Rgb(PixelColor),
+}
+
+impl Color {
+    /// Resolves the color against the console's `default`.
+    ///
+    /// `bold` selects the bright variant of the eight standard colors, as most terminals do.
+    pub fn resolve(self, default: PixelColor, bold: bool) -> PixelColor {
+        match self {
+            Color::Default => default,
+            Color::Indexed(i) if bold && i < 8 => palette(i + 8),
+            Color::Indexed(i) => palette(i),
+            Color::Rgb(color) => color,
+        }
+    }
+}
+
+/// Returns entry `i` of the xterm 256-color palette.
+pub fn palette(i: u8) -> PixelColor {
+    const STANDARD: [(u8, u8, u8); 16] = [
+        (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+        (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+        (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+        (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+    ];
+    match i {
+        0..=15 => {
+            let (r, g, b) = STANDARD[i as usize];
+            PixelColor { r, g, b }
+        }
+        // 6x6x6 color cube
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = i - 16;
+            PixelColor { r: level(i / 36), g: level(i / 6 % 6), b: level(i % 6) }
+        }
+        // grayscale ramp
+        232..=255 => {
+            let v = 8 + (i - 232) * 10;
+            PixelColor { r: v, g: v, b: v }
+        }
+    }
+}
+
+/// Rendition of a cell, as set by SGR escape sequences.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Attributes {
+    pub fg: Color,
+    pub bg: Color,
+    pub bold: bool,
+    pub underline: bool,
+    /// Swap foreground and background when drawing.
+    pub reverse: bool,
+}
+
+impl Attributes {
+    pub const DEFAULT: Attributes = Attributes {
+        fg: Color::Default,
+        bg: Color::Default,
+        bold: false,
+        underline: false,
+        reverse: false,
+    };
+}
+
+impl Default for Attributes {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A character on the console together with the attributes it is drawn with.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cell {
+    pub c: char,
+    pub attr: Attributes,
+}
+
+/// Stands in for the character in the right half of a double-width character,
+/// whose glyph the cell to its left draws.
+pub const CONTINUATION: char = '\0';
+
+impl Cell {
+    pub const BLANK: Cell = Cell { c: ' ', attr: Attributes::DEFAULT };
+
+    pub fn is_continuation(&self) -> bool {
+        self.c == CONTINUATION
+    }
+
+    /// An empty cell keeping the background of `attr`, as left behind by erasing.
+    pub fn erased(attr: Attributes) -> Self {
+        Cell { c: ' ', attr: Attributes { bg: attr.bg, ..Attributes::DEFAULT } }
+    }
+}
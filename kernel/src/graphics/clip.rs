@@ -0,0 +1,56 @@
+use super::{common::{PixelColor, XY}, frame_buffer::PixelWriter, rect::Rect};
+
+/// A drawing target that passes on only the drawing inside `clip`, e.g. to
+/// repaint one damaged area of the screen with code that draws more.
+pub struct ClippedWriter<'a> {
+    inner: &'a mut dyn PixelWriter,
+    /// Within the bounds of `inner`, and empty if nothing may be drawn.
+    clip: Rect,
+}
+
+impl<'a> ClippedWriter<'a> {
+    pub fn new(inner: &'a mut dyn PixelWriter, clip: Rect) -> Self {
+        let clip = clip.clip(inner.size()).unwrap_or(Rect::new(0, 0, 0, 0));
+        Self { inner, clip }
+    }
+    /// The part of the row of `len` pixels at `pos` inside the clip, as the
+    /// offset into the row and the clipped row.
+    fn clip_row(&self, pos: XY<usize>, len: usize) -> Option<(usize, Rect)> {
+        let row = Rect::new(pos.x as isize, pos.y as isize, len, 1).intersection(self.clip)?;
+        Some(((row.x - pos.x as isize) as usize, row))
+    }
+}
+
+impl PixelWriter for ClippedWriter<'_> {
+    fn size(&self) -> XY<usize> {
+        self.inner.size()
+    }
+    fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor) {
+        if self.clip.is_contained(XY::new(pos.x as isize, pos.y as isize)) {
+            self.inner.draw_pixel(pos, color);
+        }
+    }
+    fn read_pixel(&self, pos: XY<usize>) -> PixelColor {
+        self.inner.read_pixel(pos)
+    }
+    fn copy_rect(&mut self, src: Rect, dst: XY<usize>) {
+        let dst_rect = Rect::new(dst.x as isize, dst.y as isize, src.w, src.h);
+        let Some(visible) = dst_rect.intersection(self.clip) else { return };
+        let offset = XY::new(visible.x - dst_rect.x, visible.y - dst_rect.y);
+        let src = Rect::new(src.x + offset.x, src.y + offset.y, visible.w, visible.h);
+        self.inner.copy_rect(src, XY::new(visible.x as usize, visible.y as usize));
+    }
+    fn fill_row(&mut self, pos: XY<usize>, len: usize, color: PixelColor) {
+        if let Some((_, row)) = self.clip_row(pos, len) {
+            self.inner.fill_row(XY::new(row.x as usize, row.y as usize), row.w, color);
+        }
+    }
+    fn write_row(&mut self, pos: XY<usize>, pixels: &[PixelColor]) {
+        if let Some((skip, row)) = self.clip_row(pos, pixels.len()) {
+            self.inner.write_row(XY::new(row.x as usize, row.y as usize), &pixels[skip..skip + row.w]);
+        }
+    }
+    fn read_row(&self, pos: XY<usize>, pixels: &mut [PixelColor]) {
+        self.inner.read_row(pos, pixels);
+    }
+}
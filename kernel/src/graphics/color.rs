@@ -0,0 +1,114 @@
+//! Colors with transparency and the ways they combine with what is already drawn.
+//!
+//! The framebuffer itself is opaque, so blending always ends in a
+//! `PixelColor`. Sources are blended in premultiplied form, where each
+//! channel is already scaled by alpha, which saves a multiplication per
+//! channel and composes translucent layers correctly.
+
+use super::common::PixelColor;
+
+/// A color with straight alpha, 0 being fully transparent and 255 opaque.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba {
+    pub const TRANSPARENT: Self = Self::new(0, 0, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+    pub fn premultiply(self) -> Premultiplied {
+        let a = self.a;
+        Premultiplied { r: mul(self.r, a), g: mul(self.g, a), b: mul(self.b, a), a }
+    }
+}
+
+impl From<PixelColor> for Rgba {
+    fn from(color: PixelColor) -> Self {
+        Self::new(color.r, color.g, color.b, 255)
+    }
+}
+
+/// Drops the alpha channel; use `BlendMode::blend` to draw a translucent color onto a pixel.
+impl From<Rgba> for PixelColor {
+    fn from(color: Rgba) -> Self {
+        PixelColor { r: color.r, g: color.g, b: color.b }
+    }
+}
+
+/// A color whose channels are scaled by its alpha, so that none exceeds `a`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub struct Premultiplied {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Premultiplied {
+    pub const TRANSPARENT: Self = Self { r: 0, g: 0, b: 0, a: 0 };
+
+    /// The straight color, as exact as the rounding of `premultiply` allows.
+    pub fn unpremultiply(self) -> Rgba {
+        if self.a == 0 {
+            return Rgba::TRANSPARENT;
+        }
+        let div = |c: u8| ((c as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255) as u8;
+        Rgba::new(div(self.r), div(self.g), div(self.b), self.a)
+    }
+}
+
+impl From<Rgba> for Premultiplied {
+    fn from(color: Rgba) -> Self {
+        color.premultiply()
+    }
+}
+
+impl From<PixelColor> for Premultiplied {
+    fn from(color: PixelColor) -> Self {
+        Self { r: color.r, g: color.g, b: color.b, a: 255 }
+    }
+}
+
+/// How a source color is combined with the pixel below it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
+pub enum BlendMode {
+    /// The source painted over the destination, letting it show through by
+    /// the source's transparency.
+    #[default]
+    SourceOver,
+    /// The source light added to the destination, saturating at white, for
+    /// glows and highlights.
+    Additive,
+}
+
+impl BlendMode {
+    pub fn blend(self, src: Premultiplied, dst: PixelColor) -> PixelColor {
+        match self {
+            BlendMode::SourceOver => {
+                let keep = 255 - src.a;
+                let over = |s: u8, d: u8| s.saturating_add(mul(d, keep));
+                PixelColor { r: over(src.r, dst.r), g: over(src.g, dst.g), b: over(src.b, dst.b) }
+            }
+            BlendMode::Additive => PixelColor {
+                r: dst.r.saturating_add(src.r),
+                g: dst.g.saturating_add(src.g),
+                b: dst.b.saturating_add(src.b),
+            },
+        }
+    }
+}
+
+/// `x * y / 255`, rounded to nearest.
+fn mul(x: u8, y: u8) -> u8 {
+    let t = x as u32 * y as u32 + 128;
+    ((t + (t >> 8)) >> 8) as u8
+}
@@ -1,106 +1,1079 @@
-use core::{fmt::Write, str::from_utf8};
-use core::fmt;
+use alloc::{collections::VecDeque, format, string::String, vec, vec::Vec};
+use core::fmt::Write;
+use core::{fmt, mem, ops::Range};
 
 use spin::{Mutex, Once, MutexGuard};
 
+use super::cell::{Attributes, Cell, Color, CONTINUATION};
 use super::common::XY;
-use super::{frame_buffer::{PixelWriter, self}, font::{self, Font}, common::PixelColor};
+use super::rect::Rect;
+use super::virtual_console::VirtualConsoles;
+use super::window_manager;
+use super::vt100::{Action, Csi, Parser};
+use crate::input::{Key, KeyEvent};
+use super::{frame_buffer::{PixelWriter, self}, font::{self, Font, Hankaku}, common::PixelColor};
 
-const CONSOLE_BG_COLOR: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
-const CONSOLE_FG_COLOR: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
-const SHINONOME_FONT: &[u8] = include_bytes!("../../resources/hankaku.bin") as &[u8];
+const DEFAULT_BG_COLOR: PixelColor = PixelColor { r: 0, g: 0, b: 0 };
+const DEFAULT_FG_COLOR: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const TAB_WIDTH: usize = 8;
+/// Rows kept after scrolling off the top of the screen, unless changed with `set_scrollback_limit`.
+pub const DEFAULT_SCROLLBACK_LINES: usize = 1000;
+/// How long a blinking cursor stays on or off, for the timer calling `blink_cursor`.
+pub const CURSOR_BLINK_MS: u64 = 500;
+pub(crate) static SHINONOME_FONT: Hankaku = Hankaku::new(include_bytes!("../../resources/hankaku.bin"));
 
 // spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
-pub static CONSOLE: Once<Mutex<Console>> = Once::new();
+// Lock order: CONSOLES before the back buffer and the window manager, which
+// `with_pixel_writer` takes; never lock CONSOLES while holding either
+pub static CONSOLES: Once<Mutex<VirtualConsoles>> = Once::new();
 
 pub fn init() {
-    let font = font::Font::new(SHINONOME_FONT);
-
-    CONSOLE.call_once(|| Mutex::new(Console::new(font)));
     frame_buffer::lock_pixel_writer(|mut w| {
-        lock_console(|console| console.flush(&mut w))
+        let font = Font::new(&SHINONOME_FONT);
+        let consoles = CONSOLES.call_once(|| Mutex::new(VirtualConsoles::new(font, w.size())));
+        consoles.lock().active_mut().flush(&mut *w);
     });
 }
 
-pub fn lock_console<F: FnMut(MutexGuard<Console>)>(mut f: F) {
-    let console = CONSOLE.get()
-        .expect("console::lock_console is called before console::init");
-    f(console.lock())
+pub fn lock_consoles<F: FnMut(MutexGuard<VirtualConsoles>)>(mut f: F) {
+    let consoles = CONSOLES.get()
+        .expect("console::lock_consoles is called before console::init");
+    f(consoles.lock())
+}
+
+/// Runs `f` on what the consoles are drawn into: their window once the window
+/// manager runs, the framebuffer before.
+fn with_pixel_writer(mut f: impl FnMut(&mut dyn PixelWriter)) {
+    if !window_manager::draw_console(&mut f) {
+        frame_buffer::lock_pixel_writer(|mut w| f(&mut *w));
+    }
+}
+
+/// Re-lays out the consoles after the framebuffer or their window changed its size.
+pub fn resize() {
+    lock_consoles(|mut consoles| with_pixel_writer(|w| consoles.resize(w)));
+}
+
+/// Switches all consoles to `font`, e.g. to add a fallback with full-width glyphs.
+pub fn set_font(font: Font<'static>) {
+    lock_consoles(|mut consoles| with_pixel_writer(|w| consoles.set_font(w, font)));
+}
+
+/// Toggles the cursor of the active console if it blinks; called from a timer
+/// every `CURSOR_BLINK_MS`.
+pub fn blink_cursor() {
+    lock_consoles(|mut consoles| with_pixel_writer(|w| consoles.active_mut().blink_cursor(w)));
+}
+
+/// Passes a key press to the consoles, returning whether it was consumed by
+/// switching consoles or paging through the history.
+pub fn handle_key(event: KeyEvent) -> bool {
+    let mut consumed = false;
+    lock_consoles(|mut consoles| with_pixel_writer(|w| consumed = consoles.handle_key(w, event)));
+    consumed
 }
 
-const ROWS: usize = 25;
-const COLUMNS: usize = 80;
+/// A row that scrolled off the top of the screen.
+struct HistoryRow {
+    cells: Vec<Cell>,
+    /// Whether the line continues on the next row because it was wrapped.
+    wrapped: bool,
+}
+
+/// How the cursor is drawn, as chosen with DECSCUSR (`ESC [ <n> SP q`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CursorStyle {
+    /// The cell shown in reverse video.
+    Block,
+    /// A line along the bottom of the cell.
+    Underline,
+    /// A line along the left edge of the cell.
+    Bar,
+}
+
+/// Pixel movement caused by scrolling `top..=bottom` (rows of the grid) by
+/// `rows`, positive upwards, that `render` still has to apply.
+#[derive(Clone, Copy, Debug)]
+struct Scroll {
+    top: usize,
+    bottom: usize,
+    rows: isize,
+}
+
+/// A text console that keeps its contents as a grid of cells.
+///
+/// The grid is as large as fits into the drawable area with the console's font.
+/// Text written to the console may contain VT100/xterm escape sequences for
+/// colors, cursor movement, erasing and scroll regions; see `vt100`.
+///
+/// Writes only update the grid and mark the touched cells dirty; `render` then
+/// repaints just those cells. Scrolling moves the already drawn pixel rows
+/// within the framebuffer instead of redrawing every character.
+///
+/// The cursor is drawn by `render` once the cells are up to date, so that it
+/// does not flicker through the positions passed while writing. It can be
+/// hidden and restyled with escape sequences (DECTCEM `ESC [ ? 25 h/l`, `ESC [
+/// ? 12 h/l` for blinking, and DECSCUSR), and blinks as `blink_cursor` is called.
+///
+/// Rows scrolling off the top of the screen go to a scrollback history, which
+/// can be brought into view with Shift+PageUp/PageDown (see `handle_key`).
+/// Rows remember whether their line was wrapped automatically, so that `reflow`
+/// can wrap the lines again when the console changes its width.
+///
+/// A hidden console keeps updating its grid but does not draw, as it is not
+/// the one shown on the framebuffer (see `VirtualConsoles`).
 pub struct Console<'a> {
     cursor: XY<usize>,
-    buf: [[u8; COLUMNS]; ROWS],
+    /// Size of the grid in characters.
+    size: XY<usize>,
+    /// Row-major cells of the grid.
+    buf: Vec<Cell>,
+    dirty: Vec<bool>,
+    /// For each row, whether its line continues on the next row because it was wrapped.
+    wrapped: Vec<bool>,
+    /// First and last cell index of the highlighted text, if any.
+    selection: Option<(usize, usize)>,
+    pending_scroll: Option<Scroll>,
+    /// Attributes given to newly written characters.
+    attr: Attributes,
+    saved_cursor: (XY<usize>, Attributes),
+    /// First and last row affected by scrolling, as set by DECSTBM.
+    scroll_region: (usize, usize),
+    default_fg: PixelColor,
+    default_bg: PixelColor,
+    parser: Parser,
+    /// Rows that scrolled off the screen, oldest first.
+    history: VecDeque<HistoryRow>,
+    scrollback_limit: usize,
+    /// How many rows of history the view is scrolled back; 0 shows the grid.
+    view_offset: usize,
+    visible: bool,
+    cursor_style: CursorStyle,
+    cursor_blinks: bool,
+    /// Whether the cursor is shown at all, as set by DECTCEM.
+    cursor_enabled: bool,
+    /// Whether a blinking cursor is in its visible phase.
+    blink_on: bool,
+    /// Where the cursor was last drawn, moved along with scrolled pixels.
+    drawn_cursor: Option<XY<usize>>,
     font: Font<'a>,
 }
 impl<'a> Console<'a> {
-    pub fn new(font: Font<'a>) -> Self {
+    /// Creates a console filling `pixel_size`, the resolution of the drawing target.
+    pub fn new(font: Font<'a>, pixel_size: XY<usize>) -> Self {
+        let size = Self::grid_size(&font, pixel_size);
         Self {
             cursor: XY::new(0, 0),
-            buf: [[0; COLUMNS]; ROWS],
+            size,
+            buf: vec![Cell::BLANK; size.x * size.y],
+            dirty: vec![true; size.x * size.y],
+            wrapped: vec![false; size.y],
+            selection: None,
+            pending_scroll: None,
+            attr: Attributes::DEFAULT,
+            saved_cursor: (XY::new(0, 0), Attributes::DEFAULT),
+            scroll_region: (0, size.y - 1),
+            default_fg: DEFAULT_FG_COLOR,
+            default_bg: DEFAULT_BG_COLOR,
+            parser: Parser::new(),
+            history: VecDeque::new(),
+            scrollback_limit: DEFAULT_SCROLLBACK_LINES,
+            view_offset: 0,
+            visible: true,
+            cursor_style: CursorStyle::Block,
+            cursor_blinks: true,
+            cursor_enabled: true,
+            blink_on: true,
+            drawn_cursor: None,
             font
         }
     }
-    pub fn flush(&self, pixel_writer: &mut PixelWriter) {
-        for y in 0..(ROWS * self.font.char_size().y) {
-            for x in 0..(COLUMNS * self.font.char_size().x) {
-                pixel_writer.draw_pixel(XY::new(x, y), CONSOLE_BG_COLOR);
+    fn grid_size(font: &Font, pixel_size: XY<usize>) -> XY<usize> {
+        let char_size = font.char_size();
+        // keep at least one cell so that the cursor always points into the grid
+        XY::new((pixel_size.x / char_size.x).max(1), (pixel_size.y / char_size.y).max(1))
+    }
+    /// Number of columns and rows.
+    pub fn size(&self) -> XY<usize> {
+        self.size
+    }
+    pub fn cursor(&self) -> XY<usize> {
+        self.cursor
+    }
+    pub fn cell(&self, pos: XY<usize>) -> Cell {
+        self.buf[self.index(pos)]
+    }
+    fn index(&self, pos: XY<usize>) -> usize {
+        pos.y * self.size.x + pos.x
+    }
+    /// Sets the colors `Color::Default` stands for. Takes effect on the next `flush`.
+    pub fn set_default_colors(&mut self, fg: PixelColor, bg: PixelColor) {
+        self.default_fg = fg;
+        self.default_bg = bg;
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    /// Shows or hides the console. A console becoming visible has to be `flush`ed.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+    pub fn cursor_style(&self) -> CursorStyle {
+        self.cursor_style
+    }
+    pub fn cursor_blinks(&self) -> bool {
+        self.cursor_blinks
+    }
+    /// Changes how the cursor is drawn. Takes effect on the next `render`.
+    pub fn set_cursor_style(&mut self, style: CursorStyle, blinks: bool) {
+        self.cursor_style = style;
+        self.cursor_blinks = blinks;
+        self.blink_on = true;
+        self.invalidate_cursor();
+    }
+    pub fn is_cursor_enabled(&self) -> bool {
+        self.cursor_enabled
+    }
+    /// Shows or hides the cursor like DECTCEM. Takes effect on the next `render`.
+    pub fn set_cursor_enabled(&mut self, enabled: bool) {
+        self.cursor_enabled = enabled;
+    }
+    /// Toggles a blinking cursor between shown and hidden and redraws it.
+    pub fn blink_cursor(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        // the history shown while scrolled back has no cursor
+        if self.cursor_enabled && self.cursor_blinks && self.view_offset == 0 {
+            self.blink_on = !self.blink_on;
+            self.render(pixel_writer);
+        }
+    }
+    /// Where the cursor is to be drawn, at the start of the character it is on.
+    fn shown_cursor(&self) -> Option<XY<usize>> {
+        if !self.cursor_enabled || self.cursor_blinks && !self.blink_on {
+            return None;
+        }
+        // after writing the last column the cursor waits there for the line to wrap
+        let pos = self.clamp(self.cursor);
+        if pos.x > 0 && self.buf[self.index(pos)].is_continuation() {
+            return Some(XY::new(pos.x - 1, pos.y));
+        }
+        Some(pos)
+    }
+    /// Has the cursor redrawn by the next `render`, e.g. after its style changed.
+    fn invalidate_cursor(&mut self) {
+        if let Some(pos) = self.drawn_cursor {
+            let i = self.index(pos);
+            self.dirty[i] = true;
+        }
+    }
+    /// Sets how many rows of history are kept, dropping the oldest ones beyond it.
+    pub fn set_scrollback_limit(&mut self, lines: usize) {
+        self.scrollback_limit = lines;
+        self.trim_history();
+    }
+    /// Rows that scrolled off the top of the screen, oldest first.
+    ///
+    /// Rows keep the width the grid had when they were scrolled off.
+    pub fn history(&self) -> impl Iterator<Item = &[Cell]> {
+        self.history.iter().map(|row| row.cells.as_slice())
+    }
+    /// Writes the history followed by the screen contents as plain text, one
+    /// line per row without trailing blanks, e.g. to save boot messages over serial.
+    pub fn dump_history(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        let screen = self.buf.chunks(self.size.x);
+        for row in self.history().chain(screen) {
+            let len = row.iter().rposition(|cell| cell.c != ' ').map_or(0, |i| i + 1);
+            for cell in row[..len].iter().filter(|cell| !cell.is_continuation()) {
+                out.write_char(cell.c)?;
             }
+            out.write_char('\n')?;
         }
+        Ok(())
+    }
+    /// Number of rows the view is scrolled back into the history.
+    pub fn view_offset(&self) -> usize {
+        self.view_offset
+    }
+    /// Scrolls the view `lines` rows back into the history, or forward if negative.
+    ///
+    /// Takes effect on the next `render`. While scrolled back, new output does
+    /// not move the view.
+    pub fn scroll_view(&mut self, lines: isize) {
+        let offset = self.view_offset.saturating_add_signed(lines).min(self.history.len());
+        if offset != self.view_offset {
+            self.view_offset = offset;
+            self.dirty.fill(true);
+            self.pending_scroll = None;
+        }
+    }
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_view(-(self.view_offset as isize));
     }
-    pub fn put_string(&mut self, pixel_writer: &mut PixelWriter, s: &str) {
+    /// Handles the console's own key bindings, returning whether `event` was consumed.
+    ///
+    /// Shift+PageUp/PageDown page through the history by half a screen. Any
+    /// other key returns the view to the bottom and is left to the caller.
+    pub fn handle_key(&mut self, pixel_writer: &mut dyn PixelWriter, event: KeyEvent) -> bool {
+        let page = (self.size.y / 2).max(1) as isize;
+        let consumed = match (event.key, event.modifiers.shift) {
+            (Key::PageUp, true) => { self.scroll_view(page); true }
+            (Key::PageDown, true) => { self.scroll_view(-page); true }
+            _ => { self.scroll_to_bottom(); false }
+        };
+        self.render(pixel_writer);
+        consumed
+    }
+    fn push_history(&mut self, cells: Vec<Cell>, wrapped: bool) {
+        self.history.push_back(HistoryRow { cells, wrapped });
+        // keep showing the same rows while scrolled back
+        if self.view_offset > 0 {
+            self.view_offset += 1;
+        }
+        self.trim_history();
+    }
+    fn trim_history(&mut self) {
+        while self.history.len() > self.scrollback_limit {
+            self.history.pop_front();
+        }
+        self.view_offset = self.view_offset.min(self.history.len());
+    }
+    /// Replaces the font, re-laying out the grid in case the character size changed.
+    pub fn set_font(&mut self, font: Font<'a>, pixel_size: XY<usize>) {
+        self.font = font;
+        self.resize(pixel_size);
+    }
+    /// Re-lays out the grid for a drawing target of `pixel_size`.
+    ///
+    /// Text is kept anchored at the top left; if the cursor's row would no
+    /// longer fit, the topmost rows are dropped instead. Everything is marked
+    /// dirty, so a `flush` is needed afterwards.
+    pub fn resize(&mut self, pixel_size: XY<usize>) {
+        let size = Self::grid_size(&self.font, pixel_size);
+        let dropped_rows = (self.cursor.y + 1).saturating_sub(size.y);
+        for y in 0..dropped_rows {
+            let row = self.buf[self.index(XY::new(0, y))..][..self.size.x].to_vec();
+            self.push_history(row, self.wrapped[y]);
+        }
+        let mut buf = vec![Cell::BLANK; size.x * size.y];
+        let mut wrapped = vec![false; size.y];
+        for y in 0..size.y.min(self.size.y - dropped_rows) {
+            for x in 0..size.x.min(self.size.x) {
+                buf[y * size.x + x] = self.cell(XY::new(x, y + dropped_rows));
+            }
+            wrapped[y] = self.wrapped[y + dropped_rows];
+        }
+        let cursor = XY::new(self.cursor.x.min(size.x), self.cursor.y - dropped_rows);
+        self.replace_grid(size, buf, wrapped, cursor);
+        // narrowing may have cut double-width characters in half
+        for y in 0..size.y {
+            self.repair_row(y);
+        }
+    }
+    /// Re-lays out the grid for a drawing target of `pixel_size` like `resize`,
+    /// but wraps lines to the new width again, history included, instead of
+    /// cutting them off.
+    ///
+    /// The cursor stays on the character it was on, and the screen shows the
+    /// last rows of text, or starts at the cursor's row if that would be above them.
+    pub fn reflow(&mut self, pixel_size: XY<usize>) {
+        let size = Self::grid_size(&self.font, pixel_size);
+        // join wrapped rows into lines, with the cursor as a line and an offset into it
+        let used_rows = (0..self.size.y).rev()
+            .find(|&y| self.buf[self.index(XY::new(0, y))..][..self.size.x].iter().any(|&cell| cell != Cell::BLANK))
+            .map_or(0, |y| y + 1)
+            .max(self.cursor.y + 1);
+        let history = mem::take(&mut self.history);
+        let screen = (0..used_rows).map(|y| HistoryRow {
+            cells: self.buf[self.index(XY::new(0, y))..][..self.size.x].to_vec(),
+            wrapped: self.wrapped[y],
+        });
+        let cursor_row = history.len() + self.cursor.y;
+        let old_rows: Vec<HistoryRow> = history.into_iter().chain(screen).collect();
+        let mut lines: Vec<Vec<Cell>> = Vec::new();
+        let mut cursor = (0, 0);
+        let mut continued = false;
+        for (i, row) in old_rows.iter().enumerate() {
+            if !continued {
+                lines.push(Vec::new());
+            }
+            let index = lines.len() - 1;
+            let line = &mut lines[index];
+            let mut len = if row.wrapped {
+                // a double-width character that did not fit left a blank behind
+                let next_wide = old_rows.get(i + 1).is_some_and(|next| font::char_width(next.cells[0].c) == 2);
+                row.cells.len() - (next_wide && row.cells.last() == Some(&Cell::BLANK)) as usize
+            } else {
+                row.cells.iter().rposition(|&cell| cell != Cell::BLANK).map_or(0, |x| x + 1)
+            };
+            if i == cursor_row {
+                // keep the blanks the cursor moved over
+                len = len.max(self.cursor.x.min(row.cells.len()));
+                cursor = (index, line.len() + self.cursor.x);
+            }
+            line.extend_from_slice(&row.cells[..len]);
+            continued = row.wrapped;
+        }
+
+        let mut rows = Vec::new();
+        let mut new_cursor = XY::new(0, 0);
+        for (i, line) in lines.iter().enumerate() {
+            let cursor_offset = (i == cursor.0).then_some(cursor.1);
+            rows.extend(wrap_line(line, size.x, cursor_offset, &mut new_cursor, rows.len()));
+        }
+        // the screen ends with the last line but must show the cursor's row
+        let first = rows.len().saturating_sub(size.y).min(new_cursor.y);
+        let mut buf = vec![Cell::BLANK; size.x * size.y];
+        let mut wrapped = vec![false; size.y];
+        for (y, row) in rows.drain(first..).take(size.y).enumerate() {
+            buf[y * size.x..][..size.x].copy_from_slice(&row.cells);
+            wrapped[y] = row.wrapped;
+        }
+        for row in rows {
+            self.push_history(row.cells, row.wrapped);
+        }
+        self.replace_grid(size, buf, wrapped, XY::new(new_cursor.x, new_cursor.y - first));
+    }
+    /// Installs a new grid of `size` after `resize` or `reflow`, resetting
+    /// what refers to positions in the old one.
+    fn replace_grid(&mut self, size: XY<usize>, buf: Vec<Cell>, wrapped: Vec<bool>, cursor: XY<usize>) {
+        self.buf = buf;
+        self.wrapped = wrapped;
+        self.dirty = vec![true; size.x * size.y];
+        self.size = size;
+        self.selection = None;
+        self.pending_scroll = None;
+        self.view_offset = 0;
+        self.drawn_cursor = None;
+        self.scroll_region = (0, size.y - 1);
+        self.cursor = cursor;
+        self.saved_cursor.0 = self.clamp(self.saved_cursor.0);
+    }
+    /// Highlights the text from `from` to `to`, both included, in reading
+    /// order as when selecting with the mouse. Takes effect on the next `render`.
+    pub fn select(&mut self, from: XY<usize>, to: XY<usize>) {
+        let (from, to) = (self.index(self.clamp(from)), self.index(self.clamp(to)));
+        self.set_selection(Some((from.min(to), from.max(to))));
+    }
+    pub fn clear_selection(&mut self) {
+        self.set_selection(None);
+    }
+    /// First and last cell of the highlighted text.
+    pub fn selection(&self) -> Option<(XY<usize>, XY<usize>)> {
+        let position = |i: usize| XY::new(i % self.size.x, i / self.size.x);
+        self.selection.map(|(start, end)| (position(start), position(end)))
+    }
+    /// The highlighted text, with a line break after each row whose line was
+    /// not wrapped and without the blanks after the end of a line.
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection?;
+        let mut text = String::new();
+        for y in start / self.size.x..=end / self.size.x {
+            let row = self.index(XY::new(0, y));
+            let last = end.min(row + self.size.x - 1);
+            let cells = &self.buf[start.max(row)..=last];
+            let line_end = last == row + self.size.x - 1;
+            // blanks count only if text follows them on the line
+            let rest_blank = self.buf[last + 1..row + self.size.x].iter().all(|cell| cell.c == ' ');
+            let len = if rest_blank && !self.wrapped[y] {
+                cells.iter().rposition(|cell| cell.c != ' ').map_or(0, |x| x + 1)
+            } else {
+                cells.len()
+            };
+            text.extend(cells[..len].iter().filter(|cell| !cell.is_continuation()).map(|cell| cell.c));
+            if line_end && !self.wrapped[y] && y < end / self.size.x {
+                text.push('\n');
+            }
+        }
+        Some(text)
+    }
+    fn set_selection(&mut self, selection: Option<(usize, usize)>) {
+        if selection == self.selection {
+            return;
+        }
+        for (start, end) in [self.selection, selection].into_iter().flatten() {
+            self.dirty[start..=end].fill(true);
+        }
+        self.selection = selection;
+    }
+    fn is_selected(&self, i: usize) -> bool {
+        self.selection.is_some_and(|(start, end)| (start..=end).contains(&i))
+    }
+    /// Repaints the whole console, e.g. after something else has drawn over it.
+    pub fn flush(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        self.dirty.fill(true);
+        self.pending_scroll = None;
+        self.drawn_cursor = None;
+        self.render(pixel_writer);
+    }
+    pub fn put_string(&mut self, pixel_writer: &mut dyn PixelWriter, s: &str) {
+        // keep the cursor steady while output is arriving
+        self.blink_on = true;
         for c in s.chars() {
-            if c == '\n' {
-                self.newline(pixel_writer);
-            } else if self.cursor.x < COLUMNS - 1 {
-                let pos = XY::new(self.cursor.x * self.font.char_size().x,
-                    self.cursor.y * self.font.char_size().y);
-                self.font.draw_char(pixel_writer, pos,
-                    CONSOLE_FG_COLOR, CONSOLE_BG_COLOR, c);
-                self.buf[self.cursor.y][self.cursor.x] = c as u8;
-                self.cursor.x += 1;
+            match self.parser.advance(c) {
+                Some(Action::Print(c)) => self.put_char(c),
+                Some(Action::Control(c)) => self.control(c),
+                Some(Action::Esc(c)) => self.esc(c),
+                Some(Action::Csi(csi)) => self.csi(&csi),
+                None => {}
+            }
+        }
+        self.render(pixel_writer);
+    }
+    fn put_char(&mut self, c: char) {
+        let mut width = font::char_width(c);
+        let c = if width > self.size.x {
+            width = 1;
+            char::REPLACEMENT_CHARACTER
+        } else {
+            c
+        };
+        // wrap only once another character follows, so that a line of
+        // exactly as many characters as columns does not leave an empty line behind;
+        // a double-width character not fitting into the last column wraps as well
+        if self.cursor.x + width > self.size.x {
+            self.wrapped[self.cursor.y] = true;
+            self.newline();
+        }
+        let i = self.index(self.cursor);
+        for i in i..i + width {
+            self.split_wide(i);
+        }
+        self.buf[i] = Cell { c, attr: self.attr };
+        self.dirty[i] = true;
+        if width == 2 {
+            self.buf[i + 1] = Cell { c: CONTINUATION, attr: self.attr };
+            self.dirty[i + 1] = true;
+        }
+        self.cursor.x += width;
+    }
+    /// Blanks the other half of the double-width character cell `i` belongs to,
+    /// before `i` gets overwritten.
+    fn split_wide(&mut self, i: usize) {
+        let x = i % self.size.x;
+        let other = if self.buf[i].is_continuation() && x > 0 {
+            i - 1
+        } else if x + 1 < self.size.x && self.buf[i + 1].is_continuation() {
+            i + 1
+        } else {
+            return;
+        };
+        self.buf[other] = Cell::erased(self.buf[other].attr);
+        self.dirty[other] = true;
+    }
+    /// Blanks halves of double-width characters in row `y` whose other half was overwritten.
+    fn repair_row(&mut self, y: usize) {
+        let row = y * self.size.x;
+        for x in 0..self.size.x {
+            let i = row + x;
+            let orphan = if self.buf[i].is_continuation() {
+                x == 0 || font::char_width(self.buf[i - 1].c) != 2
+            } else {
+                font::char_width(self.buf[i].c) == 2
+                    && (x + 1 == self.size.x || !self.buf[i + 1].is_continuation())
+            };
+            if orphan {
+                self.buf[i] = Cell::erased(self.buf[i].attr);
+                self.dirty[i] = true;
             }
         }
     }
-    fn newline(&mut self, pixel_writer: &mut PixelWriter) {
+    fn control(&mut self, c: char) {
+        match c {
+            '\n' | '\x0b' | '\x0c' => self.newline(),
+            '\r' => self.cursor.x = 0,
+            '\x08' => self.cursor.x = self.cursor.x.min(self.size.x - 1).saturating_sub(1),
+            '\t' => self.cursor.x = ((self.cursor.x / TAB_WIDTH + 1) * TAB_WIDTH).min(self.size.x - 1),
+            _ => {}
+        }
+    }
+    fn esc(&mut self, c: char) {
+        match c {
+            '7' => self.saved_cursor = (self.cursor, self.attr),
+            '8' => (self.cursor, self.attr) = self.saved_cursor,
+            'D' => self.line_feed(),
+            'E' => self.newline(),
+            'M' => self.reverse_index(),
+            'c' => self.reset(),
+            _ => {}
+        }
+    }
+    fn csi(&mut self, csi: &Csi) {
+        match (csi.private, csi.intermediate, csi.final_char) {
+            (None, None, _) => {}
+            (Some('?'), None, 'h' | 'l') => return self.set_private_modes(csi),
+            (None, Some(' '), 'q') => return self.set_cursor_shape(csi.param(0, 0)),
+            _ => return,
+        }
+        let n = csi.param(0, 1) as usize;
+        let XY { x, y } = self.cursor;
+        let (top, bottom) = self.scroll_region;
+        match csi.final_char {
+            // cursor movement stops at the scroll region's margins when starting inside it
+            'A' => self.cursor.y = y.saturating_sub(n).max(if y >= top { top } else { 0 }),
+            'B' => self.cursor.y = (y + n).min(if y <= bottom { bottom } else { self.size.y - 1 }),
+            'C' => self.cursor.x = (x + n).min(self.size.x - 1),
+            'D' => self.cursor.x = x.min(self.size.x - 1).saturating_sub(n),
+            'E' => self.cursor = XY::new(0, (y + n).min(if y <= bottom { bottom } else { self.size.y - 1 })),
+            'F' => self.cursor = XY::new(0, y.saturating_sub(n).max(if y >= top { top } else { 0 })),
+            'G' | '`' => self.cursor.x = (n - 1).min(self.size.x - 1),
+            'd' => self.cursor.y = (n - 1).min(self.size.y - 1),
+            'H' | 'f' => {
+                let row = csi.param(0, 1) as usize - 1;
+                let column = csi.param(1, 1) as usize - 1;
+                self.cursor = self.clamp(XY::new(column, row));
+            }
+            'J' => {
+                let cursor = self.index(self.clamp(self.cursor));
+                match csi.param(0, 0) {
+                    0 => self.erase(cursor..self.buf.len()),
+                    1 => self.erase(0..cursor + 1),
+                    2 | 3 => self.erase(0..self.buf.len()),
+                    _ => {}
+                }
+            }
+            'K' => {
+                let line = self.index(XY::new(0, y));
+                let cursor = self.index(self.clamp(self.cursor));
+                match csi.param(0, 0) {
+                    0 => self.erase(cursor..line + self.size.x),
+                    1 => self.erase(line..cursor + 1),
+                    2 => self.erase(line..line + self.size.x),
+                    _ => {}
+                }
+            }
+            'X' => {
+                let cursor = self.index(self.clamp(self.cursor));
+                let line_end = self.index(XY::new(0, y)) + self.size.x;
+                self.erase(cursor..(cursor + n).min(line_end));
+            }
+            '@' | 'P' => self.shift_line(n, csi.final_char == '@'),
+            'L' | 'M' if (top..=bottom).contains(&y) => {
+                let rows = if csi.final_char == 'L' { -(n as isize) } else { n as isize };
+                self.scroll(y, bottom, rows);
+                self.cursor.x = 0;
+            }
+            'S' => self.scroll(top, bottom, n as isize),
+            'T' => self.scroll(top, bottom, -(n as isize)),
+            'm' => self.sgr(csi),
+            'r' => {
+                let top = csi.param(0, 1) as usize - 1;
+                let bottom = (csi.param(1, self.size.y as u16) as usize - 1).min(self.size.y - 1);
+                if top < bottom {
+                    self.scroll_region = (top, bottom);
+                    self.cursor = XY::new(0, 0);
+                }
+            }
+            's' => self.saved_cursor = (self.cursor, self.attr),
+            'u' => (self.cursor, self.attr) = self.saved_cursor,
+            _ => {}
+        }
+    }
+    /// DEC private modes set by `ESC [ ? ... h` and reset by `ESC [ ? ... l`.
+    fn set_private_modes(&mut self, csi: &Csi) {
+        let set = csi.final_char == 'h';
+        for &mode in csi.params() {
+            match mode {
+                12 => self.set_cursor_style(self.cursor_style, set),
+                25 => self.cursor_enabled = set,
+                _ => {}
+            }
+        }
+    }
+    /// DECSCUSR: odd numbers and 0 select a blinking cursor, even numbers a steady one.
+    fn set_cursor_shape(&mut self, n: u16) {
+        let style = match n {
+            0..=2 => CursorStyle::Block,
+            3 | 4 => CursorStyle::Underline,
+            5 | 6 => CursorStyle::Bar,
+            _ => return,
+        };
+        self.set_cursor_style(style, n == 0 || n % 2 == 1);
+    }
+    /// Select Graphic Rendition: applies the attribute changes of an `ESC [ ... m` sequence.
+    fn sgr(&mut self, csi: &Csi) {
+        let params = csi.params();
+        if params.is_empty() {
+            self.attr = Attributes::DEFAULT;
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => self.attr = Attributes::DEFAULT,
+                1 => self.attr.bold = true,
+                4 => self.attr.underline = true,
+                7 => self.attr.reverse = true,
+                22 => self.attr.bold = false,
+                24 => self.attr.underline = false,
+                27 => self.attr.reverse = false,
+                p @ 30..=37 => self.attr.fg = Color::Indexed(p as u8 - 30),
+                p @ 40..=47 => self.attr.bg = Color::Indexed(p as u8 - 40),
+                p @ 90..=97 => self.attr.fg = Color::Indexed(p as u8 - 90 + 8),
+                p @ 100..=107 => self.attr.bg = Color::Indexed(p as u8 - 100 + 8),
+                39 => self.attr.fg = Color::Default,
+                49 => self.attr.bg = Color::Default,
+                p @ (38 | 48) => {
+                    let (color, used) = extended_color(&params[i + 1..]);
+                    if let Some(color) = color {
+                        if p == 38 { self.attr.fg = color } else { self.attr.bg = color }
+                    }
+                    i += used;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+    fn clamp(&self, pos: XY<usize>) -> XY<usize> {
+        XY::new(pos.x.min(self.size.x - 1), pos.y.min(self.size.y - 1))
+    }
+    /// Blanks the cells in `range` of `buf`, keeping the current background color.
+    fn erase(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        self.buf[range.clone()].fill(Cell::erased(self.attr));
+        self.dirty[range.clone()].fill(true);
+        // rows erased completely no longer continue a line
+        let columns = self.size.x;
+        if let Some(rows) = self.wrapped.get_mut(range.start.div_ceil(columns)..range.end / columns) {
+            rows.fill(false);
+        }
+        self.repair_row(range.start / self.size.x);
+        self.repair_row((range.end - 1) / self.size.x);
+    }
+    /// Inserts (ICH) or deletes (DCH) `n` blank cells at the cursor, shifting the rest of the line.
+    fn shift_line(&mut self, n: usize, insert: bool) {
+        let cursor = self.index(self.clamp(self.cursor));
+        let line_end = self.index(XY::new(0, self.cursor.y)) + self.size.x;
+        let n = n.min(line_end - cursor);
+        if insert {
+            self.buf.copy_within(cursor..line_end - n, cursor + n);
+            self.erase(cursor..cursor + n);
+        } else {
+            self.buf.copy_within(cursor + n..line_end, cursor);
+            self.erase(line_end - n..line_end);
+        }
+        self.dirty[cursor..line_end].fill(true);
+        self.repair_row(self.cursor.y);
+    }
+    fn newline(&mut self) {
         self.cursor.x = 0;
-        if self.cursor.y < ROWS - 1 {
+        self.line_feed();
+    }
+    fn line_feed(&mut self) {
+        if self.cursor.y == self.scroll_region.1 {
+            self.scroll(self.scroll_region.0, self.scroll_region.1, 1);
+        } else if self.cursor.y < self.size.y - 1 {
             self.cursor.y += 1;
+        }
+    }
+    fn reverse_index(&mut self) {
+        if self.cursor.y == self.scroll_region.0 {
+            self.scroll(self.scroll_region.0, self.scroll_region.1, -1);
+        } else if self.cursor.y > 0 {
+            self.cursor.y -= 1;
+        }
+    }
+    fn reset(&mut self) {
+        self.attr = Attributes::DEFAULT;
+        self.saved_cursor = (XY::new(0, 0), Attributes::DEFAULT);
+        self.scroll_region = (0, self.size.y - 1);
+        self.erase(0..self.buf.len());
+        self.cursor = XY::new(0, 0);
+        self.cursor_enabled = true;
+        self.set_cursor_style(CursorStyle::Block, true);
+    }
+    /// Scrolls rows `top..=bottom` by `rows`, upwards if positive, filling the
+    /// rows that become empty with blanks.
+    fn scroll(&mut self, top: usize, bottom: usize, rows: isize) {
+        let columns = self.size.x;
+        let region = top * columns..(bottom + 1) * columns;
+        let n = rows.unsigned_abs().min(bottom + 1 - top) * columns;
+        if n == 0 {
+            return;
+        }
+
+        // only one region can have its pixels moved at render time; an earlier
+        // scroll of another region falls back to redrawing that region
+        self.pending_scroll = match self.pending_scroll {
+            Some(s) if (s.top, s.bottom) == (top, bottom) => Some(Scroll { rows: s.rows + rows, ..s }),
+            Some(s) => {
+                self.dirty[s.top * columns..(s.bottom + 1) * columns].fill(true);
+                Some(Scroll { top, bottom, rows })
+            }
+            None => Some(Scroll { top, bottom, rows }),
+        };
+
+        // moved text is no longer where it was selected
+        self.clear_selection();
+
+        // the drawn cursor moves along with the pixels, or out of the region
+        if let Some(pos) = self.drawn_cursor.filter(|pos| (top..=bottom).contains(&pos.y)) {
+            let y = pos.y as isize - rows;
+            self.drawn_cursor = (top as isize..=bottom as isize).contains(&y).then(|| XY::new(pos.x, y as usize));
+        }
+
+        // like xterm, only rows leaving the top of the screen are kept
+        if rows > 0 && top == 0 {
+            for y in 0..n / columns {
+                self.push_history(self.buf[y * columns..][..columns].to_vec(), self.wrapped[y]);
+            }
+        }
+
+        let wrapped = &mut self.wrapped[top..=bottom];
+        let lines = n / columns;
+        if rows > 0 {
+            wrapped.copy_within(lines.., 0);
+        } else {
+            wrapped.copy_within(..wrapped.len() - lines, lines);
+        }
+
+        // cells not yet rendered move along with their contents
+        let (buf, dirty) = (&mut self.buf[region.clone()], &mut self.dirty[region.clone()]);
+        let cleared = if rows > 0 {
+            buf.copy_within(n.., 0);
+            dirty.copy_within(n.., 0);
+            region.end - n..region.end
         } else {
-            self.flush(pixel_writer);
-            for row in 0..ROWS {
-                self.buf[row] = self.buf[row+1];
-                let buf = self.buf.clone(); // to borrow self as mut in next line
-                self.put_string(pixel_writer, from_utf8(&buf[row]).unwrap());
+            buf.copy_within(..region.len() - n, n);
+            dirty.copy_within(..region.len() - n, n);
+            region.start..region.start + n
+        };
+        self.erase(cleared);
+    }
+    /// Brings the screen up to date with the grid.
+    pub fn render(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        if !self.visible {
+            return;
+        }
+        if self.view_offset > 0 {
+            if self.pending_scroll.is_some() || self.dirty.contains(&true) {
+                self.render_scrollback(pixel_writer);
+            }
+            return;
+        }
+
+        let char_size = self.font.char_size();
+        if let Some(scroll) = self.pending_scroll.take() {
+            let height = scroll.bottom + 1 - scroll.top;
+            let n = scroll.rows.unsigned_abs();
+            if n >= height {
+                let columns = self.size.x;
+                self.dirty[scroll.top * columns..(scroll.bottom + 1) * columns].fill(true);
+            } else if n > 0 {
+                let shift = n * char_size.y;
+                let top = scroll.top * char_size.y;
+                let width = self.size.x * char_size.x;
+                let moved = height * char_size.y - shift;
+                if scroll.rows > 0 {
+                    pixel_writer.copy_rect(Rect::new(0, (top + shift) as isize, width, moved), XY::new(0, top));
+                } else {
+                    pixel_writer.copy_rect(Rect::new(0, top as isize, width, moved), XY::new(0, top + shift));
+                }
+            }
+        }
+
+        // the cells under the cursor's old and new position get redrawn with or without it
+        let cursor = self.shown_cursor();
+        if cursor != self.drawn_cursor {
+            for pos in [self.drawn_cursor, cursor].into_iter().flatten() {
+                let i = self.index(pos);
+                self.dirty[i] = true;
+            }
+        }
+
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let i = self.index(XY::new(x, y));
+                if !self.dirty[i] {
+                    continue;
+                }
+                // the right half of a double-width character is drawn with its left half
+                let start = if self.buf[i].is_continuation() && x > 0 { i - 1 } else { i };
+                let pos = XY::new(start % self.size.x, y);
+                if Some(pos) == cursor {
+                    self.draw_cursor(pixel_writer, pos, self.displayed(start));
+                } else {
+                    self.draw_cell(pixel_writer, pos, self.displayed(start));
+                }
+                if start == i && font::char_width(self.buf[i].c) == 2 && x + 1 < self.size.x {
+                    self.dirty[i + 1] = false;
+                }
+                self.dirty[i] = false;
+            }
+        }
+        self.drawn_cursor = cursor;
+    }
+    /// Cell `i` of the grid as drawn, reversed if it is selected.
+    fn displayed(&self, i: usize) -> Cell {
+        let mut cell = self.buf[i];
+        if self.is_selected(i) {
+            cell.attr.reverse = !cell.attr.reverse;
+        }
+        cell
+    }
+    /// Draws the history scrolled into view above the top of the grid, and an
+    /// indicator of how far the view is scrolled back in the top right corner.
+    fn render_scrollback(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        self.pending_scroll = None;
+        self.dirty.fill(false);
+        self.drawn_cursor = None;
+        let first = self.history.len() - self.view_offset;
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let cell = if y < self.view_offset {
+                    self.history[first + y].cells.get(x).copied().unwrap_or(Cell::BLANK)
+                } else {
+                    self.cell(XY::new(x, y - self.view_offset))
+                };
+                self.draw_cell(pixel_writer, XY::new(x, y), cell);
             }
-            self.buf[ROWS-1] = [0; COLUMNS];
         }
+
+        let indicator = format!("[-{}/{}]", self.view_offset, self.history.len());
+        let attr = Attributes { reverse: true, ..Attributes::DEFAULT };
+        let start = self.size.x.saturating_sub(indicator.len());
+        for (x, c) in (start..self.size.x).zip(indicator.chars()) {
+            self.draw_cell(pixel_writer, XY::new(x, 0), Cell { c, attr });
+        }
+    }
+    /// Draws `cell` at `pos`, spanning two cells for a double-width character.
+    fn draw_cell(&self, pixel_writer: &mut dyn PixelWriter, pos: XY<usize>, mut cell: Cell) {
+        if cell.is_continuation() {
+            return;
+        }
+        let width = font::char_width(cell.c);
+        if pos.x + width > self.size.x {
+            // e.g. from a history row that was wider than the grid is now
+            cell.c = ' ';
+        }
+        let (fg, bg) = self.colors(cell.attr);
+        let width = font::char_width(cell.c);
+        let char_size = XY::new(self.font.char_size().x * width, self.font.char_size().y);
+        let glyph_size = XY::new(self.font.glyph_size().x * width, self.font.glyph_size().y);
+        let origin = XY::new(pos.x * self.font.char_size().x, pos.y * char_size.y);
+        self.font.draw_char(pixel_writer, origin, fg, bg, cell.c);
+        // the spacing around the glyph keeps the cell's background as well
+        let (x, y) = (origin.x as isize, origin.y as isize);
+        pixel_writer.fill_rect(Rect::new(x + glyph_size.x as isize, y, char_size.x - glyph_size.x, glyph_size.y), bg);
+        pixel_writer.fill_rect(Rect::new(x, y + glyph_size.y as isize, char_size.x, char_size.y - glyph_size.y), bg);
+        if cell.attr.underline {
+            // below the glyph, or on its last row for fonts without spacing
+            let row = glyph_size.y.min(char_size.y - 1) as isize;
+            pixel_writer.fill_rect(Rect::new(x, y + row, char_size.x, 1), fg);
+        }
+    }
+    /// Draws `cell` at `pos` with the cursor on it.
+    fn draw_cursor(&self, pixel_writer: &mut dyn PixelWriter, pos: XY<usize>, mut cell: Cell) {
+        if self.cursor_style == CursorStyle::Block {
+            cell.attr.reverse = !cell.attr.reverse;
+            self.draw_cell(pixel_writer, pos, cell);
+            return;
+        }
+        self.draw_cell(pixel_writer, pos, cell);
+        let char_size = self.font.char_size();
+        let width = font::char_width(cell.c).min(self.size.x - pos.x) * char_size.x;
+        let thickness = (char_size.y / 8).max(1);
+        let (x, y) = ((pos.x * char_size.x) as isize, (pos.y * char_size.y) as isize);
+        let rect = match self.cursor_style {
+            CursorStyle::Underline => Rect::new(x, y + (char_size.y - thickness) as isize, width, thickness),
+            _ => Rect::new(x, y, thickness, char_size.y),
+        };
+        pixel_writer.fill_rect(rect, self.colors(cell.attr).0);
+    }
+    /// Foreground and background color of cells with `attr`.
+    fn colors(&self, attr: Attributes) -> (PixelColor, PixelColor) {
+        let fg = attr.fg.resolve(self.default_fg, attr.bold);
+        let bg = attr.bg.resolve(self.default_bg, false);
+        if attr.reverse { (bg, fg) } else { (fg, bg) }
+    }
+}
+
+/// Wraps `line` into rows of `width` cells, moving double-width characters
+/// that would be cut in half to the next row.
+///
+/// If the cursor is at `cursor_offset` in `line`, its position is stored into
+/// `cursor`, counting rows from `first_row`.
+fn wrap_line(line: &[Cell], width: usize, cursor_offset: Option<usize>, cursor: &mut XY<usize>,
+    first_row: usize) -> Vec<HistoryRow>
+{
+    let mut rows = Vec::new();
+    let mut row = Vec::with_capacity(width);
+    for (i, &cell) in line.iter().enumerate() {
+        // the right half of a double-width character moves with its left half
+        if cell.is_continuation() {
+            continue;
+        }
+        let wide = line.get(i + 1).is_some_and(Cell::is_continuation);
+        let cells: &[Cell] = match wide {
+            true if width < 2 => &[Cell { c: char::REPLACEMENT_CHARACTER, attr: cell.attr }],
+            true => &line[i..i + 2],
+            false => &line[i..i + 1],
+        };
+        if row.len() + cells.len() > width {
+            row.resize(width, Cell::BLANK);
+            rows.push(HistoryRow { cells: mem::replace(&mut row, Vec::with_capacity(width)), wrapped: true });
+        }
+        if cursor_offset.is_some_and(|offset| offset == i || wide && offset == i + 1) {
+            *cursor = XY::new(row.len(), first_row + rows.len());
+        }
+        row.extend_from_slice(cells);
+    }
+    if cursor_offset.is_some_and(|offset| offset >= line.len()) {
+        *cursor = XY::new(row.len(), first_row + rows.len());
+    }
+    row.resize(width, Cell::BLANK);
+    rows.push(HistoryRow { cells: row, wrapped: false });
+    rows
+}
+
+/// Parses the color of SGR 38/48 from the parameters following it, returning
+/// the color and the number of parameters used.
+fn extended_color(params: &[u16]) -> (Option<Color>, usize) {
+    match *params {
+        [5, i, ..] => (Some(Color::Indexed(i.min(255) as u8)), 2),
+        [2, r, g, b, ..] => {
+            let [r, g, b] = [r, g, b].map(|c| c.min(255) as u8);
+            (Some(Color::Rgb(PixelColor { r, g, b })), 4)
+        }
+        // unknown color space or missing values, the rest of the sequence is unusable
+        _ => (None, params.len()),
     }
 }
 
 impl fmt::Write for Console<'_> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        frame_buffer::lock_pixel_writer(|mut writer| {
-            self.put_string(&mut writer, s);
-        });
+        with_pixel_writer(|writer| self.put_string(writer, s));
         Ok(())
     }
 }
 
 #[doc(hidden)]
-pub fn _print(args: fmt::Arguments) {
-    lock_console(|mut console| console.write_fmt(args).unwrap())
+pub fn _print(index: usize, args: fmt::Arguments) {
+    lock_consoles(|mut consoles| consoles.get_mut(index).write_fmt(args).unwrap())
 }
 
+/// Prints to the kernel log console.
 #[macro_export]
 macro_rules! print {
-    ($($arg:tt)*) => ($crate::graphics::console::_print(format_args!($($arg)*)));
+    ($($arg:tt)*) => ($crate::graphics::console::_print(
+        $crate::graphics::virtual_console::KERNEL_LOG, format_args!($($arg)*)));
 }
 
 #[macro_export]
 macro_rules! println {
-    ($fmt:expr) => (print!(concat!($fmt, "\n")));
-    ($fmt:expr, $($arg:tt)*) => (print!(concat!($fmt, "\n"), $($arg)*));
+    ($fmt:expr) => ($crate::print!(concat!($fmt, "\n")));
+    ($fmt:expr, $($arg:tt)*) => ($crate::print!(concat!($fmt, "\n"), $($arg)*));
+}
+
+/// Prints to virtual console `index`.
+#[macro_export]
+macro_rules! console_print {
+    ($index:expr, $($arg:tt)*) => ($crate::graphics::console::_print($index, format_args!($($arg)*)));
+}
+
+#[macro_export]
+macro_rules! console_println {
+    ($index:expr, $fmt:expr) => ($crate::console_print!($index, concat!($fmt, "\n")));
+    ($index:expr, $fmt:expr, $($arg:tt)*) => ($crate::console_print!($index, concat!($fmt, "\n"), $($arg)*));
 }
@@ -0,0 +1,261 @@
+//! The mouse pointer, drawn over everything else.
+//!
+//! The cursor saves the pixels under its sprite before drawing it and puts
+//! them back when it moves or is hidden, so that nothing below it has to be
+//! repainted. Anything drawing into the area must hide the cursor first,
+//! which `Cursor::hidden` takes care of.
+
+use alloc::{vec, vec::Vec};
+
+use spin::{Mutex, MutexGuard, Once};
+
+use super::{
+    color::{BlendMode, Premultiplied, Rgba},
+    common::{PixelColor, XY},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+
+// spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
+pub static CURSOR: Once<Mutex<Cursor>> = Once::new();
+
+pub fn init(pos: XY<isize>) {
+    CURSOR.call_once(|| Mutex::new(Cursor::new(CursorShape::Arrow, pos)));
+}
+
+pub fn lock_cursor<F: FnMut(MutexGuard<Cursor>)>(mut f: F) {
+    let cursor = CURSOR.get().expect("cursor::lock_cursor is called before cursor::init");
+    f(cursor.lock())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    Arrow,
+    /// The I-beam shown over editable text.
+    TextBeam,
+    ResizeHorizontal,
+    ResizeVertical,
+    /// An hourglass for waiting.
+    Busy,
+}
+
+/// Sprites as rows of `#` for the outline, `.` for the fill and spaces for
+/// transparent pixels, and the hot spot pointing at the cursor position.
+fn art(shape: CursorShape) -> (&'static [&'static str], XY<usize>) {
+    match shape {
+        CursorShape::Arrow => (&[
+            "#",
+            "##",
+            "#.#",
+            "#..#",
+            "#...#",
+            "#....#",
+            "#.....#",
+            "#......#",
+            "#.......#",
+            "#........#",
+            "#.........#",
+            "#......#####",
+            "#...#..#",
+            "#..##..#",
+            "#.#  #..#",
+            "##   #..#",
+            "#     #..#",
+            "      #..#",
+            "       ##",
+        ], XY::new(0, 0)),
+        CursorShape::TextBeam => (&[
+            "### ###",
+            "#..#..#",
+            "###.###",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "  #.#",
+            "###.###",
+            "#..#..#",
+            "### ###",
+        ], XY::new(3, 8)),
+        CursorShape::ResizeHorizontal => (&[
+            "    #         #",
+            "   ##         ##",
+            "  #.#         #.#",
+            " #..###########..#",
+            "#.................#",
+            " #..###########..#",
+            "  #.#         #.#",
+            "   ##         ##",
+            "    #         #",
+        ], XY::new(9, 4)),
+        CursorShape::ResizeVertical => (&[
+            "    #",
+            "   #.#",
+            "  #...#",
+            " #.....#",
+            "####.####",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "   #.#",
+            "####.####",
+            " #.....#",
+            "  #...#",
+            "   #.#",
+            "    #",
+        ], XY::new(4, 9)),
+        CursorShape::Busy => (&[
+            "###########",
+            "#.........#",
+            " #.......#",
+            " #.......#",
+            "  #.....#",
+            "   #...#",
+            "    #.#",
+            "    #.#",
+            "   #...#",
+            "  #.....#",
+            " #.......#",
+            " #.......#",
+            "#.........#",
+            "###########",
+        ], XY::new(5, 7)),
+    }
+}
+
+const OUTLINE: Rgba = Rgba::new(0, 0, 0, 255);
+const FILL: Rgba = Rgba::new(255, 255, 255, 255);
+
+struct Sprite {
+    size: XY<usize>,
+    hotspot: XY<usize>,
+    pixels: Vec<Premultiplied>,
+}
+
+impl Sprite {
+    fn new(shape: CursorShape) -> Self {
+        let (rows, hotspot) = art(shape);
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut pixels = Vec::with_capacity(width * rows.len());
+        for row in rows {
+            let mut chars = row.chars();
+            pixels.extend((0..width).map(|_| match chars.next() {
+                Some('#') => OUTLINE.premultiply(),
+                Some('.') => FILL.premultiply(),
+                _ => Premultiplied::TRANSPARENT,
+            }));
+        }
+        Self { size: XY::new(width, rows.len()), hotspot, pixels }
+    }
+}
+
+/// Pixels of the screen covered by the sprite.
+struct SavedArea {
+    rect: Rect,
+    pixels: Vec<PixelColor>,
+}
+
+pub struct Cursor {
+    shape: CursorShape,
+    sprite: Sprite,
+    /// Position of the hot spot.
+    pos: XY<isize>,
+    visible: bool,
+    /// What the drawn sprite covers, `None` while it is not drawn.
+    saved: Option<SavedArea>,
+}
+
+impl Cursor {
+    /// A cursor at `pos`, not drawn until `show` is called.
+    pub fn new(shape: CursorShape, pos: XY<isize>) -> Self {
+        Self { shape, sprite: Sprite::new(shape), pos, visible: false, saved: None }
+    }
+    pub fn shape(&self) -> CursorShape {
+        self.shape
+    }
+    pub fn pos(&self) -> XY<isize> {
+        self.pos
+    }
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+    /// The area the sprite covers at the current position, before clipping.
+    pub fn rect(&self) -> Rect {
+        let sprite = &self.sprite;
+        Rect::new(self.pos.x - sprite.hotspot.x as isize, self.pos.y - sprite.hotspot.y as isize,
+            sprite.size.x, sprite.size.y)
+    }
+
+    pub fn show(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        self.visible = true;
+        self.draw(pixel_writer);
+    }
+    /// Removes the sprite, restoring what it covered.
+    pub fn hide(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        self.visible = false;
+        self.erase(pixel_writer);
+    }
+
+    /// Moves the hot spot to `pos`, kept within `pixel_writer`, and returns the position used.
+    pub fn move_to(&mut self, pixel_writer: &mut dyn PixelWriter, pos: XY<isize>) -> XY<isize> {
+        let size = pixel_writer.size();
+        let pos = XY::new(pos.x.clamp(0, size.x as isize - 1), pos.y.clamp(0, size.y as isize - 1));
+        if pos != self.pos {
+            self.erase(pixel_writer);
+            self.pos = pos;
+            self.draw(pixel_writer);
+        }
+        pos
+    }
+
+    pub fn set_shape(&mut self, pixel_writer: &mut dyn PixelWriter, shape: CursorShape) {
+        if shape != self.shape {
+            self.erase(pixel_writer);
+            self.shape = shape;
+            self.sprite = Sprite::new(shape);
+            self.draw(pixel_writer);
+        }
+    }
+
+    /// Runs `f`, which draws into `pixel_writer`, with the cursor taken off the
+    /// screen so that it neither gets painted over nor saves stale pixels.
+    pub fn hidden<R>(&mut self, pixel_writer: &mut dyn PixelWriter, f: impl FnOnce(&mut dyn PixelWriter) -> R) -> R {
+        self.erase(pixel_writer);
+        let result = f(pixel_writer);
+        self.draw(pixel_writer);
+        result
+    }
+
+    fn draw(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        if !self.visible || self.saved.is_some() {
+            return;
+        }
+        let Some(rect) = self.rect().clip(pixel_writer.size()) else { return };
+        let mut pixels = Vec::with_capacity(rect.w * rect.h);
+        let mut row = vec![PixelColor { r: 0, g: 0, b: 0 }; rect.w];
+        for y in rect.y..rect.bottom() {
+            pixel_writer.read_row(XY::new(rect.x as usize, y as usize), &mut row);
+            pixels.extend_from_slice(&row);
+        }
+        self.saved = Some(SavedArea { rect, pixels });
+        let origin = self.rect();
+        pixel_writer.blend_blit(XY::new(origin.x, origin.y), self.sprite.size, &self.sprite.pixels, BlendMode::SourceOver);
+    }
+
+    fn erase(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        let Some(saved) = self.saved.take() else { return };
+        for (i, row) in saved.pixels.chunks_exact(saved.rect.w).enumerate() {
+            pixel_writer.write_row(XY::new(saved.rect.x as usize, saved.rect.y as usize + i), row);
+        }
+    }
+}
@@ -1,27 +1,145 @@
 use super::{frame_buffer::{PixelWriter}, common::{PixelColor, XY}};
 
-pub struct Font<'a> { regular: &'a[u8] }
+/// A monochrome glyph bitmap, one bit per pixel with the most significant bit
+/// leftmost and each row padded to whole bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyph<'a> {
+    pub size: XY<usize>,
+    pub bitmap: &'a [u8],
+}
+
+impl Glyph<'_> {
+    pub fn pixel(&self, pos: XY<usize>) -> bool {
+        let stride = self.size.x.div_ceil(8);
+        self.bitmap[pos.y * stride + pos.x / 8] & (0x80 >> (pos.x % 8)) != 0
+    }
+}
+
+/// A source of glyphs looked up by Unicode code point.
+pub trait GlyphSet: Sync {
+    /// Size of a single-width glyph. Double-width glyphs are twice as wide.
+    fn glyph_size(&self) -> XY<usize>;
+    /// Blank pixels to add to the right of and below each glyph, for fonts
+    /// whose glyphs leave no gap between characters.
+    fn spacing(&self) -> XY<usize> {
+        XY::new(0, 0)
+    }
+    fn glyph(&self, c: char) -> Option<Glyph<'_>>;
+}
+
+/// Errors from parsing font files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontError {
+    /// Line `line` (1-based) of a text font is not understood.
+    Malformed { line: usize },
+    /// The data does not start with the format's magic number.
+    UnknownFormat,
+    /// The data ends before the glyphs or tables its header announces.
+    Truncated,
+    Unsupported(&'static str),
+}
+
+/// The 8x16 hankaku font: ASCII and the JIS X 0201 half-width katakana.
+pub struct Hankaku<'a> { data: &'a [u8] }
+impl<'a> Hankaku<'a> {
+    /// `data` holds 256 glyphs of 16 bytes, indexed by their JIS X 0201 code.
+    pub const fn new(data: &'a [u8]) -> Self { Self { data } }
+}
+impl GlyphSet for Hankaku<'_> {
+    fn glyph_size(&self) -> XY<usize> {
+        XY::new(8, 16)
+    }
+    fn spacing(&self) -> XY<usize> {
+        XY::new(2, 2)
+    }
+    fn glyph(&self, c: char) -> Option<Glyph<'_>> {
+        let index = match c as u32 {
+            c @ 0x20..=0x7e => c,
+            // half-width katakana, U+FF61..U+FF9F, are 0xa1..0xdf in JIS X 0201
+            c @ 0xff61..=0xff9f => c - 0xff61 + 0xa1,
+            _ => return None,
+        } as usize;
+        let bitmap = self.data.get(index * 16..(index + 1) * 16)?;
+        Some(Glyph { size: self.glyph_size(), bitmap })
+    }
+}
+
+/// Number of console cells `c` occupies: 2 for East Asian wide and
+/// full-width characters, 1 otherwise.
+pub fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115f // Hangul Jamo initials
+        | 0x2e80..=0x303e // CJK radicals, Kangxi, CJK symbols and punctuation
+        | 0x3041..=0x33ff // Hiragana, Katakana, Bopomofo, CJK compatibility
+        | 0x3400..=0x4dbf // CJK extension A
+        | 0x4e00..=0x9fff // CJK unified ideographs
+        | 0xa000..=0xa4cf // Yi
+        | 0xac00..=0xd7a3 // Hangul syllables
+        | 0xf900..=0xfaff // CJK compatibility ideographs
+        | 0xfe30..=0xfe4f // CJK compatibility forms
+        | 0xff00..=0xff60 // full-width forms
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f // pictographs and emoticons
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x2fffd
+        | 0x30000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// Glyphs for the console, looked up in `regular` first and then in `fallback`,
+/// which typically provides full-width CJK glyphs.
+#[derive(Clone, Copy)]
+pub struct Font<'a> {
+    regular: &'a dyn GlyphSet,
+    fallback: Option<&'a dyn GlyphSet>,
+}
 impl<'a> Font<'a> {
-    pub fn new(regular: &'a[u8]) -> Self { Self { regular } }
+    pub fn new(regular: &'a dyn GlyphSet) -> Self { Self { regular, fallback: None } }
+    pub fn with_fallback(self, fallback: &'a dyn GlyphSet) -> Self {
+        Self { fallback: Some(fallback), ..self }
+    }
+    /// Size of a character cell, which is the glyph plus spacing to the right and below.
     pub fn char_size(&self) -> XY<usize> {
-        XY::new(8 + 2, 16 + 2) // monospaced
+        let glyph = self.glyph_size();
+        let spacing = self.regular.spacing();
+        XY::new(glyph.x + spacing.x, glyph.y + spacing.y) // monospaced
     }
-    pub fn draw_char(&self, pixel_writer: &mut PixelWriter, pos: XY<usize>,
+    /// Size of the area `draw_char` paints for a single-width character.
+    pub fn glyph_size(&self) -> XY<usize> {
+        self.regular.glyph_size()
+    }
+    pub fn glyph(&self, c: char) -> Option<Glyph<'a>> {
+        self.regular.glyph(c).or_else(|| self.fallback?.glyph(c))
+    }
+    /// Draws `c` into the glyph area at `pos`, which is `char_width(c)` glyphs wide.
+    ///
+    /// Glyphs are clipped to the area; characters without a glyph are drawn as a box.
+    pub fn draw_char(&self, pixel_writer: &mut dyn PixelWriter, pos: XY<usize>,
         fg: PixelColor, bg: PixelColor, c: char)
     {
-        let mut c = c as usize;
-        if self.regular.len() < c {
-            c = b'?' as usize;
-        }
-        for dy in 0..16 {
-            let row = self.regular[c * 16 + dy as usize];
-            for dx in 0..8 {
-                if row & (0x80 >> dx) != 0 {
-                    pixel_writer.draw_pixel(XY::new(pos.x + dx, pos.y + dy), fg);
-                } else {
-                    pixel_writer.draw_pixel(XY::new(pos.x + dx, pos.y + dy), bg);
-                }
+        let size = XY::new(self.glyph_size().x * char_width(c), self.glyph_size().y);
+        let glyph = self.glyph(c);
+        for dy in 0..size.y {
+            for dx in 0..size.x {
+                let ink = match glyph {
+                    Some(glyph) => dx < glyph.size.x && dy < glyph.size.y && glyph.pixel(XY::new(dx, dy)),
+                    None => is_fallback_pixel(size, XY::new(dx, dy)),
+                };
+                pixel_writer.draw_pixel(XY::new(pos.x + dx, pos.y + dy), if ink { fg } else { bg });
             }
         }
     }
 }
+
+/// Whether `pos` is on the outline of the box drawn for missing glyphs,
+/// inset by one pixel from the glyph area of `size`.
+fn is_fallback_pixel(size: XY<usize>, pos: XY<usize>) -> bool {
+    // too small for a box with a gap around it, e.g. cells of a tiny scaled font
+    if size.x < 3 || size.y < 5 {
+        return false;
+    }
+    let inside = (1..size.x - 1).contains(&pos.x) && (2..size.y - 2).contains(&pos.y);
+    let edge = pos.x == 1 || pos.x == size.x - 2 || pos.y == 2 || pos.y == size.y - 3;
+    inside && edge
+}
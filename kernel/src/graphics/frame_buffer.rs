@@ -1,56 +1,317 @@
-use bootloader::boot_info::{FrameBuffer, PixelFormat};
+use alloc::vec;
+
+use bootloader::boot_info::FrameBuffer;
 use spin::{Mutex, Once, MutexGuard};
 
-use super::common::{PixelColor, XY};
+use super::{back_buffer::BackBuffer, common::{PixelColor, XY}, pixel_format::PixelEncoder, rect::Rect};
+use super::color::{BlendMode, Premultiplied, Rgba};
 
 // spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
-pub static PIXEL_WRITER: Once<Mutex<PixelWriter>> = Once::new();
+pub static PIXEL_WRITER: Once<Mutex<FrameBufferWriter>> = Once::new();
+pub static BACK_BUFFER: Once<Mutex<BackBuffer>> = Once::new();
 
 pub fn init(frame_buffer: FrameBuffer) {
-    PIXEL_WRITER.call_once(|| Mutex::new(PixelWriter::new(frame_buffer)));
+    let writer = PIXEL_WRITER.call_once(|| Mutex::new(FrameBufferWriter::new(frame_buffer)));
+    let size = writer.lock().size();
+    BACK_BUFFER.call_once(|| Mutex::new(BackBuffer::new(size, PixelColor { r: 0, g: 0, b: 0 })));
 }
 
-pub fn lock_pixel_writer<F: FnMut(MutexGuard<PixelWriter>)>(mut f: F) {
-    let pixel_writer = PIXEL_WRITER.get()
+/// Runs `f` on the back buffer and then flushes what it drew to the screen.
+pub fn lock_pixel_writer<F: FnMut(MutexGuard<BackBuffer>)>(mut f: F) {
+    let back_buffer = BACK_BUFFER.get()
         .expect("frame_buffer::lock_pixel_writer is called before frame_buffer::init");
-    f(pixel_writer.lock())
+    f(back_buffer.lock());
+    flush();
+}
+
+/// Copies the areas of the back buffer drawn since the last flush to the framebuffer.
+pub fn flush() {
+    let (Some(back_buffer), Some(writer)) = (BACK_BUFFER.get(), PIXEL_WRITER.get()) else { return };
+    back_buffer.lock().flush(&mut *writer.lock());
 }
 
-pub struct PixelWriter {
+/// A drawing target addressed in pixels, with the origin at the top left.
+///
+/// Implemented by the real framebuffer, by `back_buffer::BackBuffer` and by
+/// `canvas::MemoryCanvas`, so that everything drawing through this trait can be
+/// tested on the host.
+pub trait PixelWriter {
+    /// Width and height of the drawable area in pixels.
+    fn size(&self) -> XY<usize>;
+    fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor);
+    /// The color of the pixel at `pos`, which must lie within `size()`.
+    fn read_pixel(&self, pos: XY<usize>) -> PixelColor;
+    /// Moves the pixels of `src` so that its top left corner ends up at `dst`.
+    ///
+    /// Overlapping areas are handled like `memmove`. Both areas must lie within `size()`.
+    fn copy_rect(&mut self, src: Rect, dst: XY<usize>);
+
+    /// Sets `len` pixels of the row starting at `pos` to `color`.
+    ///
+    /// The row must lie within `size()`. Implementors override this and
+    /// `write_row` with bulk writes; everything below draws through them.
+    fn fill_row(&mut self, pos: XY<usize>, len: usize, color: PixelColor) {
+        (0..len).for_each(|dx| self.draw_pixel(XY::new(pos.x + dx, pos.y), color));
+    }
+    /// Copies `pixels` into the row starting at `pos`, which must lie within `size()`.
+    fn write_row(&mut self, pos: XY<usize>, pixels: &[PixelColor]) {
+        for (dx, &color) in pixels.iter().enumerate() {
+            self.draw_pixel(XY::new(pos.x + dx, pos.y), color);
+        }
+    }
+
+    /// Reads the row starting at `pos` into `pixels`. The row must lie within `size()`.
+    fn read_row(&self, pos: XY<usize>, pixels: &mut [PixelColor]) {
+        for (dx, pixel) in pixels.iter_mut().enumerate() {
+            *pixel = self.read_pixel(XY::new(pos.x + dx, pos.y));
+        }
+    }
+
+    /// Draws the pixel at `pos` if it lies within `size()`.
+    fn plot(&mut self, pos: XY<isize>, color: PixelColor) {
+        if Rect::from_size(self.size()).is_contained(pos) {
+            self.draw_pixel(XY::new(pos.x as usize, pos.y as usize), color);
+        }
+    }
+    /// Fills `rect`, clipped to `size()`.
+    fn fill_rect(&mut self, rect: Rect, color: PixelColor) {
+        let Some(rect) = rect.clip(self.size()) else { return };
+        for y in rect.y..rect.bottom() {
+            self.fill_row(XY::new(rect.x as usize, y as usize), rect.w, color);
+        }
+    }
+    /// Draws the one pixel wide outline of `rect`, clipped to `size()`.
+    fn draw_rect(&mut self, rect: Rect, color: PixelColor) {
+        if rect.is_empty() {
+            return;
+        }
+        self.fill_rect(Rect::new(rect.x, rect.y, rect.w, 1), color);
+        self.fill_rect(Rect::new(rect.x, rect.bottom() - 1, rect.w, 1), color);
+        self.fill_rect(Rect::new(rect.x, rect.y, 1, rect.h), color);
+        self.fill_rect(Rect::new(rect.right() - 1, rect.y, 1, rect.h), color);
+    }
+    /// Draws a line from `from` to `to`, both ends included, with Bresenham's algorithm.
+    fn draw_line(&mut self, from: XY<isize>, to: XY<isize>, color: PixelColor) {
+        if from.y == to.y || from.x == to.x {
+            let (x, y) = (from.x.min(to.x), from.y.min(to.y));
+            let (w, h) = (from.x.abs_diff(to.x) + 1, from.y.abs_diff(to.y) + 1);
+            return self.fill_rect(Rect::new(x, y, w, h), color);
+        }
+        let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+        let step = XY::new((to.x - from.x).signum(), (to.y - from.y).signum());
+        let (mut pos, mut err) = (from, dx + dy);
+        loop {
+            self.plot(pos, color);
+            if pos == to {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                pos.x += step.x;
+            }
+            if e2 <= dx {
+                err += dx;
+                pos.y += step.y;
+            }
+        }
+    }
+    /// Draws the outline of the circle of `radius` around `center`.
+    fn draw_circle(&mut self, center: XY<isize>, radius: usize, color: PixelColor) {
+        self.draw_rounded_rect(circle_bounds(center, radius), radius, color);
+    }
+    fn fill_circle(&mut self, center: XY<isize>, radius: usize, color: PixelColor) {
+        self.fill_rounded_rect(circle_bounds(center, radius), radius, color);
+    }
+    /// Draws the outline of `rect` with its corners rounded to quarter circles of `radius`.
+    fn draw_rounded_rect(&mut self, rect: Rect, radius: usize, color: PixelColor) {
+        let Some((inner, r)) = corner_centers(rect, radius) else { return };
+        self.fill_rect(Rect::new(inner.x, rect.y, inner.w, 1), color);
+        self.fill_rect(Rect::new(inner.x, rect.bottom() - 1, inner.w, 1), color);
+        self.fill_rect(Rect::new(rect.x, inner.y, 1, inner.h), color);
+        self.fill_rect(Rect::new(rect.right() - 1, inner.y, 1, inner.h), color);
+        let (left, top, right, bottom) = (inner.x, inner.y, inner.right() - 1, inner.bottom() - 1);
+        for_each_octant_point(r, |a, b| {
+            for (x, y) in [(a, b), (b, a)] {
+                self.plot(XY::new(left - x, top - y), color);
+                self.plot(XY::new(right + x, top - y), color);
+                self.plot(XY::new(left - x, bottom + y), color);
+                self.plot(XY::new(right + x, bottom + y), color);
+            }
+        });
+    }
+    /// Fills `rect` with its corners rounded to quarter circles of `radius`.
+    fn fill_rounded_rect(&mut self, rect: Rect, radius: usize, color: PixelColor) {
+        let Some((inner, r)) = corner_centers(rect, radius) else { return };
+        self.fill_rect(Rect::new(rect.x, inner.y, rect.w, inner.h), color);
+        let (top, bottom) = (inner.y, inner.bottom() - 1);
+        for_each_octant_point(r, |a, b| {
+            for (x, y) in [(a, b), (b, a)] {
+                let w = inner.w + 2 * x as usize;
+                self.fill_rect(Rect::new(inner.x - x, top - y, w, 1), color);
+                self.fill_rect(Rect::new(inner.x - x, bottom + y, w, 1), color);
+            }
+        });
+    }
+    /// Blends `color` into the pixel at `pos` if it lies within `size()`.
+    fn blend_pixel(&mut self, pos: XY<isize>, color: Premultiplied, mode: BlendMode) {
+        if Rect::from_size(self.size()).is_contained(pos) {
+            let pos = XY::new(pos.x as usize, pos.y as usize);
+            let blended = mode.blend(color, self.read_pixel(pos));
+            self.draw_pixel(pos, blended);
+        }
+    }
+    /// Blends `color` over `rect`, clipped to `size()`, e.g. to dim or tint an area.
+    fn blend_rect(&mut self, rect: Rect, color: Rgba, mode: BlendMode) {
+        let Some(rect) = rect.clip(self.size()) else { return };
+        let color = color.premultiply();
+        let mut row = vec![PixelColor { r: 0, g: 0, b: 0 }; rect.w];
+        for y in rect.y as usize..rect.bottom() as usize {
+            let pos = XY::new(rect.x as usize, y);
+            self.read_row(pos, &mut row);
+            row.iter_mut().for_each(|pixel| *pixel = mode.blend(color, *pixel));
+            self.write_row(pos, &row);
+        }
+    }
+    /// Blends a `size.x` by `size.y` buffer of row-major premultiplied `pixels`
+    /// onto `pos`, clipped to `size()`.
+    fn blend_blit(&mut self, pos: XY<isize>, size: XY<usize>, pixels: &[Premultiplied], mode: BlendMode) {
+        let Some(visible) = Rect::new(pos.x, pos.y, size.x, size.y).clip(self.size()) else { return };
+        let skip = (visible.x - pos.x) as usize;
+        let mut row = vec![PixelColor { r: 0, g: 0, b: 0 }; visible.w];
+        for y in visible.y..visible.bottom() {
+            let start = (y - pos.y) as usize * size.x + skip;
+            let dst = XY::new(visible.x as usize, y as usize);
+            self.read_row(dst, &mut row);
+            for (pixel, &src) in row.iter_mut().zip(&pixels[start..start + visible.w]) {
+                *pixel = mode.blend(src, *pixel);
+            }
+            self.write_row(dst, &row);
+        }
+    }
+    /// Copies a `size.x` by `size.y` buffer of row-major `pixels` to `pos`,
+    /// clipped to `size()`.
+    fn blit(&mut self, pos: XY<isize>, size: XY<usize>, pixels: &[PixelColor]) {
+        let Some(visible) = Rect::new(pos.x, pos.y, size.x, size.y).clip(self.size()) else { return };
+        let skip = (visible.x - pos.x) as usize;
+        for y in visible.y..visible.bottom() {
+            let start = (y - pos.y) as usize * size.x + skip;
+            self.write_row(XY::new(visible.x as usize, y as usize), &pixels[start..start + visible.w]);
+        }
+    }
+}
+
+fn circle_bounds(center: XY<isize>, radius: usize) -> Rect {
+    let r = radius as isize;
+    Rect::new(center.x - r, center.y - r, 2 * radius + 1, 2 * radius + 1)
+}
+
+/// The rectangle spanned by the centers of the corner circles of a rounded
+/// `rect`, and the radius clamped so that opposite corners do not overlap.
+fn corner_centers(rect: Rect, radius: usize) -> Option<(Rect, isize)> {
+    if rect.is_empty() {
+        return None;
+    }
+    let r = radius.min((rect.w.min(rect.h) - 1) / 2);
+    Some((Rect::new(rect.x + r as isize, rect.y + r as isize, rect.w - 2 * r, rect.h - 2 * r), r as isize))
+}
+
+/// Calls `f(a, b)` for the points of the midpoint circle of `radius` in the
+/// octant where `a >= b >= 0`; the other octants are its reflections.
+fn for_each_octant_point(radius: isize, mut f: impl FnMut(isize, isize)) {
+    let (mut a, mut b, mut err) = (radius, 0, 1 - radius);
+    while a >= b {
+        f(a, b);
+        b += 1;
+        if err < 0 {
+            err += 2 * b + 1;
+        } else {
+            a -= 1;
+            err += 2 * (b - a) + 1;
+        }
+    }
+}
+
+/// Copies the rows of `src` within a row-major pixel buffer, in an order that
+/// leaves overlapping areas intact.
+///
+/// `stride` and `pixel_len` are the row length in pixels and the pixel size in
+/// elements of `buf`.
+pub(crate) fn copy_rect_within<T: Copy>(buf: &mut [T], stride: usize, pixel_len: usize,
+    src: Rect, dst: XY<usize>)
+{
+    let (src_x, src_y) = (src.x as usize, src.y as usize);
+    let row_len = src.w * pixel_len;
+    let copy_row = |buf: &mut [T], dy: usize| {
+        let from = ((src_y + dy) * stride + src_x) * pixel_len;
+        let to = ((dst.y + dy) * stride + dst.x) * pixel_len;
+        buf.copy_within(from..from + row_len, to);
+    };
+    if dst.y <= src_y {
+        (0..src.h).for_each(|dy| copy_row(buf, dy));
+    } else {
+        (0..src.h).rev().for_each(|dy| copy_row(buf, dy));
+    }
+}
+
+/// Draws into the framebuffer handed over by the bootloader.
+pub struct FrameBufferWriter {
     frame_buffer: FrameBuffer,
-    draw_pixel_fn: fn(buf: &mut [u8], off: usize, color: PixelColor) -> (),
+    encoder: PixelEncoder,
 }
 
-impl PixelWriter {
+impl FrameBufferWriter {
     fn new(frame_buffer: FrameBuffer) -> Self {
-        let pixel_format = frame_buffer.info().pixel_format;
-        Self {
-            frame_buffer,
-            draw_pixel_fn: match pixel_format {
-                PixelFormat::RGB => Self::draw_pixel_rgb,
-                PixelFormat::BGR => Self::draw_pixel_bgr,
-                _ => unimplemented!(),
-            },
-        }
-    }
-    fn draw_pixel_rgb(buf: &mut [u8], off: usize, color: PixelColor) {
-        buf[off] = color.r;
-        buf[off + 1] = color.g;
-        buf[off + 2] = color.b;
-    }
-    fn draw_pixel_bgr(buf: &mut [u8], off: usize, color: PixelColor) {
-        buf[off] = color.b;
-        buf[off + 1] = color.g;
-        buf[off + 2] = color.r;
-    }
-    pub fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor) {
-        let off = {
-            (pos.y * self.frame_buffer.info().stride + pos.x) * 4
-        };
-        // let buf = unsafe { self.frame_buffer.buffer().offset(off as isize) };
-        // (self.draw_pixel_fn)(self, buf, color);
-        let draw_pixel_fn = self.draw_pixel_fn.clone();
-        (draw_pixel_fn)(self.frame_buffer.buffer_mut(), off, color);
+        let info = frame_buffer.info();
+        let encoder = PixelEncoder::from_info(&info)
+            .unwrap_or_else(|| panic!("unsupported framebuffer pixel format: {:?}", info));
+        Self { frame_buffer, encoder }
+    }
+    /// Byte offset of the pixel at `pos`.
+    fn offset(&self, pos: XY<usize>) -> usize {
+        (pos.y * self.frame_buffer.info().stride + pos.x) * self.encoder.bytes_per_pixel()
+    }
+}
 
+impl PixelWriter for FrameBufferWriter {
+    fn size(&self) -> XY<usize> {
+        let info = self.frame_buffer.info();
+        XY::new(info.horizontal_resolution, info.vertical_resolution)
+    }
+    fn draw_pixel(&mut self, pos: XY<usize>, color: PixelColor) {
+        let pixel = self.encoder.encode(color);
+        let off = self.offset(pos);
+        self.frame_buffer.buffer_mut()[off..off + pixel.bytes().len()].copy_from_slice(pixel.bytes());
+    }
+    fn read_pixel(&self, pos: XY<usize>) -> PixelColor {
+        let off = self.offset(pos);
+        self.encoder.decode(&self.frame_buffer.buffer()[off..])
+    }
+    fn copy_rect(&mut self, src: Rect, dst: XY<usize>) {
+        let stride = self.frame_buffer.info().stride;
+        let pixel_len = self.encoder.bytes_per_pixel();
+        copy_rect_within(self.frame_buffer.buffer_mut(), stride, pixel_len, src, dst);
+    }
+    fn fill_row(&mut self, pos: XY<usize>, len: usize, color: PixelColor) {
+        let pixel = self.encoder.encode(color);
+        let off = self.offset(pos);
+        let row = &mut self.frame_buffer.buffer_mut()[off..off + len * pixel.bytes().len()];
+        row.chunks_exact_mut(pixel.bytes().len()).for_each(|dst| dst.copy_from_slice(pixel.bytes()));
+    }
+    fn write_row(&mut self, pos: XY<usize>, pixels: &[PixelColor]) {
+        let encoder = self.encoder;
+        let off = self.offset(pos);
+        let pixel_len = encoder.bytes_per_pixel();
+        let row = &mut self.frame_buffer.buffer_mut()[off..off + pixels.len() * pixel_len];
+        // neighbouring pixels mostly share their color, so reuse the last conversion
+        let mut last = None;
+        for (dst, &color) in row.chunks_exact_mut(pixel_len).zip(pixels) {
+            let pixel = match last {
+                Some((prev, pixel)) if prev == color => pixel,
+                _ => encoder.encode(color),
+            };
+            last = Some((color, pixel));
+            dst.copy_from_slice(pixel.bytes());
+        }
     }
 }
@@ -0,0 +1,118 @@
+//! Windows bitmaps: uncompressed BMPs with 1, 4 or 8 bit palettes, and 16,
+//! 24 or 32 bit pixels with optional channel bit masks.
+
+use alloc::vec::Vec;
+
+use super::{checked_size, Image, ImageError};
+use crate::graphics::color::Rgba;
+
+pub(super) const MAGIC: &[u8] = b"BM";
+const FILE_HEADER_LEN: usize = 14;
+/// `BITMAPINFOHEADER`; the later versions of the header extend it.
+const INFO_HEADER_LEN: usize = 40;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+fn u16_at(data: &[u8], off: usize) -> Result<u16, ImageError> {
+    Ok(u16::from_le_bytes(data.get(off..off + 2).ok_or(ImageError::Truncated)?.try_into().unwrap()))
+}
+
+fn u32_at(data: &[u8], off: usize) -> Result<u32, ImageError> {
+    Ok(u32::from_le_bytes(data.get(off..off + 4).ok_or(ImageError::Truncated)?.try_into().unwrap()))
+}
+
+pub(super) fn decode(data: &[u8]) -> Result<Image, ImageError> {
+    let pixel_offset = u32_at(data, 10)? as usize;
+    let header_len = u32_at(data, FILE_HEADER_LEN)? as usize;
+    if header_len < INFO_HEADER_LEN {
+        return Err(ImageError::Unsupported("BMP with an OS/2 core header"));
+    }
+    let width = u32_at(data, 18)? as i32;
+    let height = u32_at(data, 22)? as i32;
+    let bits_per_pixel = u16_at(data, 28)?;
+    let compression = u32_at(data, 30)?;
+    let colors_used = u32_at(data, 46)? as usize;
+    if width <= 0 || height == 0 {
+        return Err(ImageError::Malformed("BMP with a negative or zero size"));
+    }
+    // rows are stored bottom-up unless the height is negative
+    let top_down = height < 0;
+    let size = checked_size(width as usize, height.unsigned_abs() as usize)?;
+
+    // the masks follow a plain info header, and are part of the later ones
+    let masks = match compression {
+        BI_RGB => None,
+        BI_BITFIELDS | BI_ALPHABITFIELDS => {
+            let alpha = if compression == BI_ALPHABITFIELDS || header_len > INFO_HEADER_LEN {
+                u32_at(data, 66)?
+            } else {
+                0
+            };
+            Some([u32_at(data, 54)?, u32_at(data, 58)?, u32_at(data, 62)?, alpha])
+        }
+        _ => return Err(ImageError::Unsupported("compressed BMP")),
+    };
+    let masks = match (masks, bits_per_pixel) {
+        (Some(masks), 16 | 32) => masks,
+        (Some(_), _) => return Err(ImageError::Malformed("BMP bit masks for a palette or 24-bit image")),
+        (None, 16) => [0x7c00, 0x03e0, 0x001f, 0],
+        (None, _) => [0xff_0000, 0xff00, 0xff, 0],
+    };
+
+    let palette = match bits_per_pixel {
+        1 | 4 | 8 => {
+            let mut offset = FILE_HEADER_LEN + header_len;
+            if compression == BI_BITFIELDS && header_len == INFO_HEADER_LEN {
+                offset += 12;
+            }
+            // files written without a count may still hold fewer entries than the depth allows
+            let room = pixel_offset.saturating_sub(offset) / 4;
+            let count = if colors_used == 0 { room.min(1 << bits_per_pixel) } else { colors_used.min(256) };
+            let entries = data.get(offset..offset + count * 4).ok_or(ImageError::Truncated)?;
+            // blue, green, red and an unused byte
+            entries.chunks_exact(4).map(|e| Rgba::new(e[2], e[1], e[0], 255)).collect()
+        }
+        16 | 24 | 32 => Vec::new(),
+        _ => return Err(ImageError::Unsupported("BMP bit depth")),
+    };
+
+    // rows are padded to a multiple of 4 bytes
+    let stride = (size.x * bits_per_pixel as usize).div_ceil(32) * 4;
+    let mut pixels = Vec::with_capacity(size.x * size.y);
+    for y in 0..size.y {
+        let row_index = if top_down { y } else { size.y - 1 - y };
+        let start = pixel_offset + row_index * stride;
+        let row = data.get(start..start + stride).ok_or(ImageError::Truncated)?;
+        for x in 0..size.x {
+            let pixel = match bits_per_pixel {
+                1 | 4 | 8 => {
+                    let bits = bits_per_pixel as usize;
+                    let bit = x * bits;
+                    let index = (row[bit / 8] >> (8 - bits - bit % 8)) & ((1 << bits) - 1) as u8;
+                    *palette.get(index as usize).ok_or(ImageError::Malformed("BMP palette index out of range"))?
+                }
+                16 => from_masks(u16_at(row, x * 2)? as u32, masks),
+                24 => Rgba::new(row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255),
+                _ => from_masks(u32_at(row, x * 4)?, masks),
+            };
+            pixels.push(pixel);
+        }
+    }
+    Ok(Image::new(size, pixels))
+}
+
+/// The color of a pixel value with channels in `[red, green, blue, alpha]`
+/// masks, opaque if there is no alpha mask.
+fn from_masks(value: u32, masks: [u32; 4]) -> Rgba {
+    let channel = |mask: u32, missing: u8| {
+        if mask == 0 {
+            return missing;
+        }
+        let max = (mask >> mask.trailing_zeros()) as u64;
+        ((((value & mask) >> mask.trailing_zeros()) as u64 * 255 + max / 2) / max) as u8
+    };
+    let [r, g, b, a] = masks;
+    Rgba::new(channel(r, 0), channel(g, 0), channel(b, 0), channel(a, 255))
+}
@@ -0,0 +1,89 @@
+//! Decoding of BMP, PNG and QOI images into RGBA pixels, for the boot splash
+//! and icons embedded in the kernel.
+
+mod bmp;
+mod png;
+mod qoi;
+
+use alloc::vec::Vec;
+
+use super::{
+    color::{BlendMode, Premultiplied, Rgba},
+    common::XY,
+    frame_buffer::PixelWriter,
+};
+
+/// Images with more pixels are rejected rather than exhausting the heap.
+///
+/// Decoding a 16-bit PNG holds about 20 bytes per pixel at once (inflated
+/// data, unfiltered rows and the RGBA result), so the largest image takes
+/// about 5 MiB of the 16 MiB kernel heap, which the screen's back buffer and
+/// the windows share.
+pub const MAX_PIXELS: usize = 512 * 512;
+
+/// Errors from decoding images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageError {
+    /// The data starts with none of the supported signatures.
+    UnknownFormat,
+    /// The data ends before the pixels its header announces.
+    Truncated,
+    Malformed(&'static str),
+    Unsupported(&'static str),
+    /// The image has more than `MAX_PIXELS` pixels.
+    TooLarge,
+}
+
+/// A decoded image, its pixels in row-major order with straight alpha.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    size: XY<usize>,
+    pixels: Vec<Rgba>,
+}
+
+impl Image {
+    /// `pixels` must hold `size.x * size.y` pixels.
+    pub fn new(size: XY<usize>, pixels: Vec<Rgba>) -> Self {
+        assert_eq!(pixels.len(), size.x * size.y, "pixel count does not match the image size");
+        Self { size, pixels }
+    }
+    /// Decodes a BMP, PNG or QOI image, telling the formats apart by their signatures.
+    pub fn decode(data: &[u8]) -> Result<Self, ImageError> {
+        if data.starts_with(png::SIGNATURE) {
+            png::decode(data)
+        } else if data.starts_with(qoi::MAGIC) {
+            qoi::decode(data)
+        } else if data.starts_with(bmp::MAGIC) {
+            bmp::decode(data)
+        } else {
+            Err(ImageError::UnknownFormat)
+        }
+    }
+    pub fn size(&self) -> XY<usize> {
+        self.size
+    }
+    pub fn pixel(&self, pos: XY<usize>) -> Rgba {
+        self.pixels[pos.y * self.size.x + pos.x]
+    }
+    pub fn pixels(&self) -> &[Rgba] {
+        &self.pixels
+    }
+    /// The pixels ready for `PixelWriter::blend_blit`.
+    pub fn premultiplied(&self) -> Vec<Premultiplied> {
+        self.pixels.iter().map(|pixel| pixel.premultiply()).collect()
+    }
+    /// Draws the image with its top left corner at `pos`, blended over what is
+    /// already drawn and clipped to `pixel_writer`.
+    pub fn draw(&self, pixel_writer: &mut dyn PixelWriter, pos: XY<isize>) {
+        pixel_writer.blend_blit(pos, self.size, &self.premultiplied(), BlendMode::SourceOver);
+    }
+}
+
+/// Checks the dimensions read from a header, returning them as a size.
+fn checked_size(width: usize, height: usize) -> Result<XY<usize>, ImageError> {
+    match width.checked_mul(height) {
+        Some(0) => Err(ImageError::Malformed("image without pixels")),
+        Some(n) if n <= MAX_PIXELS => Ok(XY::new(width, height)),
+        _ => Err(ImageError::TooLarge),
+    }
+}
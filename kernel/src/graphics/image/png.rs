@@ -0,0 +1,217 @@
+//! Portable Network Graphics in all color types and bit depths, interlaced or
+//! not, cf. https://www.w3.org/TR/png/
+//!
+//! The image data is inflated with `miniz_oxide`; checksums of the chunks are
+//! not verified, but the zlib stream's own checksum is.
+
+use alloc::{vec, vec::Vec};
+
+use miniz_oxide::inflate::decompress_to_vec_zlib_with_limit;
+
+use super::{checked_size, Image, ImageError};
+use crate::graphics::{color::Rgba, common::XY};
+
+pub(super) const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+const GRAYSCALE: u8 = 0;
+const TRUECOLOR: u8 = 2;
+const INDEXED: u8 = 3;
+const GRAYSCALE_ALPHA: u8 = 4;
+const TRUECOLOR_ALPHA: u8 = 6;
+
+/// First column and row, and the distance between pixels, of the seven
+/// passes of Adam7 interlacing.
+const ADAM7: [(usize, usize, usize, usize); 7] =
+    [(0, 0, 8, 8), (4, 0, 8, 8), (0, 4, 4, 8), (2, 0, 4, 4), (0, 2, 2, 4), (1, 0, 2, 2), (0, 1, 1, 2)];
+
+struct Header {
+    size: XY<usize>,
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+}
+
+impl Header {
+    fn channels(&self) -> usize {
+        match self.color_type {
+            GRAYSCALE | INDEXED => 1,
+            GRAYSCALE_ALPHA => 2,
+            TRUECOLOR => 3,
+            _ => 4,
+        }
+    }
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+    /// Bytes of a row of `width` pixels, without the filter type.
+    fn stride(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+/// Colors of the palette and which sample values are transparent.
+#[derive(Default)]
+struct Colors {
+    palette: Vec<Rgba>,
+    /// `tRNS`: alpha of the palette entries, or the one transparent gray or RGB value.
+    transparency: Vec<u8>,
+}
+
+pub(super) fn decode(data: &[u8]) -> Result<Image, ImageError> {
+    let mut header = None;
+    let mut colors = Colors::default();
+    let mut compressed = Vec::new();
+    let mut rest = &data[SIGNATURE.len()..];
+    loop {
+        let len = u32::from_be_bytes(rest.get(..4).ok_or(ImageError::Truncated)?.try_into().unwrap()) as usize;
+        let kind = rest.get(4..8).ok_or(ImageError::Truncated)?;
+        let body = rest.get(8..8 + len).ok_or(ImageError::Truncated)?;
+        match kind {
+            b"IHDR" => header = Some(parse_header(body)?),
+            b"PLTE" => colors.palette = body.chunks_exact(3).map(|c| Rgba::new(c[0], c[1], c[2], 255)).collect(),
+            b"tRNS" => colors.transparency = body.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            // ancillary chunks, e.g. gamma or text, have a lowercase first letter
+            _ if kind[0].is_ascii_lowercase() => {}
+            _ => return Err(ImageError::Unsupported("critical PNG chunk")),
+        }
+        // the chunk is followed by its CRC
+        rest = rest.get(12 + len..).ok_or(ImageError::Truncated)?;
+    }
+    let header = header.ok_or(ImageError::Malformed("PNG without IHDR"))?;
+    if header.color_type == INDEXED && colors.palette.is_empty() {
+        return Err(ImageError::Malformed("indexed PNG without PLTE"));
+    }
+
+    let passes: &[_] = if header.interlaced { &ADAM7 } else { &[(0, 0, 1, 1)] };
+    let expected: usize = passes.iter()
+        .map(|&pass| pass_size(header.size, pass))
+        .filter(|size| size.x > 0 && size.y > 0)
+        .map(|size| (header.stride(size.x) + 1) * size.y)
+        .sum();
+    let raw = decompress_to_vec_zlib_with_limit(&compressed, expected)
+        .map_err(|_| ImageError::Malformed("PNG image data does not inflate"))?;
+
+    let mut pixels = vec![Rgba::TRANSPARENT; header.size.x * header.size.y];
+    let mut raw = &raw[..];
+    for &pass in passes {
+        let size = pass_size(header.size, pass);
+        if size.x == 0 || size.y == 0 {
+            continue;
+        }
+        let stride = header.stride(size.x);
+        let (pass_data, next) = raw.split_at_checked((stride + 1) * size.y).ok_or(ImageError::Truncated)?;
+        raw = next;
+        let rows = unfilter(pass_data, stride, header.bits_per_pixel().div_ceil(8))?;
+        let (x0, y0, dx, dy) = pass;
+        for (y, row) in rows.chunks_exact(stride).enumerate() {
+            for x in 0..size.x {
+                pixels[(y0 + y * dy) * header.size.x + x0 + x * dx] = pixel(&header, &colors, row, x)?;
+            }
+        }
+    }
+    Ok(Image::new(header.size, pixels))
+}
+
+fn parse_header(body: &[u8]) -> Result<Header, ImageError> {
+    let body = body.get(..13).ok_or(ImageError::Truncated)?;
+    let width = u32::from_be_bytes(body[0..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(body[4..8].try_into().unwrap()) as usize;
+    let [bit_depth, color_type, compression, filter, interlace] = body[8..13].try_into().unwrap();
+    let valid_depth = match color_type {
+        GRAYSCALE => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+        INDEXED => matches!(bit_depth, 1 | 2 | 4 | 8),
+        TRUECOLOR | GRAYSCALE_ALPHA | TRUECOLOR_ALPHA => matches!(bit_depth, 8 | 16),
+        _ => return Err(ImageError::Malformed("unknown PNG color type")),
+    };
+    if !valid_depth || compression != 0 || filter != 0 || interlace > 1 {
+        return Err(ImageError::Malformed("invalid PNG header"));
+    }
+    Ok(Header { size: checked_size(width, height)?, bit_depth, color_type, interlaced: interlace == 1 })
+}
+
+fn pass_size(size: XY<usize>, (x0, y0, dx, dy): (usize, usize, usize, usize)) -> XY<usize> {
+    XY::new((size.x + dx - 1 - x0) / dx, (size.y + dy - 1 - y0) / dy)
+}
+
+/// Reverses the filter of each row in `data`, where each row of `stride`
+/// bytes is preceded by its filter type, and returns the plain rows.
+fn unfilter(data: &[u8], stride: usize, pixel_len: usize) -> Result<Vec<u8>, ImageError> {
+    let mut rows = vec![0; data.len() / (stride + 1) * stride];
+    let mut prev = vec![0; stride];
+    for (line, row) in data.chunks_exact(stride + 1).zip(rows.chunks_exact_mut(stride)) {
+        let (filter, filtered) = (line[0], &line[1..]);
+        for i in 0..stride {
+            let left = if i >= pixel_len { row[i - pixel_len] } else { 0 };
+            let up = prev[i];
+            let up_left = if i >= pixel_len { prev[i - pixel_len] } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => return Err(ImageError::Malformed("unknown PNG filter type")),
+            };
+            row[i] = filtered[i].wrapping_add(predicted);
+        }
+        prev.copy_from_slice(row);
+    }
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// The color of pixel `x` of an unfiltered `row`.
+fn pixel(header: &Header, colors: &Colors, row: &[u8], x: usize) -> Result<Rgba, ImageError> {
+    let depth = header.bit_depth as usize;
+    // raw sample `i` of the pixel, at most 16 bits
+    let sample = |i: usize| -> u16 {
+        let bit = (x * header.channels() + i) * depth;
+        match depth {
+            16 => u16::from_be_bytes([row[bit / 8], row[bit / 8 + 1]]),
+            8 => row[bit / 8] as u16,
+            _ => ((row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1)) as u16,
+        }
+    };
+    // a sample scaled to 8 bits
+    let level = |i: usize| -> u8 {
+        match depth {
+            16 => (sample(i) >> 8) as u8,
+            _ => (sample(i) as u32 * 255 / ((1 << depth) - 1)) as u8,
+        }
+    };
+    // whether the samples match the single transparent color of `tRNS`
+    let transparent = |count: usize| {
+        colors.transparency.len() >= count * 2
+            && (0..count).all(|i| u16::from_be_bytes([colors.transparency[i * 2], colors.transparency[i * 2 + 1]]) == sample(i))
+    };
+    Ok(match header.color_type {
+        GRAYSCALE => {
+            let v = level(0);
+            Rgba::new(v, v, v, if transparent(1) { 0 } else { 255 })
+        }
+        TRUECOLOR => Rgba::new(level(0), level(1), level(2), if transparent(3) { 0 } else { 255 }),
+        INDEXED => {
+            let index = sample(0) as usize;
+            let color = *colors.palette.get(index).ok_or(ImageError::Malformed("PNG palette index out of range"))?;
+            color.with_alpha(colors.transparency.get(index).copied().unwrap_or(255))
+        }
+        GRAYSCALE_ALPHA => {
+            let v = level(0);
+            Rgba::new(v, v, v, level(1))
+        }
+        _ => Rgba::new(level(0), level(1), level(2), level(3)),
+    })
+}
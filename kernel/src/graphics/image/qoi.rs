@@ -0,0 +1,68 @@
+//! The Quite OK Image format, a simple lossless format that decodes in one
+//! pass without tables, cf. https://qoiformat.org/qoi-specification.pdf
+
+use alloc::vec::Vec;
+
+use super::{checked_size, Image, ImageError};
+use crate::graphics::color::Rgba;
+
+pub(super) const MAGIC: &[u8] = b"qoif";
+const HEADER_LEN: usize = 14;
+
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_MASK: u8 = 0xc0;
+
+pub(super) fn decode(data: &[u8]) -> Result<Image, ImageError> {
+    let header = data.get(..HEADER_LEN).ok_or(ImageError::Truncated)?;
+    let width = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+    if !matches!(header[12], 3 | 4) {
+        return Err(ImageError::Malformed("QOI channel count is not 3 or 4"));
+    }
+    let size = checked_size(width, height)?;
+
+    let mut bytes = data[HEADER_LEN..].iter().copied();
+    let mut next = || bytes.next().ok_or(ImageError::Truncated);
+    let mut pixels = Vec::with_capacity(size.x * size.y);
+    // pixels seen before, by their hash
+    let mut index = [Rgba::TRANSPARENT; 64];
+    let mut px = Rgba::new(0, 0, 0, 255);
+    while pixels.len() < size.x * size.y {
+        let op = next()?;
+        let mut run = 1;
+        match op {
+            OP_RGB => px = Rgba { r: next()?, g: next()?, b: next()?, ..px },
+            OP_RGBA => px = Rgba::new(next()?, next()?, next()?, next()?),
+            _ => match op & OP_MASK {
+                OP_INDEX => px = index[op as usize],
+                OP_DIFF => {
+                    let diff = |shift: u8| ((op >> shift) & 0x03).wrapping_sub(2);
+                    px.r = px.r.wrapping_add(diff(4));
+                    px.g = px.g.wrapping_add(diff(2));
+                    px.b = px.b.wrapping_add(diff(0));
+                }
+                OP_LUMA => {
+                    let dg = (op & 0x3f).wrapping_sub(32);
+                    let rb = next()?;
+                    px.r = px.r.wrapping_add(dg.wrapping_add(rb >> 4).wrapping_sub(8));
+                    px.g = px.g.wrapping_add(dg);
+                    px.b = px.b.wrapping_add(dg.wrapping_add(rb & 0x0f).wrapping_sub(8));
+                }
+                // the remaining 0b11 tag is a run, of which 62 and 63 are taken by OP_RGB(A)
+                _ => run = (op & 0x3f) as usize + 1,
+            },
+        }
+        index[hash(px)] = px;
+        let run = run.min(size.x * size.y - pixels.len());
+        pixels.resize(pixels.len() + run, px);
+    }
+    Ok(Image::new(size, pixels))
+}
+
+fn hash(px: Rgba) -> usize {
+    (px.r as usize * 3 + px.g as usize * 5 + px.b as usize * 7 + px.a as usize * 11) % 64
+}
@@ -1,5 +1,22 @@
 pub mod common;
+pub mod color;
 pub mod frame_buffer;
+pub mod pixel_format;
+pub mod canvas;
+pub mod back_buffer;
+pub mod clip;
 pub mod rect;
 pub mod font;
+pub mod bdf;
+pub mod psf;
+pub mod truetype;
+pub mod text_layout;
+pub mod image;
+pub mod splash;
+pub mod cell;
+pub mod vt100;
 pub mod console;
+pub mod virtual_console;
+pub mod window_manager;
+pub mod cursor;
+pub mod widget;
@@ -0,0 +1,141 @@
+//! Encoding of colors into the bytes of a framebuffer pixel.
+//!
+//! Every layout a framebuffer can report is described as channel bit masks
+//! over a little-endian pixel value of 1 to 4 bytes, and `PixelEncoder`
+//! turns a `PixelColor` into those bytes once so that they can be copied to
+//! as many pixels as needed.
+
+use bootloader::boot_info::{self, FrameBufferInfo};
+
+use super::common::PixelColor;
+
+/// Where the color channels are stored in a pixel value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Each channel in the bits set in its mask, as UEFI GOP reports for `PixelBitMask`.
+    Bitmask { red: u32, green: u32, blue: u32 },
+    /// A single gray level in the bits of `mask`.
+    Gray { mask: u32 },
+}
+
+impl PixelFormat {
+    /// One byte each for red, green and blue in this order.
+    pub const RGB: Self = Self::Bitmask { red: 0xff, green: 0xff00, blue: 0xff_0000 };
+    /// One byte each for blue, green and red in this order.
+    pub const BGR: Self = Self::Bitmask { red: 0xff_0000, green: 0xff00, blue: 0xff };
+    /// One byte of gray level.
+    pub const U8: Self = Self::Gray { mask: 0xff };
+    /// 16-bit pixels with 5 bits of red, 6 of green and 5 of blue.
+    pub const RGB565: Self = Self::Bitmask { red: 0xf800, green: 0x07e0, blue: 0x001f };
+}
+
+/// A pixel value ready to be copied into the framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EncodedPixel {
+    bytes: [u8; 4],
+    len: usize,
+}
+
+impl EncodedPixel {
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Scales an 8-bit channel into the bits of a mask and back.
+#[derive(Clone, Copy, Debug)]
+struct Channel {
+    shift: u32,
+    max: u32,
+}
+
+impl Channel {
+    fn new(mask: u32) -> Self {
+        if mask == 0 {
+            return Self { shift: 0, max: 0 };
+        }
+        Self { shift: mask.trailing_zeros(), max: mask >> mask.trailing_zeros() }
+    }
+    fn encode(self, value: u8) -> u32 {
+        (((value as u64 * self.max as u64 + 127) / 255) as u32) << self.shift
+    }
+    fn decode(self, pixel: u32) -> u8 {
+        if self.max == 0 {
+            return 0;
+        }
+        let level = ((pixel >> self.shift) & self.max) as u64;
+        ((level * 255 + self.max as u64 / 2) / self.max as u64) as u8
+    }
+}
+
+/// Converts colors to and from the pixels of one framebuffer layout.
+#[derive(Clone, Copy, Debug)]
+pub struct PixelEncoder {
+    format: PixelFormat,
+    bytes_per_pixel: usize,
+    channels: [Channel; 3],
+}
+
+impl PixelEncoder {
+    /// Returns `None` for pixels of more than 4 bytes, masks that do not fit in
+    /// them, or masks whose bits are not all next to each other.
+    pub fn new(format: PixelFormat, bytes_per_pixel: usize) -> Option<Self> {
+        let masks = match format {
+            PixelFormat::Bitmask { red, green, blue } => [red, green, blue],
+            PixelFormat::Gray { mask } => [mask, 0, 0],
+        };
+        let bits = bytes_per_pixel as u32 * 8;
+        let fits = |mask: u32| bits >= 32 || mask >> bits == 0;
+        let contiguous = |mask: u32| {
+            let run = mask.checked_shr(mask.trailing_zeros()).unwrap_or(0);
+            run & run.wrapping_add(1) == 0
+        };
+        if !(1..=4).contains(&bytes_per_pixel) || !masks.iter().all(|&mask| fits(mask) && contiguous(mask)) {
+            return None;
+        }
+        Some(Self { format, bytes_per_pixel, channels: masks.map(Channel::new) })
+    }
+    /// The encoder for a framebuffer as the bootloader describes it.
+    pub fn from_info(info: &FrameBufferInfo) -> Option<Self> {
+        let format = match info.pixel_format {
+            boot_info::PixelFormat::RGB => PixelFormat::RGB,
+            boot_info::PixelFormat::BGR => PixelFormat::BGR,
+            boot_info::PixelFormat::U8 => PixelFormat::U8,
+            _ => return None,
+        };
+        Self::new(format, info.bytes_per_pixel)
+    }
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.bytes_per_pixel
+    }
+    pub fn encode(&self, color: PixelColor) -> EncodedPixel {
+        let [red, green, blue] = self.channels;
+        let value = match self.format {
+            PixelFormat::Bitmask { .. } => red.encode(color.r) | green.encode(color.g) | blue.encode(color.b),
+            PixelFormat::Gray { .. } => red.encode(luma(color)),
+        };
+        EncodedPixel { bytes: value.to_le_bytes(), len: self.bytes_per_pixel }
+    }
+    /// The color of the pixel stored in `bytes`, as close as the format allows.
+    pub fn decode(&self, bytes: &[u8]) -> PixelColor {
+        let mut value = [0; 4];
+        value[..self.bytes_per_pixel].copy_from_slice(&bytes[..self.bytes_per_pixel]);
+        let value = u32::from_le_bytes(value);
+        let [red, green, blue] = self.channels;
+        match self.format {
+            PixelFormat::Bitmask { .. } => PixelColor { r: red.decode(value), g: green.decode(value), b: blue.decode(value) },
+            PixelFormat::Gray { .. } => {
+                let level = red.decode(value);
+                PixelColor { r: level, g: level, b: level }
+            }
+        }
+    }
+}
+
+/// Perceived brightness of `color` with the ITU-R BT.601 weights.
+fn luma(color: PixelColor) -> u8 {
+    ((color.r as u32 * 299 + color.g as u32 * 587 + color.b as u32 * 114 + 500) / 1000) as u8
+}
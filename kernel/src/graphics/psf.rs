@@ -0,0 +1,133 @@
+//! PC Screen Fonts, the bitmap console font format of Linux, in versions 1 and 2.
+//!
+//! The fonts' Unicode tables map code points to glyphs; fonts without one are
+//! indexed by code point directly.
+
+use alloc::collections::BTreeMap;
+use core::str;
+
+use super::common::XY;
+use super::font::{FontError, Glyph, GlyphSet};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE_512: u8 = 0x01;
+const PSF1_MODE_HAS_TABLE: u8 = 0x02;
+/// Like `PSF1_MODE_HAS_TABLE`, with sequences in the table.
+const PSF1_MODE_HAS_SEQUENCES: u8 = 0x04;
+const PSF1_SEPARATOR: u16 = 0xffff;
+const PSF1_START_SEQUENCE: u16 = 0xfffe;
+
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xff;
+const PSF2_START_SEQUENCE: u8 = 0xfe;
+
+/// A PSF1 or PSF2 font borrowing the font file.
+pub struct PsfFont<'a> {
+    glyph_size: XY<usize>,
+    /// The glyph bitmaps, `bytes_per_glyph` each.
+    glyphs: &'a [u8],
+    bytes_per_glyph: usize,
+    /// Glyph index of each character listed in the Unicode table.
+    unicode: Option<BTreeMap<char, usize>>,
+}
+
+impl<'a> PsfFont<'a> {
+    /// Parses a PSF1 or PSF2 font, telling the versions apart by their magic number.
+    pub fn parse(data: &'a [u8]) -> Result<Self, FontError> {
+        if data.starts_with(&PSF1_MAGIC) {
+            Self::parse_psf1(data)
+        } else if data.starts_with(&PSF2_MAGIC) {
+            Self::parse_psf2(data)
+        } else {
+            Err(FontError::UnknownFormat)
+        }
+    }
+
+    fn parse_psf1(data: &'a [u8]) -> Result<Self, FontError> {
+        let [_, _, mode, height] = *data.first_chunk::<4>().ok_or(FontError::Truncated)?;
+        let count = if mode & PSF1_MODE_512 != 0 { 512 } else { 256 };
+        let bytes_per_glyph = height as usize;
+        if height == 0 {
+            return Err(FontError::Unsupported("PSF font with empty glyphs"));
+        }
+        let glyphs = data.get(4..4 + count * bytes_per_glyph).ok_or(FontError::Truncated)?;
+
+        let unicode = if mode & (PSF1_MODE_HAS_TABLE | PSF1_MODE_HAS_SEQUENCES) != 0 {
+            let table = &data[4 + glyphs.len()..];
+            let mut map = BTreeMap::new();
+            let mut entries = table.chunks_exact(2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+            for index in 0..count {
+                let mut in_sequence = false;
+                loop {
+                    match entries.next().ok_or(FontError::Truncated)? {
+                        PSF1_SEPARATOR => break,
+                        // sequences of combining characters are not supported
+                        PSF1_START_SEQUENCE => in_sequence = true,
+                        _ if in_sequence => {}
+                        unit => {
+                            if let Some(c) = char::from_u32(unit as u32) {
+                                map.entry(c).or_insert(index);
+                            }
+                        }
+                    }
+                }
+            }
+            Some(map)
+        } else {
+            None
+        };
+        Ok(Self { glyph_size: XY::new(8, height as usize), glyphs, bytes_per_glyph, unicode })
+    }
+
+    fn parse_psf2(data: &'a [u8]) -> Result<Self, FontError> {
+        let header = data.get(..32).ok_or(FontError::Truncated)?;
+        let field = |i: usize| u32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap()) as usize;
+        let [header_size, flags, count, bytes_per_glyph, height, width] = [2, 3, 4, 5, 6, 7].map(field);
+        if width == 0 || height == 0 {
+            return Err(FontError::Unsupported("PSF font with empty glyphs"));
+        }
+        if bytes_per_glyph < height * width.div_ceil(8) {
+            return Err(FontError::Unsupported("PSF2 glyphs smaller than their bitmap"));
+        }
+        let end = count.checked_mul(bytes_per_glyph).and_then(|len| len.checked_add(header_size));
+        let glyphs = data.get(header_size..end.ok_or(FontError::Truncated)?).ok_or(FontError::Truncated)?;
+
+        let unicode = if flags as u32 & PSF2_HAS_UNICODE_TABLE != 0 {
+            let mut table = &data[header_size + glyphs.len()..];
+            let mut map = BTreeMap::new();
+            for index in 0..count {
+                let len = table.iter().position(|&b| b == PSF2_SEPARATOR).ok_or(FontError::Truncated)?;
+                // characters before the first sequence each map to the glyph on their own
+                let singles = table[..len].split(|&b| b == PSF2_START_SEQUENCE).next().unwrap();
+                let singles = str::from_utf8(singles).map_err(|_| FontError::Unsupported("PSF2 Unicode table is not UTF-8"))?;
+                for c in singles.chars() {
+                    map.entry(c).or_insert(index);
+                }
+                table = &table[len + 1..];
+            }
+            Some(map)
+        } else {
+            None
+        };
+        Ok(Self { glyph_size: XY::new(width, height), glyphs, bytes_per_glyph, unicode })
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len() / self.bytes_per_glyph
+    }
+}
+
+impl GlyphSet for PsfFont<'_> {
+    fn glyph_size(&self) -> XY<usize> {
+        self.glyph_size
+    }
+    fn glyph(&self, c: char) -> Option<Glyph<'_>> {
+        let index = match &self.unicode {
+            Some(map) => *map.get(&c)?,
+            None => c as usize,
+        };
+        let bitmap = self.glyphs.get(index * self.bytes_per_glyph..(index + 1) * self.bytes_per_glyph)?;
+        Some(Glyph { size: self.glyph_size, bitmap })
+    }
+}
@@ -11,10 +11,54 @@ pub struct Rect {
 }
 
 impl Rect {
+    /// The rectangle from the origin to `size`, e.g. the bounds of a drawing target.
+    pub fn from_size(size: XY<usize>) -> Self {
+        Rect::new(0, 0, size.x, size.y)
+    }
+    /// One past the rightmost column.
+    pub fn right(self) -> isize {
+        self.x + self.w as isize
+    }
+    /// One past the bottom row.
+    pub fn bottom(self) -> isize {
+        self.y + self.h as isize
+    }
+    pub fn is_empty(self) -> bool {
+        self.w == 0 || self.h == 0
+    }
     pub fn is_contained(self, pos: XY<isize>) -> bool {
         self.x <= pos.x
             && pos.x < self.x + self.w as isize
             && self.y <= pos.y
             && pos.y < self.y + self.h as isize
     }
+    /// The area covered by both rectangles, or `None` if they do not overlap.
+    pub fn intersection(self, other: Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (x < right && y < bottom).then(|| Rect::new(x, y, (right - x) as usize, (bottom - y) as usize))
+    }
+    /// The smallest rectangle containing both. Empty rectangles are ignored.
+    pub fn union(self, other: Rect) -> Rect {
+        if self.is_empty() {
+            return other;
+        }
+        if other.is_empty() {
+            return self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = self.right().max(other.right());
+        let bottom = self.bottom().max(other.bottom());
+        Rect::new(x, y, (right - x) as usize, (bottom - y) as usize)
+    }
+    /// The part of the rectangle within a drawing target of `size`, if any.
+    pub fn clip(self, size: XY<usize>) -> Option<Rect> {
+        self.intersection(Rect::from_size(size))
+    }
+    pub fn translate(self, offset: XY<isize>) -> Rect {
+        Rect::new(self.x + offset.x, self.y + offset.y, self.w, self.h)
+    }
 }
@@ -0,0 +1,31 @@
+//! The logo shown while the kernel boots, until the console takes over the screen.
+
+use super::{
+    common::{PixelColor, XY},
+    frame_buffer::{self, PixelWriter},
+    image::{Image, ImageError},
+    rect::Rect,
+};
+
+pub const SPLASH_PNG: &[u8] = include_bytes!("../../resources/splash.png");
+const BACKGROUND: PixelColor = PixelColor { r: 16, g: 16, b: 24 };
+/// How long the splash stays on the screen before the console replaces it.
+pub const SPLASH_MS: u64 = 1500;
+
+/// Clears `pixel_writer` and draws the logo in its center.
+pub fn draw(pixel_writer: &mut dyn PixelWriter) -> Result<(), ImageError> {
+    let image = Image::decode(SPLASH_PNG)?;
+    let size = pixel_writer.size();
+    pixel_writer.fill_rect(Rect::from_size(size), BACKGROUND);
+    let pos = XY::new(
+        (size.x as isize - image.size().x as isize) / 2,
+        (size.y as isize - image.size().y as isize) / 2,
+    );
+    image.draw(pixel_writer, pos);
+    Ok(())
+}
+
+/// Draws the splash on the screen.
+pub fn show() {
+    frame_buffer::lock_pixel_writer(|mut w| draw(&mut *w).expect("the embedded splash image is invalid"));
+}
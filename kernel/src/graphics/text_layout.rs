@@ -0,0 +1,209 @@
+//! Measuring, wrapping and aligning text, in the bitmap font or a TrueType font.
+//!
+//! `TextLayout` breaks text into lines at explicit line breaks and, given a
+//! width, between words, breaking words only if one alone is too wide. The
+//! lines can then be drawn left aligned, centered or right aligned in a
+//! rectangle that they are clipped to.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use super::{
+    clip::ClippedWriter,
+    common::{PixelColor, XY},
+    font::{self, Font},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+    truetype::TrueTypeFont,
+};
+
+/// A font as far as laying out text is concerned.
+pub trait TextFont {
+    /// Distance between the tops of consecutive lines.
+    fn line_height(&self) -> usize;
+    /// Width of `text` on a single line, in pixels.
+    fn measure(&self, text: &str) -> usize;
+    /// Draws `text` with the top left of its line at `pos`, only over the
+    /// pixels the glyphs cover or their whole cells, depending on the font.
+    fn draw(&mut self, pixel_writer: &mut dyn PixelWriter, pos: XY<isize>, text: &str, fg: PixelColor, bg: PixelColor);
+}
+
+/// Characters are drawn whole, so those starting left of or above the
+/// drawing target are left out.
+impl TextFont for Font<'_> {
+    fn line_height(&self) -> usize {
+        self.char_size().y
+    }
+    fn measure(&self, text: &str) -> usize {
+        text.chars().map(|c| font::char_width(c) * self.char_size().x).sum()
+    }
+    fn draw(&mut self, pixel_writer: &mut dyn PixelWriter, pos: XY<isize>, text: &str, fg: PixelColor, bg: PixelColor) {
+        let mut x = pos.x;
+        for c in text.chars() {
+            if x >= 0 && pos.y >= 0 {
+                self.draw_char(pixel_writer, XY::new(x as usize, pos.y as usize), fg, bg, c);
+            }
+            x += (font::char_width(c) * self.char_size().x) as isize;
+        }
+    }
+}
+
+/// A TrueType font at one pixel size.
+pub struct ScaledFont<'f, 'a> {
+    font: &'f mut TrueTypeFont<'a>,
+    size: f32,
+}
+
+impl<'f, 'a> ScaledFont<'f, 'a> {
+    /// `font` at `size` pixels per em.
+    pub fn new(font: &'f mut TrueTypeFont<'a>, size: f32) -> Self {
+        Self { font, size }
+    }
+}
+
+/// Widths are rounded up to whole pixels.
+impl TextFont for ScaledFont<'_, '_> {
+    fn line_height(&self) -> usize {
+        libm::ceilf(self.font.line_metrics(self.size).line_height()) as usize
+    }
+    fn measure(&self, text: &str) -> usize {
+        libm::ceilf(self.font.measure(text, self.size)) as usize
+    }
+    fn draw(&mut self, pixel_writer: &mut dyn PixelWriter, pos: XY<isize>, text: &str, fg: PixelColor, bg: PixelColor) {
+        self.font.draw_text(pixel_writer, pos, self.size, fg, bg, text);
+    }
+}
+
+/// Where lines go between the left and right edge of the area they are drawn in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A line of laid out text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    /// Byte range of the line in the text, without the spaces or line break ending it.
+    pub range: Range<usize>,
+    pub width: usize,
+}
+
+/// Text broken into lines for one font.
+pub struct TextLayout<'t> {
+    text: &'t str,
+    lines: Vec<Line>,
+    line_height: usize,
+}
+
+impl<'t> TextLayout<'t> {
+    /// Lays `text` out in `font`, breaking lines at `\n` and, if `max_width`
+    /// is given, wrapping them at spaces to fit into it.
+    pub fn new(font: &dyn TextFont, text: &'t str, max_width: Option<usize>) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for paragraph in text.split('\n') {
+            let range = start..start + paragraph.len();
+            match max_width {
+                Some(max_width) => wrap(font, text, range, max_width, &mut lines),
+                None => lines.push(Line { width: font.measure(paragraph), range }),
+            }
+            start += paragraph.len() + 1;
+        }
+        Self { text, lines, line_height: font.line_height() }
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    pub fn line_text(&self, line: &Line) -> &'t str {
+        &self.text[line.range.clone()]
+    }
+
+    /// Width of the widest line and height of all lines.
+    pub fn size(&self) -> XY<usize> {
+        let width = self.lines.iter().map(|line| line.width).max().unwrap_or(0);
+        XY::new(width, self.lines.len() * self.line_height)
+    }
+
+    /// Draws the lines from the top of `rect` down, aligned between its
+    /// sides, and clips them to it.
+    pub fn draw(&self, font: &mut dyn TextFont, pixel_writer: &mut dyn PixelWriter, rect: Rect,
+        alignment: Alignment, fg: PixelColor, bg: PixelColor)
+    {
+        let mut clipped = ClippedWriter::new(pixel_writer, rect);
+        for (i, line) in self.lines.iter().enumerate() {
+            let y = rect.y + (i * self.line_height) as isize;
+            if y >= rect.bottom() {
+                break;
+            }
+            // lines wider than the area overflow on both sides when centered
+            let room = rect.w as isize - line.width as isize;
+            let x = rect.x + match alignment {
+                Alignment::Left => 0,
+                Alignment::Center => room / 2,
+                Alignment::Right => room,
+            };
+            font.draw(&mut clipped, XY::new(x, y), self.line_text(line), fg, bg);
+        }
+    }
+}
+
+/// Size of `text` on as many lines as it has line breaks.
+pub fn measure(font: &dyn TextFont, text: &str) -> XY<usize> {
+    TextLayout::new(font, text, None).size()
+}
+
+/// Breaks the line `range` of `text` into lines of at most `max_width`
+/// pixels at spaces, and words too long for a line of their own between
+/// characters.
+fn wrap(font: &dyn TextFont, text: &str, range: Range<usize>, max_width: usize, lines: &mut Vec<Line>) {
+    let first = lines.len();
+    let mut line: Option<Line> = None;
+    let mut rest = range.start;
+    while rest < range.end {
+        // the next word and the spaces before it
+        let spaces = text[rest..range.end].len() - text[rest..range.end].trim_start_matches(' ').len();
+        let word_start = rest + spaces;
+        if word_start == range.end {
+            break;
+        }
+        let word_end = text[word_start..range.end].find(' ').map_or(range.end, |i| word_start + i);
+
+        if let Some(current) = &mut line {
+            let width = font.measure(&text[current.range.start..word_end]);
+            if width <= max_width {
+                *current = Line { range: current.range.start..word_end, width };
+                rest = word_end;
+                continue;
+            }
+            lines.extend(line.take());
+        }
+        let width = font.measure(&text[word_start..word_end]);
+        if width <= max_width {
+            line = Some(Line { range: word_start..word_end, width });
+            rest = word_end;
+            continue;
+        }
+        // as many characters as fit, but at least one
+        let mut end = word_start;
+        for (i, c) in text[word_start..word_end].char_indices() {
+            let next = word_start + i + c.len_utf8();
+            if end > word_start && font.measure(&text[word_start..next]) > max_width {
+                break;
+            }
+            end = next;
+        }
+        let line_width = font.measure(&text[word_start..end]);
+        lines.push(Line { range: word_start..end, width: line_width });
+        rest = end;
+    }
+    lines.extend(line);
+    // an empty line, or one of only spaces, still takes a line
+    if lines.len() == first {
+        lines.push(Line { range: range.start..range.start, width: 0 });
+    }
+}
@@ -0,0 +1,348 @@
+//! Anti-aliased text in TrueType fonts at arbitrary pixel sizes, for GUI text
+//! where the 8x16 bitmap `Font` is too coarse.
+//!
+//! Outlines are read with `ttf_parser` and filled by `ab_glyph_rasterizer`,
+//! which yields the coverage of every pixel. Text is laid out with the
+//! proportional advance of each glyph and the font's pair kerning.
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+use ab_glyph_rasterizer::{point, Point, Rasterizer};
+use ttf_parser::{
+    gpos::{PairAdjustment, PositioningSubtable},
+    Face, GlyphId, OutlineBuilder, Tag,
+};
+
+use super::{
+    color::{BlendMode, Rgba},
+    common::{PixelColor, XY},
+    font::{Glyph, GlyphSet},
+    frame_buffer::PixelWriter,
+};
+
+pub const ROBOTO_REGULAR: &[u8] = include_bytes!("../../resources/Roboto-Regular.ttf");
+
+/// Rasterized glyphs kept before the cache starts over.
+const MAX_CACHED_GLYPHS: usize = 512;
+/// Coverage from which a pixel is set in the monochrome glyphs of `CellGlyphs`.
+const CELL_THRESHOLD: u8 = 112;
+
+/// Vertical metrics of a line of text, in pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Distance from the top of the line to the baseline.
+    pub ascent: f32,
+    /// Distance from the baseline to the bottom of the line, negative below the baseline.
+    pub descent: f32,
+    pub line_gap: f32,
+}
+
+impl LineMetrics {
+    /// Distance between the baselines of consecutive lines.
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+/// Coverage of a glyph's pixels, 0 for outside and 255 for fully inside the outline.
+struct GlyphBitmap {
+    /// Position of the top left pixel relative to the pen position on the baseline.
+    offset: XY<isize>,
+    size: XY<usize>,
+    coverage: Vec<u8>,
+}
+
+pub struct TrueTypeFont<'a> {
+    face: Face<'a>,
+    /// GPOS lookups implementing the `kern` feature.
+    kern_lookups: Vec<u16>,
+    /// Rasterized glyphs by glyph and pixel size, as drawing text reuses a small set of glyphs.
+    cache: BTreeMap<(GlyphId, u32), GlyphBitmap>,
+}
+
+impl<'a> TrueTypeFont<'a> {
+    /// Parses the font in `data`, returning `None` if it is not a valid TrueType/OpenType font.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let face = Face::parse(data, 0).ok()?;
+        let mut kern_lookups = Vec::new();
+        if let Some(gpos) = face.tables().gpos {
+            for feature in gpos.features {
+                if feature.tag == Tag::from_bytes(b"kern") {
+                    kern_lookups.extend(feature.lookup_indices);
+                }
+            }
+        }
+        kern_lookups.sort_unstable();
+        kern_lookups.dedup();
+        Some(Self { face, kern_lookups, cache: BTreeMap::new() })
+    }
+
+    /// Scale from font units to pixels for text of `size` pixels per em.
+    fn scale(&self, size: f32) -> f32 {
+        size / self.face.units_per_em() as f32
+    }
+
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        let scale = self.scale(size);
+        LineMetrics {
+            ascent: self.face.ascender() as f32 * scale,
+            descent: self.face.descender() as f32 * scale,
+            line_gap: self.face.line_gap() as f32 * scale,
+        }
+    }
+
+    /// Glyph for `c`, or the font's `.notdef` glyph if it has none.
+    fn glyph_id(&self, c: char) -> GlyphId {
+        self.face.glyph_index(c).unwrap_or(GlyphId(0))
+    }
+
+    /// How far the pen moves after drawing `c`, in pixels.
+    pub fn advance(&self, c: char, size: f32) -> f32 {
+        self.glyph_advance(self.glyph_id(c), size)
+    }
+
+    fn glyph_advance(&self, id: GlyphId, size: f32) -> f32 {
+        self.face.glyph_hor_advance(id).unwrap_or(0) as f32 * self.scale(size)
+    }
+
+    /// Adjustment of the advance between `left` and `right`, usually negative
+    /// to tighten pairs like "AV".
+    pub fn kerning(&self, left: char, right: char, size: f32) -> f32 {
+        self.glyph_kerning(self.glyph_id(left), self.glyph_id(right), size)
+    }
+
+    fn glyph_kerning(&self, left: GlyphId, right: GlyphId, size: f32) -> f32 {
+        let units = self.gpos_kerning(left, right)
+            .or_else(|| self.kern_table_kerning(left, right))
+            .unwrap_or(0);
+        units as f32 * self.scale(size)
+    }
+
+    /// Pair adjustment from the GPOS table, which modern fonts such as Roboto use.
+    fn gpos_kerning(&self, left: GlyphId, right: GlyphId) -> Option<i16> {
+        let gpos = self.face.tables().gpos?;
+        for &index in &self.kern_lookups {
+            let Some(lookup) = gpos.lookups.get(index) else { continue };
+            for subtable in lookup.subtables.into_iter::<PositioningSubtable>() {
+                let PositioningSubtable::Pair(pair) = subtable else { continue };
+                let values = match pair {
+                    PairAdjustment::Format1 { coverage, sets } => coverage.get(left)
+                        .and_then(|i| sets.get(i))
+                        .and_then(|set| set.get(right)),
+                    PairAdjustment::Format2 { coverage, classes, matrix } if coverage.contains(left) => {
+                        matrix.get((classes.0.get(left), classes.1.get(right)))
+                    }
+                    PairAdjustment::Format2 { .. } => None,
+                };
+                // the first subtable holding the pair decides
+                if let Some((first, _)) = values {
+                    return Some(first.x_advance);
+                }
+            }
+        }
+        None
+    }
+
+    /// Pair adjustment from the legacy `kern` table.
+    fn kern_table_kerning(&self, left: GlyphId, right: GlyphId) -> Option<i16> {
+        self.face.tables().kern?.subtables.into_iter()
+            .filter(|subtable| subtable.horizontal && !subtable.variable)
+            .find_map(|subtable| subtable.glyphs_kerning(left, right))
+    }
+
+    /// Width of `text` in pixels as `draw_text` would lay it out.
+    pub fn measure(&self, text: &str, size: f32) -> f32 {
+        let mut width = 0.0;
+        let mut prev = None;
+        for c in text.chars() {
+            let id = self.glyph_id(c);
+            if let Some(prev) = prev {
+                width += self.glyph_kerning(prev, id, size);
+            }
+            width += self.glyph_advance(id, size);
+            prev = Some(id);
+        }
+        width
+    }
+
+    /// Draws `text` with the top of its line at `pos` and returns its width in pixels.
+    ///
+    /// Only pixels the glyphs cover are drawn, blending `fg` into `bg` by
+    /// coverage, so `bg` should be the color already behind the text. Pixels
+    /// outside `pixel_writer` are clipped.
+    pub fn draw_text(&mut self, pixel_writer: &mut dyn PixelWriter, pos: XY<isize>, size: f32,
+        fg: PixelColor, bg: PixelColor, text: &str) -> f32
+    {
+        let baseline = pos.y + libm::roundf(self.line_metrics(size).ascent) as isize;
+        let mut x = 0.0;
+        let mut prev = None;
+        for c in text.chars() {
+            let id = self.glyph_id(c);
+            if let Some(prev) = prev {
+                x += self.glyph_kerning(prev, id, size);
+            }
+            let pen = XY::new(pos.x + libm::roundf(x) as isize, baseline);
+            draw_bitmap(pixel_writer, self.rasterize(id, size), pen, fg, bg);
+            x += self.glyph_advance(id, size);
+            prev = Some(id);
+        }
+        x
+    }
+
+    fn rasterize(&mut self, id: GlyphId, size: f32) -> &GlyphBitmap {
+        let key = (id, size.to_bits());
+        if !self.cache.contains_key(&key) {
+            if self.cache.len() >= MAX_CACHED_GLYPHS {
+                self.cache.clear();
+            }
+            let bitmap = self.rasterize_uncached(id, size);
+            self.cache.insert(key, bitmap);
+        }
+        &self.cache[&key]
+    }
+
+    fn rasterize_uncached(&self, id: GlyphId, size: f32) -> GlyphBitmap {
+        let scale = self.scale(size);
+        let Some(bounds) = self.face.glyph_bounding_box(id) else {
+            // glyphs without outline such as the space
+            return GlyphBitmap { offset: XY::new(0, 0), size: XY::new(0, 0), coverage: Vec::new() };
+        };
+        // pixel bounds, with y growing downwards from the baseline
+        let left = libm::floorf(bounds.x_min as f32 * scale);
+        let top = libm::floorf(-bounds.y_max as f32 * scale);
+        let width = (libm::ceilf(bounds.x_max as f32 * scale) - left) as usize;
+        let height = (libm::ceilf(-bounds.y_min as f32 * scale) - top) as usize;
+
+        let mut builder = Builder {
+            rasterizer: Rasterizer::new(width, height),
+            scale,
+            origin: point(left, top),
+            start: point(0.0, 0.0),
+            last: point(0.0, 0.0),
+        };
+        self.face.outline_glyph(id, &mut builder);
+        let mut coverage = vec![0; width * height];
+        builder.rasterizer.for_each_pixel(|i, alpha| {
+            coverage[i] = libm::roundf(alpha.clamp(0.0, 1.0) * 255.0) as u8;
+        });
+        GlyphBitmap { offset: XY::new(left as isize, top as isize), size: XY::new(width, height), coverage }
+    }
+}
+
+/// A TrueType font rendered into monochrome glyphs of one cell size, so that
+/// the console can use it like a bitmap font.
+///
+/// Glyphs are rendered up front for printable ASCII and Latin-1, each
+/// centered in a cell as wide as the widest of them; other characters are
+/// left to the `Font`'s fallback.
+pub struct CellGlyphs {
+    size: XY<usize>,
+    glyphs: BTreeMap<char, Vec<u8>>,
+}
+
+impl CellGlyphs {
+    pub fn new(font: &TrueTypeFont, pixel_size: f32) -> Self {
+        let chars = || (' '..='~').chain('\u{a0}'..='\u{ff}');
+        let advance = chars().map(|c| font.advance(c, pixel_size)).fold(0.0, f32::max);
+        let metrics = font.line_metrics(pixel_size);
+        let size = XY::new(libm::ceilf(advance) as usize, libm::ceilf(metrics.line_height()) as usize);
+        let baseline = libm::roundf(metrics.ascent + metrics.line_gap / 2.0) as isize;
+        let stride = size.x.div_ceil(8);
+
+        let mut glyphs = BTreeMap::new();
+        for c in chars() {
+            let Some(id) = font.face.glyph_index(c) else { continue };
+            let bitmap = font.rasterize_uncached(id, pixel_size);
+            let pen_x = libm::roundf((size.x as f32 - font.glyph_advance(id, pixel_size)) / 2.0) as isize;
+            let mut bits = vec![0; stride * size.y];
+            for dy in 0..bitmap.size.y {
+                for dx in 0..bitmap.size.x {
+                    let x = pen_x + bitmap.offset.x + dx as isize;
+                    let y = baseline + bitmap.offset.y + dy as isize;
+                    let inside = (0..size.x as isize).contains(&x) && (0..size.y as isize).contains(&y);
+                    if inside && bitmap.coverage[dy * bitmap.size.x + dx] >= CELL_THRESHOLD {
+                        bits[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8);
+                    }
+                }
+            }
+            glyphs.insert(c, bits);
+        }
+        Self { size, glyphs }
+    }
+}
+
+impl GlyphSet for CellGlyphs {
+    fn glyph_size(&self) -> XY<usize> {
+        self.size
+    }
+    fn glyph(&self, c: char) -> Option<Glyph<'_>> {
+        let bitmap = self.glyphs.get(&c)?;
+        Some(Glyph { size: self.size, bitmap })
+    }
+}
+
+/// Feeds a glyph outline in font units to the rasterizer in pixels.
+struct Builder {
+    rasterizer: Rasterizer,
+    scale: f32,
+    /// Top left of the glyph's pixel bounds, relative to the baseline origin.
+    origin: Point,
+    start: Point,
+    last: Point,
+}
+
+impl Builder {
+    fn point(&self, x: f32, y: f32) -> Point {
+        point(x * self.scale - self.origin.x, -y * self.scale - self.origin.y)
+    }
+}
+
+impl OutlineBuilder for Builder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.point(x, y);
+        self.last = self.start;
+    }
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.rasterizer.draw_line(self.last, p);
+        self.last = p;
+    }
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.rasterizer.draw_quad(self.last, self.point(x1, y1), p);
+        self.last = p;
+    }
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.rasterizer.draw_cubic(self.last, self.point(x1, y1), self.point(x2, y2), p);
+        self.last = p;
+    }
+    fn close(&mut self) {
+        if self.last != self.start {
+            self.rasterizer.draw_line(self.last, self.start);
+        }
+        self.last = self.start;
+    }
+}
+
+fn draw_bitmap(pixel_writer: &mut dyn PixelWriter, bitmap: &GlyphBitmap, pen: XY<isize>,
+    fg: PixelColor, bg: PixelColor)
+{
+    let size = pixel_writer.size();
+    for dy in 0..bitmap.size.y {
+        for dx in 0..bitmap.size.x {
+            let coverage = bitmap.coverage[dy * bitmap.size.x + dx];
+            let x = pen.x + bitmap.offset.x + dx as isize;
+            let y = pen.y + bitmap.offset.y + dy as isize;
+            if coverage == 0 || x < 0 || y < 0 || x as usize >= size.x || y as usize >= size.y {
+                continue;
+            }
+            pixel_writer.draw_pixel(XY::new(x as usize, y as usize), blend(fg, bg, coverage));
+        }
+    }
+}
+
+/// Mixes `fg` into `bg` by `alpha` out of 255.
+fn blend(fg: PixelColor, bg: PixelColor, alpha: u8) -> PixelColor {
+    BlendMode::SourceOver.blend(Rgba::from(fg).with_alpha(alpha).premultiply(), bg)
+}
@@ -0,0 +1,94 @@
+//! A fixed set of consoles sharing the framebuffer, of which one is shown at a time.
+
+use alloc::vec::Vec;
+
+use super::common::XY;
+use super::console::Console;
+use super::font::Font;
+use super::frame_buffer::PixelWriter;
+use crate::input::{Key, KeyEvent};
+
+/// Number of virtual consoles, switched with Alt+F1 through Alt+F6.
+pub const CONSOLE_COUNT: usize = 6;
+/// Console receiving `print!`, shown after boot.
+pub const KERNEL_LOG: usize = 0;
+
+/// Virtual consoles, each with its own grid, cursor and scrollback.
+///
+/// Only the active console draws to the framebuffer; the others keep
+/// accepting output and are repainted in full when switched to.
+pub struct VirtualConsoles<'a> {
+    consoles: Vec<Console<'a>>,
+    active: usize,
+}
+
+impl<'a> VirtualConsoles<'a> {
+    /// Creates `CONSOLE_COUNT` consoles filling `pixel_size`, with `KERNEL_LOG` active.
+    pub fn new(font: Font<'a>, pixel_size: XY<usize>) -> Self {
+        let consoles = (0..CONSOLE_COUNT)
+            .map(|i| {
+                let mut console = Console::new(font, pixel_size);
+                console.set_visible(i == KERNEL_LOG);
+                console
+            })
+            .collect();
+        Self { consoles, active: KERNEL_LOG }
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_mut(&mut self) -> &mut Console<'a> {
+        &mut self.consoles[self.active]
+    }
+
+    /// Console `index`, which must be less than `CONSOLE_COUNT`.
+    pub fn get(&self, index: usize) -> &Console<'a> {
+        &self.consoles[index]
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut Console<'a> {
+        &mut self.consoles[index]
+    }
+
+    /// Shows console `index` in place of the active one.
+    pub fn switch_to(&mut self, pixel_writer: &mut dyn PixelWriter, index: usize) {
+        if index == self.active {
+            return;
+        }
+        self.consoles[self.active].set_visible(false);
+        self.active = index;
+        self.consoles[index].set_visible(true);
+        self.consoles[index].flush(pixel_writer);
+    }
+
+    /// Re-lays out all consoles for the drawing target's current size and
+    /// repaints the active one.
+    pub fn resize(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        for console in &mut self.consoles {
+            console.resize(pixel_writer.size());
+        }
+        self.active_mut().flush(pixel_writer);
+    }
+
+    /// Switches all consoles to `font` and repaints the active one.
+    pub fn set_font(&mut self, pixel_writer: &mut dyn PixelWriter, font: Font<'a>) {
+        for console in &mut self.consoles {
+            console.set_font(font, pixel_writer.size());
+        }
+        self.active_mut().flush(pixel_writer);
+    }
+
+    /// Switches consoles on Alt+F1..F6 and passes other keys to the active
+    /// console, returning whether `event` was consumed.
+    pub fn handle_key(&mut self, pixel_writer: &mut dyn PixelWriter, event: KeyEvent) -> bool {
+        match event.key {
+            Key::F(n) if event.modifiers.alt && (1..=CONSOLE_COUNT as u8).contains(&n) => {
+                self.switch_to(pixel_writer, n as usize - 1);
+                true
+            }
+            _ => self.active_mut().handle_key(pixel_writer, event),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Parser for the VT100/xterm escape sequences understood by the console.
+//!
+//! The parser follows the structure of the DEC state machine, reduced to the
+//! sequences a console needs: C0 controls, `ESC <final>`, CSI sequences with
+//! numeric parameters and at most one intermediate, and OSC strings, which are
+//! consumed and ignored.
+
+/// Most parameters a CSI sequence can carry; sequences with more are ignored.
+pub const MAX_PARAMS: usize = 16;
+
+/// A complete control sequence, or a character to print.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    Print(char),
+    /// A C0 control character such as `\n`, `\r`, `\t` or backspace.
+    Control(char),
+    /// `ESC` followed by a final character, e.g. `ESC 7` (save cursor).
+    Esc(char),
+    Csi(Csi),
+}
+
+/// A Control Sequence Introducer sequence: `ESC [ <private> <params> <intermediate> <final>`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Csi {
+    params: [u16; MAX_PARAMS],
+    len: usize,
+    /// A private marker such as `?` in `ESC [ ? 25 h`.
+    pub private: Option<char>,
+    /// An intermediate such as the space in `ESC [ 2 SP q`.
+    pub intermediate: Option<char>,
+    pub final_char: char,
+}
+
+impl Csi {
+    /// Parameters as given; an omitted parameter reads as 0.
+    pub fn params(&self) -> &[u16] {
+        &self.params[..self.len]
+    }
+
+    /// Parameter `i`, or `default` if it was omitted or 0.
+    pub fn param(&self, i: usize, default: u16) -> u16 {
+        match self.params().get(i) {
+            Some(&p) if p != 0 => p,
+            _ => default,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+    Ground,
+    Escape,
+    /// Saw `ESC` and an intermediate such as `(` in `ESC ( B` (character set selection).
+    EscIntermediate,
+    CsiParam,
+    /// Inside a malformed CSI sequence, skipping to its final character.
+    CsiIgnore,
+    OscString,
+    /// Saw `ESC` inside an OSC string, which may be the `ESC \` terminator.
+    OscEscape,
+}
+
+pub struct Parser {
+    state: State,
+    csi: Csi,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Ground,
+            csi: Csi { params: [0; MAX_PARAMS], len: 0, private: None, intermediate: None, final_char: '\0' },
+        }
+    }
+
+    /// Feeds one character, returning an action once a sequence is complete.
+    pub fn advance(&mut self, c: char) -> Option<Action> {
+        // CAN and SUB abort any sequence, ESC starts a new one
+        match (c, self.state) {
+            ('\x18' | '\x1a', _) => {
+                self.state = State::Ground;
+                return None;
+            }
+            ('\x1b', State::OscString) => {
+                self.state = State::OscEscape;
+                return None;
+            }
+            ('\x1b', _) => {
+                self.state = State::Escape;
+                return None;
+            }
+            _ => {}
+        }
+
+        match self.state {
+            State::Ground => Some(if c.is_control() { Action::Control(c) } else { Action::Print(c) }),
+            State::Escape => match c {
+                '[' => {
+                    self.csi.len = 0;
+                    self.csi.private = None;
+                    self.csi.intermediate = None;
+                    self.state = State::CsiParam;
+                    None
+                }
+                ']' => {
+                    self.state = State::OscString;
+                    None
+                }
+                c if c.is_control() => Some(Action::Control(c)),
+                '\x20'..='\x2f' => {
+                    self.state = State::EscIntermediate;
+                    None
+                }
+                c => {
+                    self.state = State::Ground;
+                    Some(Action::Esc(c))
+                }
+            },
+            State::EscIntermediate => {
+                // character sets are not supported, the sequence is consumed without effect
+                if !('\x20'..='\x2f').contains(&c) {
+                    self.state = State::Ground;
+                }
+                None
+            }
+            State::CsiParam => self.csi_param(c),
+            State::CsiIgnore => {
+                if ('\x40'..='\x7e').contains(&c) {
+                    self.state = State::Ground;
+                }
+                None
+            }
+            State::OscString => {
+                if c == '\x07' {
+                    self.state = State::Ground;
+                }
+                None
+            }
+            State::OscEscape => {
+                // `ESC \` ends the string; anything else is treated as if the string ended as well
+                self.state = State::Ground;
+                None
+            }
+        }
+    }
+
+    fn csi_param(&mut self, c: char) -> Option<Action> {
+        if self.csi.intermediate.is_some() && ('\x30'..='\x3f').contains(&c) {
+            // parameters have to come before the intermediate
+            self.state = State::CsiIgnore;
+            return None;
+        }
+        match c {
+            '0'..='9' => {
+                if self.csi.len == 0 {
+                    self.csi.len = 1;
+                    self.csi.params[0] = 0;
+                }
+                let p = &mut self.csi.params[self.csi.len - 1];
+                *p = p.saturating_mul(10).saturating_add(c as u16 - '0' as u16);
+                None
+            }
+            // `:` separates sub-parameters as in `38:5:n`, read like `;`
+            ';' | ':' => {
+                if self.csi.len == 0 {
+                    self.csi.len = 1;
+                    self.csi.params[0] = 0;
+                }
+                if self.csi.len == MAX_PARAMS {
+                    self.state = State::CsiIgnore;
+                } else {
+                    self.csi.params[self.csi.len] = 0;
+                    self.csi.len += 1;
+                }
+                None
+            }
+            '<'..='?' if self.csi.len == 0 && self.csi.private.is_none() => {
+                self.csi.private = Some(c);
+                None
+            }
+            '\x20'..='\x2f' if self.csi.intermediate.is_none() => {
+                self.csi.intermediate = Some(c);
+                None
+            }
+            '\x40'..='\x7e' => {
+                self.state = State::Ground;
+                self.csi.final_char = c;
+                Some(Action::Csi(self.csi.clone()))
+            }
+            // controls are executed in the middle of a sequence
+            c if c.is_control() => Some(Action::Control(c)),
+            _ => {
+                // further intermediates and misplaced markers: unsupported, skip the sequence
+                self.state = State::CsiIgnore;
+                None
+            }
+        }
+    }
+}
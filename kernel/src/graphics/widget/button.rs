@@ -0,0 +1,74 @@
+//! A push button, clicked with the mouse or with Enter or Space while focused.
+
+use alloc::string::String;
+
+use super::{draw_text, Action, Response, Widget, BACKGROUND, BORDER, FACE, FACE_PRESSED, FOCUS, TEXT};
+use crate::{
+    graphics::{common::XY, font::Font, frame_buffer::PixelWriter, rect::Rect, text_layout::TextFont},
+    input::{Key, KeyEvent, MouseEvent},
+};
+
+/// Space between the label and the frame.
+const PADDING: XY<usize> = XY { x: 8, y: 4 };
+
+pub struct Button {
+    label: String,
+    /// Whether the button is held down with the mouse.
+    pressed: bool,
+}
+
+impl Button {
+    pub fn new(label: &str) -> Self {
+        Self { label: label.into(), pressed: false }
+    }
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+}
+
+impl Widget for Button {
+    fn preferred_size(&self, font: &Font) -> XY<usize> {
+        XY::new(font.measure(&self.label) + 2 * PADDING.x, font.glyph_size().y + 2 * PADDING.y)
+    }
+
+    fn draw(&self, pixel_writer: &mut dyn PixelWriter, rect: Rect, font: &Font, focused: bool) {
+        pixel_writer.fill_rect(rect, BACKGROUND);
+        let face = if self.pressed { FACE_PRESSED } else { FACE };
+        pixel_writer.fill_rounded_rect(rect, 3, face);
+        pixel_writer.draw_rounded_rect(rect, 3, if focused { FOCUS } else { BORDER });
+        let width = font.measure(&self.label);
+        let x = rect.x + (rect.w.saturating_sub(width) / 2) as isize;
+        let y = rect.y + (rect.h.saturating_sub(font.glyph_size().y) / 2) as isize;
+        draw_text(pixel_writer, font, XY::new(x, y), &self.label, TEXT, face, rect);
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn handle_mouse(&mut self, event: MouseEvent, size: XY<usize>, _font: &Font) -> Response {
+        let inside = Rect::from_size(size).is_contained(event.pos);
+        match (self.pressed, event.buttons.left) {
+            (false, true) if inside => {
+                self.pressed = true;
+                Response::REDRAW
+            }
+            (true, false) => {
+                // a click only counts if the pointer is still over the button
+                self.pressed = false;
+                if inside { Response::action(Action::Clicked) } else { Response::REDRAW }
+            }
+            _ => Response::IGNORED,
+        }
+    }
+
+    fn handle_key(&mut self, event: KeyEvent, _size: XY<usize>, _font: &Font) -> Response {
+        match event.key {
+            Key::Enter | Key::Char(' ') => Response::action(Action::Clicked),
+            _ => Response::IGNORED,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! A line of text that cannot be edited.
+
+use alloc::string::String;
+
+use super::{draw_text, Widget, BACKGROUND, TEXT};
+use crate::graphics::{common::XY, font::Font, frame_buffer::PixelWriter, rect::Rect, text_layout::TextFont};
+
+pub struct Label {
+    text: String,
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        Self { text: text.into() }
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.text.push_str(text);
+    }
+}
+
+impl Widget for Label {
+    fn preferred_size(&self, font: &Font) -> XY<usize> {
+        XY::new(font.measure(&self.text), font.glyph_size().y)
+    }
+    fn draw(&self, pixel_writer: &mut dyn PixelWriter, rect: Rect, font: &Font, _focused: bool) {
+        pixel_writer.fill_rect(rect, BACKGROUND);
+        // centered vertically when stretched across a row
+        let y = rect.y + (rect.h.saturating_sub(font.glyph_size().y) / 2) as isize;
+        draw_text(pixel_writer, font, XY::new(rect.x, y), &self.text, TEXT, BACKGROUND, rect);
+    }
+}
@@ -0,0 +1,177 @@
+//! A scrollable list of lines of which one can be selected.
+
+use alloc::{string::String, vec::Vec};
+
+use super::{draw_text, Action, Response, Widget, BORDER, FACE, FACE_PRESSED, FIELD, FOCUS, SELECTED_TEXT, TEXT};
+use crate::{
+    graphics::{common::XY, font::Font, frame_buffer::PixelWriter, rect::Rect},
+    input::{Key, KeyEvent, MouseEvent},
+};
+
+/// Width of the scroll bar, shown only when not all items fit.
+const SCROLL_BAR_WIDTH: usize = 10;
+/// Space between the text of an item and the frame.
+const PADDING: usize = 2;
+
+pub struct ListView {
+    items: Vec<String>,
+    selected: Option<usize>,
+    /// The first item in view.
+    top: usize,
+    /// Items shown at the preferred size.
+    rows: usize,
+    /// Characters shown per item at the preferred size.
+    columns: usize,
+}
+
+impl ListView {
+    /// A list of `items` showing `rows` of them at a time, `columns` characters wide.
+    pub fn new(items: Vec<String>, rows: usize, columns: usize) -> Self {
+        Self { items, selected: None, top: 0, rows, columns }
+    }
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+    /// Replaces the items, clearing the selection.
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected = None;
+        self.top = 0;
+    }
+    pub fn push(&mut self, item: String) {
+        self.items.push(item);
+    }
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    fn row_height(font: &Font) -> usize {
+        font.glyph_size().y + 2 * PADDING
+    }
+    /// Items fitting into a list of `height` pixels.
+    fn visible_rows(font: &Font, height: usize) -> usize {
+        (height.saturating_sub(2) / Self::row_height(font)).max(1)
+    }
+    fn max_top(&self, visible: usize) -> usize {
+        self.items.len().saturating_sub(visible)
+    }
+    fn scrollable(&self, visible: usize) -> bool {
+        self.items.len() > visible
+    }
+
+    /// Scrolls by `rows` items, up for negative values.
+    pub fn scroll(&mut self, rows: isize, visible: usize) {
+        self.top = self.top.saturating_add_signed(rows).min(self.max_top(visible));
+    }
+
+    /// Selects `index`, clamped to the items, and scrolls it into view.
+    fn select(&mut self, index: usize, visible: usize) -> Response {
+        let Some(last) = self.items.len().checked_sub(1) else { return Response::IGNORED };
+        let index = index.min(last);
+        if index < self.top {
+            self.top = index;
+        } else if index >= self.top + visible {
+            self.top = index + 1 - visible;
+        }
+        if self.selected == Some(index) {
+            return Response::REDRAW;
+        }
+        self.selected = Some(index);
+        Response::action(Action::Selected(index))
+    }
+
+    /// The thumb of the scroll bar within a track `height` pixels high.
+    fn thumb(&self, visible: usize, height: usize) -> (usize, usize) {
+        let len = self.items.len().max(1);
+        let size = (height * visible / len).clamp(4.min(height), height);
+        let offset = (height - size) * self.top / self.max_top(visible).max(1);
+        (offset, size)
+    }
+}
+
+impl Widget for ListView {
+    fn preferred_size(&self, font: &Font) -> XY<usize> {
+        XY::new(self.columns * font.char_size().x + 2 * PADDING + SCROLL_BAR_WIDTH + 2,
+            self.rows * Self::row_height(font) + 2)
+    }
+
+    fn draw(&self, pixel_writer: &mut dyn PixelWriter, rect: Rect, font: &Font, focused: bool) {
+        pixel_writer.fill_rect(rect, FIELD);
+        pixel_writer.draw_rect(rect, if focused { FOCUS } else { BORDER });
+        let visible = Self::visible_rows(font, rect.h);
+        let top = self.top.min(self.max_top(visible));
+        let scroll_bar = if self.scrollable(visible) { SCROLL_BAR_WIDTH } else { 0 };
+        let inner = Rect::new(rect.x + 1, rect.y + 1, rect.w.saturating_sub(2 + scroll_bar), rect.h.saturating_sub(2));
+        let row_height = Self::row_height(font);
+
+        for (i, item) in self.items.iter().enumerate().skip(top).take(visible) {
+            let row = Rect::new(inner.x, inner.y + ((i - top) * row_height) as isize, inner.w, row_height);
+            let (fg, bg) = match self.selected {
+                Some(selected) if selected == i && focused => (SELECTED_TEXT, FOCUS),
+                Some(selected) if selected == i => (TEXT, FACE_PRESSED),
+                _ => (TEXT, FIELD),
+            };
+            let Some(row) = row.intersection(inner) else { break };
+            pixel_writer.fill_rect(row, bg);
+            let pos = XY::new(row.x + PADDING as isize, row.y + PADDING as isize);
+            draw_text(pixel_writer, font, pos, item, fg, bg, row);
+        }
+
+        if scroll_bar > 0 {
+            let track = Rect::new(inner.right(), inner.y, scroll_bar, inner.h);
+            pixel_writer.fill_rect(track, FACE);
+            let (offset, size) = self.thumb(visible, track.h);
+            let thumb = Rect::new(track.x + 1, track.y + offset as isize, scroll_bar - 2, size);
+            pixel_writer.fill_rect(thumb, BORDER);
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    /// Pressing an item selects it; pressing the scroll bar above or below
+    /// its thumb scrolls by a page.
+    fn handle_mouse(&mut self, event: MouseEvent, size: XY<usize>, font: &Font) -> Response {
+        if !event.buttons.left || !Rect::from_size(size).is_contained(event.pos) {
+            return Response::IGNORED;
+        }
+        let visible = Self::visible_rows(font, size.y);
+        self.top = self.top.min(self.max_top(visible));
+        let y = (event.pos.y - 1).max(0) as usize;
+        if self.scrollable(visible) && event.pos.x >= size.x.saturating_sub(1 + SCROLL_BAR_WIDTH) as isize {
+            let (offset, thumb) = self.thumb(visible, size.y.saturating_sub(2));
+            let page = visible as isize;
+            match y {
+                y if y < offset => self.scroll(-page, visible),
+                y if y >= offset + thumb => self.scroll(page, visible),
+                _ => return Response::IGNORED,
+            }
+            return Response::REDRAW;
+        }
+        let index = self.top + y / Self::row_height(font);
+        if index >= self.items.len() {
+            return Response::IGNORED;
+        }
+        self.select(index, visible)
+    }
+
+    fn handle_key(&mut self, event: KeyEvent, size: XY<usize>, font: &Font) -> Response {
+        let visible = Self::visible_rows(font, size.y);
+        let current = self.selected;
+        let index = match (event.key, current) {
+            (Key::Enter, Some(_)) => return Response::action(Action::Submitted),
+            (Key::Up | Key::Down | Key::PageUp | Key::PageDown, None) | (Key::Home, _) => 0,
+            (Key::Up, Some(i)) => i.saturating_sub(1),
+            (Key::Down, Some(i)) => i + 1,
+            (Key::PageUp, Some(i)) => i.saturating_sub(visible),
+            (Key::PageDown, Some(i)) => i + visible,
+            (Key::End, _) => usize::MAX,
+            _ => return Response::IGNORED,
+        };
+        self.select(index, visible)
+    }
+}
@@ -0,0 +1,463 @@
+//! A small widget toolkit for windows: widgets arranged in rows and columns,
+//! with mouse and keyboard events dispatched to them and a keyboard focus
+//! moved with Tab.
+//!
+//! A `Ui` owns a tree of containers and widgets. It lays them out in its
+//! drawing target, repaints only widgets that changed, and turns input into
+//! `Action`s for the application, e.g. a button being clicked.
+
+mod button;
+mod label;
+mod list;
+mod text_field;
+
+pub use button::Button;
+pub use label::Label;
+pub use list::ListView;
+pub use text_field::TextField;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::any::Any;
+
+use super::{
+    common::{PixelColor, XY},
+    font::{self, Font},
+    frame_buffer::PixelWriter,
+    rect::Rect,
+};
+use crate::input::{Key, KeyEvent, MouseButtons, MouseEvent};
+
+pub const BACKGROUND: PixelColor = PixelColor { r: 224, g: 224, b: 228 };
+pub const TEXT: PixelColor = PixelColor { r: 16, g: 16, b: 16 };
+pub const BORDER: PixelColor = PixelColor { r: 96, g: 96, b: 108 };
+pub const FOCUS: PixelColor = PixelColor { r: 48, g: 88, b: 168 };
+pub const FACE: PixelColor = PixelColor { r: 200, g: 200, b: 208 };
+pub const FACE_PRESSED: PixelColor = PixelColor { r: 160, g: 160, b: 172 };
+pub const FIELD: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+pub const SELECTED_TEXT: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+
+/// What a widget reports to the application.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// A button was clicked or pressed with Enter or Space.
+    Clicked,
+    /// The text of a field was edited.
+    Changed,
+    /// Enter was pressed in a text field or on the selected item of a list.
+    Submitted,
+    /// The item at the index became selected in a list.
+    Selected(usize),
+}
+
+/// The outcome of passing an event to a widget.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Response {
+    /// Whether the widget has to be drawn again.
+    pub redraw: bool,
+    pub action: Option<Action>,
+}
+
+impl Response {
+    pub const IGNORED: Response = Response { redraw: false, action: None };
+    pub const REDRAW: Response = Response { redraw: true, action: None };
+
+    pub const fn action(action: Action) -> Self {
+        Response { redraw: true, action: Some(action) }
+    }
+}
+
+/// A user interface element. Positions and sizes passed in are relative to
+/// the widget's own area.
+pub trait Widget: Any + Send {
+    fn preferred_size(&self, font: &Font) -> XY<usize>;
+    /// Draws the widget over all of `rect`, background included.
+    fn draw(&self, pixel_writer: &mut dyn PixelWriter, rect: Rect, font: &Font, focused: bool);
+    /// Whether the widget takes keyboard input and can be focused.
+    fn focusable(&self) -> bool {
+        false
+    }
+    /// Mouse events from a press inside the widget until the release, even if
+    /// the pointer leaves it meanwhile.
+    fn handle_mouse(&mut self, _event: MouseEvent, _size: XY<usize>, _font: &Font) -> Response {
+        Response::IGNORED
+    }
+    /// Key presses while the widget has the focus.
+    fn handle_key(&mut self, _event: KeyEvent, _size: XY<usize>, _font: &Font) -> Response {
+        Response::IGNORED
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Children side by side, from left to right.
+    Row,
+    /// Children on top of each other, from top to bottom.
+    Column,
+}
+
+/// How a container arranges its children.
+///
+/// Children get their preferred size along the direction and the full size
+/// of the container across it. Space left over is shared by the children
+/// marked with `Ui::set_grow`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub direction: Direction,
+    /// Space between the container's edge and its children.
+    pub padding: usize,
+    /// Space between neighbouring children.
+    pub spacing: usize,
+}
+
+impl Layout {
+    pub const fn row() -> Self {
+        Layout { direction: Direction::Row, padding: 0, spacing: 0 }
+    }
+    pub const fn column() -> Self {
+        Layout { direction: Direction::Column, padding: 0, spacing: 0 }
+    }
+    pub const fn with_padding(self, padding: usize) -> Self {
+        Layout { padding, ..self }
+    }
+    pub const fn with_spacing(self, spacing: usize) -> Self {
+        Layout { spacing, ..self }
+    }
+
+    /// `size` along the layout's direction and across it.
+    fn split(&self, size: XY<usize>) -> (usize, usize) {
+        match self.direction {
+            Direction::Row => (size.x, size.y),
+            Direction::Column => (size.y, size.x),
+        }
+    }
+    fn join(&self, main: usize, cross: usize) -> XY<usize> {
+        match self.direction {
+            Direction::Row => XY::new(main, cross),
+            Direction::Column => XY::new(cross, main),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WidgetId(usize);
+
+enum Content {
+    Container(Layout),
+    Widget(Box<dyn Widget>),
+}
+
+struct Node {
+    content: Content,
+    children: Vec<WidgetId>,
+    grow: bool,
+    rect: Rect,
+    /// Preferred size when last laid out, to notice widgets that need more or less room.
+    preferred: XY<usize>,
+    dirty: bool,
+}
+
+/// A tree of widgets drawn into one target, usually a window's surface.
+pub struct Ui<'a> {
+    font: Font<'a>,
+    nodes: Vec<Node>,
+    size: XY<usize>,
+    needs_layout: bool,
+    focus: Option<WidgetId>,
+    /// The widget pressed with the mouse, which receives mouse events until the release.
+    capture: Option<WidgetId>,
+    buttons: MouseButtons,
+}
+
+impl<'a> Ui<'a> {
+    /// An empty user interface whose root container arranges its children with `layout`.
+    pub fn new(font: Font<'a>, layout: Layout) -> Self {
+        let root = Node {
+            content: Content::Container(layout),
+            children: Vec::new(),
+            grow: true,
+            rect: Rect::new(0, 0, 0, 0),
+            preferred: XY::new(0, 0),
+            dirty: true,
+        };
+        Self {
+            font,
+            nodes: vec![root],
+            size: XY::new(0, 0),
+            needs_layout: true,
+            focus: None,
+            capture: None,
+            buttons: MouseButtons::NONE,
+        }
+    }
+
+    pub fn root(&self) -> WidgetId {
+        WidgetId(0)
+    }
+
+    fn push(&mut self, parent: WidgetId, content: Content) -> WidgetId {
+        assert!(matches!(self.nodes[parent.0].content, Content::Container(_)), "widgets cannot have children");
+        let id = WidgetId(self.nodes.len());
+        self.nodes.push(Node {
+            content,
+            children: Vec::new(),
+            grow: false,
+            rect: Rect::new(0, 0, 0, 0),
+            preferred: XY::new(0, 0),
+            dirty: true,
+        });
+        self.nodes[parent.0].children.push(id);
+        self.needs_layout = true;
+        id
+    }
+
+    /// Adds a container as the last child of `parent`, which must be a container.
+    pub fn add_container(&mut self, parent: WidgetId, layout: Layout) -> WidgetId {
+        self.push(parent, Content::Container(layout))
+    }
+
+    /// Adds `widget` as the last child of `parent`, which must be a container.
+    pub fn add<W: Widget>(&mut self, parent: WidgetId, widget: W) -> WidgetId {
+        self.push(parent, Content::Widget(Box::new(widget)))
+    }
+
+    /// Lets `id` take a share of the space its container has left over.
+    pub fn set_grow(&mut self, id: WidgetId, grow: bool) {
+        self.nodes[id.0].grow = grow;
+        self.needs_layout = true;
+    }
+
+    pub fn widget<W: Widget>(&self, id: WidgetId) -> Option<&W> {
+        match &self.nodes.get(id.0)?.content {
+            Content::Widget(widget) => (widget.as_ref() as &dyn Any).downcast_ref(),
+            Content::Container(_) => None,
+        }
+    }
+
+    /// The widget to change, which is drawn again with the next `draw`.
+    pub fn widget_mut<W: Widget>(&mut self, id: WidgetId) -> Option<&mut W> {
+        let node = self.nodes.get_mut(id.0)?;
+        node.dirty = true;
+        match &mut node.content {
+            Content::Widget(widget) => (widget.as_mut() as &mut dyn Any).downcast_mut(),
+            Content::Container(_) => None,
+        }
+    }
+
+    /// Where `id` was placed by the last layout.
+    pub fn rect(&self, id: WidgetId) -> Rect {
+        self.nodes[id.0].rect
+    }
+
+    pub fn focus(&self) -> Option<WidgetId> {
+        self.focus
+    }
+
+    pub fn set_focus(&mut self, id: Option<WidgetId>) {
+        if id == self.focus {
+            return;
+        }
+        for node in [self.focus, id].into_iter().flatten() {
+            self.nodes[node.0].dirty = true;
+        }
+        self.focus = id;
+    }
+
+    /// The size the widgets need at their preferred sizes.
+    pub fn preferred_size(&self) -> XY<usize> {
+        self.preferred(self.root())
+    }
+
+    fn preferred(&self, id: WidgetId) -> XY<usize> {
+        let node = &self.nodes[id.0];
+        match &node.content {
+            Content::Widget(widget) => widget.preferred_size(&self.font),
+            Content::Container(layout) => {
+                let (mut main, mut cross) = (0, 0);
+                for &child in &node.children {
+                    let (child_main, child_cross) = layout.split(self.preferred(child));
+                    main += child_main;
+                    cross = cross.max(child_cross);
+                }
+                main += layout.spacing * node.children.len().saturating_sub(1);
+                layout.join(main + 2 * layout.padding, cross + 2 * layout.padding)
+            }
+        }
+    }
+
+    /// Lays the widgets out to fill `size` and draws all of them with the next `draw`.
+    pub fn resize(&mut self, size: XY<usize>) {
+        self.size = size;
+        self.needs_layout = true;
+    }
+
+    /// Lays the widgets out again if any was added, resized or changed its preferred size.
+    fn update_layout(&mut self) {
+        // widgets whose content now needs another size move their neighbours
+        let resized = self.nodes.iter().enumerate().any(|(i, node)| {
+            node.dirty && matches!(node.content, Content::Widget(_)) && self.preferred(WidgetId(i)) != node.preferred
+        });
+        if self.needs_layout || resized {
+            self.needs_layout = false;
+            self.arrange(self.root(), Rect::from_size(self.size));
+        }
+    }
+
+    fn arrange(&mut self, id: WidgetId, rect: Rect) {
+        let preferred = self.preferred(id);
+        let node = &mut self.nodes[id.0];
+        node.rect = rect;
+        node.preferred = preferred;
+        node.dirty = true;
+        let Content::Container(layout) = node.content else { return };
+        let children = node.children.clone();
+
+        let (main, cross) = layout.split(XY::new(rect.w, rect.h));
+        let inner_main = main.saturating_sub(2 * layout.padding);
+        let inner_cross = cross.saturating_sub(2 * layout.padding);
+        let sizes: Vec<usize> = children.iter().map(|&child| layout.split(self.preferred(child)).0).collect();
+        let used = sizes.iter().sum::<usize>() + layout.spacing * children.len().saturating_sub(1);
+        let growing = children.iter().filter(|child| self.nodes[child.0].grow).count();
+        let extra = inner_main.saturating_sub(used);
+
+        let mut offset = layout.padding;
+        let mut grown = 0;
+        for (&child, &size) in children.iter().zip(&sizes) {
+            let mut size = size;
+            if self.nodes[child.0].grow {
+                // the last growing child takes what division leaves over
+                grown += 1;
+                size += if grown == growing { extra - extra / growing * (growing - 1) } else { extra / growing };
+            }
+            let size = size.min(main.saturating_sub(offset));
+            let child_rect = match layout.direction {
+                Direction::Row => Rect::new(rect.x + offset as isize, rect.y + layout.padding as isize, size, inner_cross),
+                Direction::Column => Rect::new(rect.x + layout.padding as isize, rect.y + offset as isize, inner_cross, size),
+            };
+            self.arrange(child, child_rect);
+            offset += size + layout.spacing;
+        }
+    }
+
+    /// Draws what changed since the last call, laying the widgets out again if needed.
+    pub fn draw(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        self.update_layout();
+        self.draw_node(pixel_writer, self.root());
+    }
+
+    fn draw_node(&mut self, pixel_writer: &mut dyn PixelWriter, id: WidgetId) {
+        let focused = self.focus == Some(id);
+        let node = &mut self.nodes[id.0];
+        if node.dirty {
+            node.dirty = false;
+            match &node.content {
+                Content::Container(_) => pixel_writer.fill_rect(node.rect, BACKGROUND),
+                Content::Widget(widget) => widget.draw(pixel_writer, node.rect, &self.font, focused),
+            }
+        }
+        for child in self.nodes[id.0].children.clone() {
+            self.draw_node(pixel_writer, child);
+        }
+    }
+
+    /// The innermost widget at `pos`.
+    fn widget_at(&self, id: WidgetId, pos: XY<isize>) -> Option<WidgetId> {
+        let node = &self.nodes[id.0];
+        if !node.rect.is_contained(pos) {
+            return None;
+        }
+        match node.content {
+            Content::Widget(_) => Some(id),
+            Content::Container(_) => node.children.iter().find_map(|&child| self.widget_at(child, pos)),
+        }
+    }
+
+    /// Passes a mouse event to the widget under the pointer, or to the one
+    /// pressed until the button is released, and focuses widgets clicked on.
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> Option<(WidgetId, Action)> {
+        self.update_layout();
+        let pressed = event.buttons.left && !self.buttons.left;
+        let released = !event.buttons.left && self.buttons.left;
+        self.buttons = event.buttons;
+        let hit = self.widget_at(self.root(), event.pos);
+        if pressed {
+            self.capture = hit;
+            if let Some(id) = hit.filter(|&id| self.is_focusable(id)) {
+                self.set_focus(Some(id));
+            }
+        }
+        let target = self.capture.or(hit);
+        if released {
+            self.capture = None;
+        }
+        let target = target?;
+        let rect = self.nodes[target.0].rect;
+        let event = MouseEvent::new(XY::new(event.pos.x - rect.x, event.pos.y - rect.y), event.buttons);
+        self.dispatch(target, |widget, font| widget.handle_mouse(event, XY::new(rect.w, rect.h), font))
+    }
+
+    /// Moves the focus with Tab and Shift+Tab, and passes other keys to the focused widget.
+    pub fn handle_key(&mut self, event: KeyEvent) -> Option<(WidgetId, Action)> {
+        if event.key == Key::Tab {
+            self.move_focus(event.modifiers.shift);
+            return None;
+        }
+        self.update_layout();
+        let target = self.focus?;
+        let rect = self.nodes[target.0].rect;
+        self.dispatch(target, |widget, font| widget.handle_key(event, XY::new(rect.w, rect.h), font))
+    }
+
+    fn dispatch(&mut self, id: WidgetId, f: impl FnOnce(&mut dyn Widget, &Font) -> Response) -> Option<(WidgetId, Action)> {
+        let node = &mut self.nodes[id.0];
+        let Content::Widget(widget) = &mut node.content else { return None };
+        let response = f(widget.as_mut(), &self.font);
+        node.dirty |= response.redraw;
+        response.action.map(|action| (id, action))
+    }
+
+    fn is_focusable(&self, id: WidgetId) -> bool {
+        matches!(&self.nodes[id.0].content, Content::Widget(widget) if widget.focusable())
+    }
+
+    /// Focusable widgets in the order they are laid out.
+    fn focus_order(&self, id: WidgetId, order: &mut Vec<WidgetId>) {
+        if self.is_focusable(id) {
+            order.push(id);
+        }
+        for &child in &self.nodes[id.0].children {
+            self.focus_order(child, order);
+        }
+    }
+
+    fn move_focus(&mut self, backwards: bool) {
+        let mut order = Vec::new();
+        self.focus_order(self.root(), &mut order);
+        if order.is_empty() {
+            return;
+        }
+        let current = self.focus.and_then(|focus| order.iter().position(|&id| id == focus));
+        let next = match (current, backwards) {
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+            (Some(i), false) => (i + 1) % order.len(),
+            (Some(i), true) => (i + order.len() - 1) % order.len(),
+        };
+        self.set_focus(Some(order[next]));
+    }
+}
+
+/// Draws `text` starting at `pos` on a background of `bg`, leaving out the
+/// characters that do not fit completely into `clip`.
+pub fn draw_text(pixel_writer: &mut dyn PixelWriter, font: &Font, pos: XY<isize>, text: &str,
+    fg: PixelColor, bg: PixelColor, clip: Rect)
+{
+    let mut x = pos.x;
+    for c in text.chars() {
+        let width = font::char_width(c) * font.char_size().x;
+        let cell = Rect::new(x, pos.y, width, font.glyph_size().y);
+        if cell.intersection(clip) == Some(cell) {
+            font.draw_char(pixel_writer, XY::new(x as usize, pos.y as usize), fg, bg, c);
+        }
+        x += width as isize;
+    }
+}
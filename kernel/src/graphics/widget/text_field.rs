@@ -0,0 +1,161 @@
+//! A single line of editable text.
+
+use alloc::string::String;
+
+use super::{draw_text, Action, Response, Widget, BORDER, FIELD, FOCUS, TEXT};
+use crate::{
+    graphics::{common::XY, font::{self, Font}, frame_buffer::PixelWriter, rect::Rect},
+    input::{Key, KeyEvent, MouseEvent},
+};
+
+/// Space between the text and the frame.
+const PADDING: usize = 3;
+
+pub struct TextField {
+    text: String,
+    /// Position of the caret in characters.
+    caret: usize,
+    /// Characters shown at the preferred width.
+    columns: usize,
+}
+
+impl TextField {
+    /// An empty field wide enough for `columns` half-width characters.
+    pub fn new(columns: usize) -> Self {
+        Self { text: String::new(), caret: 0, columns }
+    }
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Replaces the text, putting the caret after it.
+    pub fn set_text(&mut self, text: &str) {
+        self.text.clear();
+        self.text.push_str(text);
+        self.caret = self.len();
+    }
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+    fn byte_index(&self, caret: usize) -> usize {
+        self.text.char_indices().nth(caret).map_or(self.text.len(), |(i, _)| i)
+    }
+    fn char_width(font: &Font, c: char) -> usize {
+        font::char_width(c) * font.char_size().x
+    }
+
+    /// The first character shown when the field is `width` pixels wide, so
+    /// that the caret stays in view.
+    fn scroll(&self, font: &Font, width: usize) -> usize {
+        let width = width.saturating_sub(2 * PADDING);
+        // room for the caret after the last character
+        let mut used = font.char_size().x;
+        let mut first = self.caret;
+        for c in self.text[..self.byte_index(self.caret)].chars().rev() {
+            used += Self::char_width(font, c);
+            if used > width {
+                break;
+            }
+            first -= 1;
+        }
+        first
+    }
+
+    fn insert(&mut self, c: char) {
+        let i = self.byte_index(self.caret);
+        self.text.insert(i, c);
+        self.caret += 1;
+    }
+    fn remove(&mut self, caret: usize) {
+        let i = self.byte_index(caret);
+        self.text.remove(i);
+    }
+}
+
+impl Widget for TextField {
+    fn preferred_size(&self, font: &Font) -> XY<usize> {
+        XY::new(self.columns * font.char_size().x + 2 * PADDING, font.glyph_size().y + 2 * PADDING)
+    }
+
+    fn draw(&self, pixel_writer: &mut dyn PixelWriter, rect: Rect, font: &Font, focused: bool) {
+        pixel_writer.fill_rect(rect, FIELD);
+        pixel_writer.draw_rect(rect, if focused { FOCUS } else { BORDER });
+        let first = self.scroll(font, rect.w);
+        let text_start = self.byte_index(first);
+        let pos = XY::new(rect.x + PADDING as isize, rect.y + (rect.h.saturating_sub(font.glyph_size().y) / 2) as isize);
+        let inner = Rect::new(rect.x + 1, rect.y + 1, rect.w.saturating_sub(2), rect.h.saturating_sub(2));
+        draw_text(pixel_writer, font, pos, &self.text[text_start..], TEXT, FIELD, inner);
+        if focused {
+            let x = pos.x + self.text.chars().skip(first).take(self.caret - first)
+                .map(|c| Self::char_width(font, c) as isize).sum::<isize>();
+            if let Some(caret) = Rect::new(x, pos.y, 1, font.glyph_size().y).intersection(inner) {
+                pixel_writer.fill_rect(caret, TEXT);
+            }
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    /// Pressing puts the caret at the character boundary nearest to the pointer.
+    fn handle_mouse(&mut self, event: MouseEvent, size: XY<usize>, font: &Font) -> Response {
+        if !event.buttons.left {
+            return Response::IGNORED;
+        }
+        let mut x = PADDING as isize;
+        let mut caret = self.scroll(font, size.x);
+        for c in self.text.chars().skip(caret) {
+            let width = Self::char_width(font, c) as isize;
+            if event.pos.x < x + width / 2 {
+                break;
+            }
+            x += width;
+            caret += 1;
+        }
+        if caret == self.caret {
+            return Response::IGNORED;
+        }
+        self.caret = caret;
+        Response::REDRAW
+    }
+
+    fn handle_key(&mut self, event: KeyEvent, _size: XY<usize>, _font: &Font) -> Response {
+        match event.key {
+            Key::Char(c) if !c.is_control() && !event.modifiers.ctrl && !event.modifiers.alt => {
+                self.insert(c);
+                Response::action(Action::Changed)
+            }
+            Key::Backspace if self.caret > 0 => {
+                self.caret -= 1;
+                self.remove(self.caret);
+                Response::action(Action::Changed)
+            }
+            Key::Delete if self.caret < self.len() => {
+                self.remove(self.caret);
+                Response::action(Action::Changed)
+            }
+            Key::Left if self.caret > 0 => {
+                self.caret -= 1;
+                Response::REDRAW
+            }
+            Key::Right if self.caret < self.len() => {
+                self.caret += 1;
+                Response::REDRAW
+            }
+            Key::Home => {
+                self.caret = 0;
+                Response::REDRAW
+            }
+            Key::End => {
+                self.caret = self.len();
+                Response::REDRAW
+            }
+            Key::Enter => Response::action(Action::Submitted),
+            _ => Response::IGNORED,
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Overlapping windows composited onto the screen.
+//!
+//! Each window draws into its own surface in RAM. The window manager keeps
+//! the windows in z-order, decorates them with a border and a title bar, and
+//! repaints only the damaged areas of the screen, i.e. those where a surface
+//! changed or a window moved, from the bottom layer up.
+
+use alloc::{string::String, vec::Vec};
+use core::mem;
+
+use spin::{Mutex, MutexGuard, Once};
+
+use super::{
+    back_buffer::BackBuffer,
+    clip::ClippedWriter,
+    common::{PixelColor, XY},
+    console,
+    cursor,
+    font::{self, Font},
+    frame_buffer::{self, PixelWriter},
+    rect::Rect,
+};
+use crate::input::{MouseButtons, MouseEvent};
+
+pub const BORDER_WIDTH: usize = 1;
+pub const TITLE_BAR_HEIGHT: usize = 20;
+const DESKTOP_COLOR: PixelColor = PixelColor { r: 0, g: 82, b: 100 };
+const BORDER_COLOR: PixelColor = PixelColor { r: 24, g: 24, b: 32 };
+const TITLE_COLOR: PixelColor = PixelColor { r: 255, g: 255, b: 255 };
+const FOCUSED_TITLE_BAR_COLOR: PixelColor = PixelColor { r: 48, g: 88, b: 168 };
+const TITLE_BAR_COLOR: PixelColor = PixelColor { r: 96, g: 96, b: 108 };
+/// Left margin of the title within the title bar.
+const TITLE_INDENT: usize = 6;
+
+// spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
+// Lock order: after console::CONSOLES and frame_buffer::BACK_BUFFER (see
+// `compose`); never lock either of them while holding it
+pub static WINDOW_MANAGER: Once<Mutex<WindowManager<'static>>> = Once::new();
+/// The window showing the virtual consoles.
+static CONSOLE_WINDOW: Once<WindowId> = Once::new();
+
+/// Starts the window manager and moves the consoles into a window.
+pub fn init() {
+    let mut size = XY::new(0, 0);
+    frame_buffer::lock_pixel_writer(|w| size = w.size());
+    let mut window_manager = WindowManager::new(size, Font::new(&console::SHINONOME_FONT));
+    let pos = XY::new((size.x / 8) as isize, (size.y / 8) as isize);
+    let id = window_manager.create_window("Console", pos, XY::new(size.x * 3 / 4, size.y * 3 / 4));
+    WINDOW_MANAGER.call_once(|| Mutex::new(window_manager));
+    CONSOLE_WINDOW.call_once(|| id);
+    // lays out the consoles for the window and draws them into it
+    console::resize();
+    cursor::init(XY::new((size.x / 2) as isize, (size.y / 2) as isize));
+    frame_buffer::lock_pixel_writer(|mut w| cursor::lock_cursor(|mut cursor| cursor.show(&mut *w)));
+}
+
+pub fn lock_window_manager<F: FnMut(MutexGuard<WindowManager<'static>>)>(mut f: F) {
+    let window_manager = WINDOW_MANAGER.get()
+        .expect("window_manager::lock_window_manager is called before window_manager::init");
+    f(window_manager.lock())
+}
+
+/// Brings the screen up to date with the windows, keeping the cursor on top.
+pub fn compose() {
+    frame_buffer::lock_pixel_writer(|mut w| lock_window_manager(|mut wm| match cursor::CURSOR.get() {
+        Some(cursor) => cursor.lock().hidden(&mut *w, |w| wm.compose(w)),
+        None => wm.compose(&mut *w),
+    }));
+}
+
+/// Runs `f` on the surface of window `id` and shows the result, returning
+/// `false` without calling `f` if the window manager is not running.
+pub fn draw_window(id: WindowId, f: &mut dyn FnMut(&mut dyn PixelWriter)) -> bool {
+    let Some(window_manager) = WINDOW_MANAGER.get() else { return false };
+    if let Some(window) = window_manager.lock().window_mut(id) {
+        f(window.surface_mut());
+    }
+    compose();
+    true
+}
+
+/// Like `draw_window` for the window showing the consoles.
+pub fn draw_console(f: &mut dyn FnMut(&mut dyn PixelWriter)) -> bool {
+    console_window().is_some_and(|id| draw_window(id, f))
+}
+
+pub fn console_window() -> Option<WindowId> {
+    CONSOLE_WINDOW.get().copied()
+}
+
+/// Passes a mouse event to the window manager, returning the window and
+/// event for a click or movement over the client area of a window. The
+/// cursor follows the event, kept on the screen.
+pub fn handle_mouse(event: MouseEvent) -> Option<(WindowId, MouseEvent)> {
+    let mut pos = event.pos;
+    frame_buffer::lock_pixel_writer(|mut w| cursor::lock_cursor(|mut cursor| pos = cursor.move_to(&mut *w, event.pos)));
+    let event = MouseEvent::new(pos, event.buttons);
+    let mut result = None;
+    lock_window_manager(|mut wm| result = wm.handle_mouse(event));
+    compose();
+    result
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(usize);
+
+pub struct Window {
+    id: WindowId,
+    title: String,
+    /// Top left corner of the frame, i.e. the border.
+    pos: XY<isize>,
+    /// The client area, which the window's owner draws into.
+    surface: BackBuffer,
+}
+
+impl Window {
+    pub fn id(&self) -> WindowId {
+        self.id
+    }
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+    /// The whole window on the screen, decorations included.
+    pub fn frame(&self) -> Rect {
+        let size = self.surface.size();
+        Rect::new(self.pos.x, self.pos.y, size.x + 2 * BORDER_WIDTH, size.y + TITLE_BAR_HEIGHT + 2 * BORDER_WIDTH)
+    }
+    pub fn title_bar(&self) -> Rect {
+        let frame = self.frame();
+        Rect::new(frame.x + BORDER_WIDTH as isize, frame.y + BORDER_WIDTH as isize,
+            frame.w - 2 * BORDER_WIDTH, TITLE_BAR_HEIGHT)
+    }
+    /// The area of the surface on the screen.
+    pub fn client_area(&self) -> Rect {
+        let size = self.surface.size();
+        let origin = XY::new(BORDER_WIDTH as isize, (BORDER_WIDTH + TITLE_BAR_HEIGHT) as isize);
+        Rect::new(self.pos.x, self.pos.y, size.x, size.y).translate(origin)
+    }
+    pub fn surface(&self) -> &BackBuffer {
+        &self.surface
+    }
+    /// The client area to draw into, in coordinates relative to its top left
+    /// corner. What is drawn shows up on the screen with the next `compose`.
+    pub fn surface_mut(&mut self) -> &mut BackBuffer {
+        &mut self.surface
+    }
+}
+
+/// A window being moved with the mouse.
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    id: WindowId,
+    /// Where the window was grabbed, relative to its top left corner.
+    grab: XY<isize>,
+}
+
+pub struct WindowManager<'a> {
+    size: XY<usize>,
+    font: Font<'a>,
+    /// Bottom to top.
+    windows: Vec<Window>,
+    focused: Option<WindowId>,
+    /// Areas of the screen to repaint, in no particular order and possibly overlapping.
+    damage: Vec<Rect>,
+    drag: Option<Drag>,
+    buttons: MouseButtons,
+    next_id: usize,
+}
+
+impl<'a> WindowManager<'a> {
+    /// A window manager for a screen of `size`, titling windows in `font`.
+    pub fn new(size: XY<usize>, font: Font<'a>) -> Self {
+        Self {
+            size,
+            font,
+            windows: Vec::new(),
+            focused: None,
+            damage: Vec::from([Rect::from_size(size)]),
+            drag: None,
+            buttons: MouseButtons::NONE,
+            next_id: 0,
+        }
+    }
+
+    /// Size of the screen.
+    pub fn size(&self) -> XY<usize> {
+        self.size
+    }
+    /// The largest client area whose frame, decorations included, fits on the screen.
+    pub fn max_client_size(&self) -> XY<usize> {
+        XY::new(self.size.x.saturating_sub(2 * BORDER_WIDTH),
+            self.size.y.saturating_sub(TITLE_BAR_HEIGHT + 2 * BORDER_WIDTH))
+    }
+    /// Opens a window with a client area of `size` whose frame is at `pos`,
+    /// on top of the others and focused.
+    pub fn create_window(&mut self, title: &str, pos: XY<isize>, size: XY<usize>) -> WindowId {
+        let id = WindowId(self.next_id);
+        self.next_id += 1;
+        let window = Window { id, title: title.into(), pos, surface: BackBuffer::new(size, DESKTOP_COLOR) };
+        self.damage.push(window.frame());
+        self.windows.push(window);
+        self.focus(id);
+        id
+    }
+
+    pub fn close_window(&mut self, id: WindowId) {
+        let Some(index) = self.index(id) else { return };
+        let window = self.windows.remove(index);
+        self.damage.push(window.frame());
+        if self.focused == Some(id) {
+            self.focused = None;
+            if let Some(top) = self.windows.last().map(|window| window.id) {
+                self.focus(top);
+            }
+        }
+        if self.drag.is_some_and(|drag| drag.id == id) {
+            self.drag = None;
+        }
+    }
+
+    fn index(&self, id: WindowId) -> Option<usize> {
+        self.windows.iter().position(|window| window.id == id)
+    }
+
+    pub fn window(&self, id: WindowId) -> Option<&Window> {
+        self.windows.iter().find(|window| window.id == id)
+    }
+
+    pub fn window_mut(&mut self, id: WindowId) -> Option<&mut Window> {
+        self.windows.iter_mut().find(|window| window.id == id)
+    }
+
+    /// Window ids from the bottom to the top.
+    pub fn z_order(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.windows.iter().map(|window| window.id)
+    }
+
+    pub fn focused(&self) -> Option<WindowId> {
+        self.focused
+    }
+
+    /// Raises `id` to the top and gives it the focus.
+    pub fn focus(&mut self, id: WindowId) {
+        let Some(index) = self.index(id) else { return };
+        let window = self.windows.remove(index);
+        if index != self.windows.len() {
+            self.damage.push(window.frame());
+        }
+        self.windows.push(window);
+        if self.focused != Some(id) {
+            // both title bars change color
+            let previous = self.focused.and_then(|previous| self.window(previous)).map(Window::title_bar);
+            self.damage.extend(previous);
+            self.damage.push(self.windows.last().unwrap().title_bar());
+            self.focused = Some(id);
+        }
+    }
+
+    /// Moves the frame of `id` to `pos`.
+    pub fn move_window(&mut self, id: WindowId, pos: XY<isize>) {
+        let Some(window) = self.window_mut(id) else { return };
+        let old = window.frame();
+        window.pos = pos;
+        let new = window.frame();
+        self.damage.extend([old, new]);
+    }
+
+    /// Gives `id` a client area of `size`, blank until its owner draws it again.
+    pub fn resize_window(&mut self, id: WindowId, size: XY<usize>) {
+        let Some(window) = self.window_mut(id) else { return };
+        let old = window.frame();
+        window.surface = BackBuffer::new(size, DESKTOP_COLOR);
+        let new = window.frame();
+        self.damage.extend([old, new]);
+    }
+
+    /// The topmost window under `pos`.
+    pub fn window_at(&self, pos: XY<isize>) -> Option<WindowId> {
+        self.windows.iter().rev().find(|window| window.frame().is_contained(pos)).map(|window| window.id)
+    }
+
+    /// Marks an area of the screen to be repainted.
+    pub fn damage(&mut self, rect: Rect) {
+        self.damage.push(rect);
+    }
+
+    /// Focuses windows clicked on and moves windows dragged by their title bar.
+    ///
+    /// Events over the client area of a window that the window manager does
+    /// not use are returned with the position relative to the client area.
+    pub fn handle_mouse(&mut self, event: MouseEvent) -> Option<(WindowId, MouseEvent)> {
+        let pressed = event.buttons.left && !self.buttons.left;
+        self.buttons = event.buttons;
+        if let Some(drag) = self.drag {
+            if event.buttons.left {
+                self.move_window(drag.id, XY::new(event.pos.x - drag.grab.x, event.pos.y - drag.grab.y));
+                return None;
+            }
+            self.drag = None;
+        }
+        let window = self.window(self.window_at(event.pos)?)?;
+        let (id, pos) = (window.id, window.pos);
+        let in_title_bar = window.title_bar().is_contained(event.pos);
+        let client = window.client_area();
+        if pressed {
+            self.focus(id);
+            if in_title_bar {
+                self.drag = Some(Drag { id, grab: XY::new(event.pos.x - pos.x, event.pos.y - pos.y) });
+                return None;
+            }
+        }
+        client.is_contained(event.pos)
+            .then(|| (id, MouseEvent::new(XY::new(event.pos.x - client.x, event.pos.y - client.y), event.buttons)))
+    }
+
+    /// Repaints the damaged areas of the screen into `pixel_writer`, which
+    /// usually is the back buffer, including what windows drew since the last call.
+    pub fn compose(&mut self, pixel_writer: &mut dyn PixelWriter) {
+        for window in &mut self.windows {
+            let origin = window.client_area();
+            for rect in window.surface.take_dirty() {
+                self.damage.push(rect.translate(XY::new(origin.x, origin.y)));
+            }
+        }
+        let damage = merge(mem::take(&mut self.damage), self.size);
+        for rect in damage {
+            let mut clipped = ClippedWriter::new(pixel_writer, rect);
+            clipped.fill_rect(rect, DESKTOP_COLOR);
+            for window in &self.windows {
+                if window.frame().intersection(rect).is_some() {
+                    self.draw_window(&mut clipped, window, rect);
+                }
+            }
+        }
+    }
+
+    fn draw_window(&self, pixel_writer: &mut dyn PixelWriter, window: &Window, rect: Rect) {
+        let focused = self.focused == Some(window.id);
+        pixel_writer.draw_rect(window.frame(), BORDER_COLOR);
+        let title_bar = window.title_bar();
+        let bar_color = if focused { FOCUSED_TITLE_BAR_COLOR } else { TITLE_BAR_COLOR };
+        pixel_writer.fill_rect(title_bar, bar_color);
+        self.draw_title(pixel_writer, window, bar_color);
+
+        let client = window.client_area();
+        let Some(visible) = client.intersection(rect) else { return };
+        let width = window.surface.size().x;
+        let pixels = window.surface.canvas().pixels();
+        for y in visible.y..visible.bottom() {
+            let start = (y - client.y) as usize * width + (visible.x - client.x) as usize;
+            pixel_writer.write_row(XY::new(visible.x as usize, y as usize), &pixels[start..start + visible.w]);
+        }
+    }
+
+    fn draw_title(&self, pixel_writer: &mut dyn PixelWriter, window: &Window, bar_color: PixelColor) {
+        let title_bar = window.title_bar();
+        let char_width = self.font.char_size().x;
+        let glyph_height = self.font.glyph_size().y as isize;
+        let y = title_bar.y + (TITLE_BAR_HEIGHT as isize - glyph_height) / 2;
+        let mut x = title_bar.x + TITLE_INDENT as isize;
+        for c in window.title.chars() {
+            let width = char_width * font::char_width(c);
+            if x + width as isize > title_bar.right() - TITLE_INDENT as isize {
+                break;
+            }
+            if x >= 0 && y >= 0 {
+                self.font.draw_char(pixel_writer, XY::new(x as usize, y as usize), TITLE_COLOR, bar_color, c);
+            }
+            x += width as isize;
+        }
+    }
+}
+
+/// Clips `damage` to the screen and merges overlapping areas, so that no
+/// pixel is repainted twice.
+fn merge(damage: Vec<Rect>, size: XY<usize>) -> Vec<Rect> {
+    let mut merged: Vec<Rect> = Vec::new();
+    for rect in damage.into_iter().filter_map(|rect| rect.clip(size)) {
+        let mut rect = rect;
+        while let Some(i) = merged.iter().position(|other| other.intersection(rect).is_some()) {
+            rect = rect.union(merged.swap_remove(i));
+        }
+        merged.push(rect);
+    }
+    merged
+}
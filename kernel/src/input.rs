@@ -0,0 +1,79 @@
+//! Keyboard and mouse input as handed to its consumers, independent of the device it comes from.
+
+use crate::graphics::common::XY;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Key {
+    /// A key producing text, already translated by the keyboard layout.
+    Char(char),
+    Enter,
+    Backspace,
+    Tab,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    /// A function key, `F(1)` through `F(12)`.
+    F(u8),
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers { shift: false, ctrl: false, alt: false };
+    pub const SHIFT: Modifiers = Modifiers { shift: true, ..Modifiers::NONE };
+    pub const CTRL: Modifiers = Modifiers { ctrl: true, ..Modifiers::NONE };
+    pub const ALT: Modifiers = Modifiers { alt: true, ..Modifiers::NONE };
+}
+
+/// A key press together with the modifiers held at the time.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct KeyEvent {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    pub const fn new(key: Key, modifiers: Modifiers) -> Self {
+        KeyEvent { key, modifiers }
+    }
+}
+
+/// Mouse buttons held down.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct MouseButtons {
+    pub left: bool,
+    pub right: bool,
+    pub middle: bool,
+}
+
+impl MouseButtons {
+    pub const NONE: MouseButtons = MouseButtons { left: false, right: false, middle: false };
+    pub const LEFT: MouseButtons = MouseButtons { left: true, ..MouseButtons::NONE };
+}
+
+/// The state of the mouse after it moved or a button changed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MouseEvent {
+    /// Position of the pointer in pixels, relative to whatever receives the event.
+    pub pos: XY<isize>,
+    pub buttons: MouseButtons,
+}
+
+impl MouseEvent {
+    pub const fn new(pos: XY<isize>, buttons: MouseButtons) -> Self {
+        MouseEvent { pos, buttons }
+    }
+}
@@ -0,0 +1,84 @@
+//! The interrupt descriptor table and the legacy PICs, set up as far as the
+//! timer needs.
+//!
+//! The PICs are remapped past the CPU exceptions, to vectors `PIC_OFFSET..`,
+//! and every line except the timer's is masked.
+
+use spin::Once;
+use x86_64::instructions::{self, port::Port};
+use x86_64::structures::idt::{InterruptDescriptorTable, InterruptStackFrame};
+
+use crate::timer;
+
+/// Vector of IRQ 0; the secondary PIC's IRQs 8..16 follow at `PIC_OFFSET + 8`.
+const PIC_OFFSET: u8 = 32;
+const TIMER_VECTOR: u8 = PIC_OFFSET;
+/// IRQ 7 is raised for an interrupt that vanished before being acknowledged;
+/// with all of its lines masked, the secondary PIC raises none.
+const SPURIOUS_VECTOR: u8 = PIC_OFFSET + 7;
+
+const PIC1_COMMAND: u16 = 0x20;
+const PIC1_DATA: u16 = 0x21;
+const PIC2_COMMAND: u16 = 0xa0;
+const PIC2_DATA: u16 = 0xa1;
+/// ICW1: initialization, with an ICW4 to follow.
+const ICW1_INIT: u8 = 0x11;
+/// ICW4: 8086 mode.
+const ICW4_8086: u8 = 0x01;
+const END_OF_INTERRUPT: u8 = 0x20;
+
+// spin::Once for lazy init; the table must live as long as it is loaded
+static IDT: Once<InterruptDescriptorTable> = Once::new();
+
+/// Loads the IDT, starts the timer and enables interrupts.
+pub fn init() {
+    let idt = IDT.call_once(|| {
+        let mut idt = InterruptDescriptorTable::new();
+        idt[TIMER_VECTOR as usize].set_handler_fn(timer_interrupt);
+        idt[SPURIOUS_VECTOR as usize].set_handler_fn(spurious_interrupt);
+        idt
+    });
+    idt.load();
+    unsafe { init_pics() };
+    timer::init();
+    instructions::interrupts::enable();
+}
+
+/// Remaps both PICs to `PIC_OFFSET` and masks all IRQs but the timer's.
+unsafe fn init_pics() {
+    let mut pic1_command = Port::<u8>::new(PIC1_COMMAND);
+    let mut pic1_data = Port::<u8>::new(PIC1_DATA);
+    let mut pic2_command = Port::<u8>::new(PIC2_COMMAND);
+    let mut pic2_data = Port::<u8>::new(PIC2_DATA);
+    // writing to the unused port 0x80 gives the PICs time to settle between commands
+    let mut wait = Port::<u8>::new(0x80);
+
+    pic1_command.write(ICW1_INIT);
+    wait.write(0);
+    pic2_command.write(ICW1_INIT);
+    wait.write(0);
+    pic1_data.write(PIC_OFFSET);
+    wait.write(0);
+    pic2_data.write(PIC_OFFSET + 8);
+    wait.write(0);
+    // the secondary PIC is cascaded on IRQ 2 of the primary one
+    pic1_data.write(1 << 2);
+    wait.write(0);
+    pic2_data.write(2);
+    wait.write(0);
+    pic1_data.write(ICW4_8086);
+    wait.write(0);
+    pic2_data.write(ICW4_8086);
+    wait.write(0);
+
+    pic1_data.write(!1);
+    pic2_data.write(!0);
+}
+
+extern "x86-interrupt" fn timer_interrupt(_frame: InterruptStackFrame) {
+    timer::tick();
+    unsafe { Port::new(PIC1_COMMAND).write(END_OF_INTERRUPT) };
+}
+
+/// Ignores a spurious IRQ, which must not be acknowledged.
+extern "x86-interrupt" fn spurious_interrupt(_frame: InterruptStackFrame) {}
@@ -0,0 +1,108 @@
+#![no_std]
+#![cfg_attr(test, no_main)]
+#![feature(type_alias_impl_trait)]
+#![feature(abi_x86_interrupt)]
+#![feature(custom_test_frameworks)]
+#![test_runner(crate::test_runner)]
+#![reexport_test_harness_main = "test_main"]
+
+// subset of the standard library that additionally contains the allocation and collection types
+// the alloc crate ships with the Rust compiler as part of the standard library, so the compiler already knows about the crate.
+// By adding this extern crate statement, we specify that the compiler should try to include it.
+extern crate alloc;
+
+pub mod apps;
+pub mod clipboard;
+pub mod graphics;
+pub mod input;
+pub mod interrupts;
+pub mod memory;
+pub mod qemu;
+pub mod serial;
+pub mod timer;
+
+use core::{any, arch::asm, mem, panic::PanicInfo};
+
+use bootloader::{boot_info::Optional, BootInfo};
+use graphics::{console, frame_buffer};
+use memory::{frame_alloc::BootInfoFrameAllocator, global_alloc, paging};
+use qemu::{exit_qemu, QemuExitCode};
+use x86_64::VirtAddr;
+
+/// Sets up the heap, the framebuffer and the console, which the rest of the kernel relies on.
+pub fn init(boot_info: &'static mut BootInfo) {
+    init_frame_buffer(boot_info);
+    console::init();
+}
+
+/// Sets up the heap and the framebuffer, which is all drawing needs before the console exists.
+pub fn init_frame_buffer(boot_info: &'static mut BootInfo) {
+    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset.into_option().unwrap());
+    let mut mapper = unsafe { paging::init(phys_mem_offset) };
+    let mut frame_allocator = unsafe { BootInfoFrameAllocator::init(&boot_info.memory_regions) };
+    global_alloc::init_heap(&mut mapper, &mut frame_allocator)
+        .expect("heap initialization failed");
+
+    let frame_buffer = mem::replace(&mut boot_info.framebuffer, Optional::None)
+        .into_option().unwrap();
+    frame_buffer::init(frame_buffer);
+}
+
+/// Anything that can be run as a `#[test_case]`.
+///
+/// Implemented for all plain functions so that each test reports its own name
+/// on the serial port before and after running.
+pub trait Testable {
+    fn run(&self);
+}
+
+impl<T: Fn()> Testable for T {
+    fn run(&self) {
+        serial_print!("{}...\t", any::type_name::<T>());
+        self();
+        serial_println!("[ok]");
+    }
+}
+
+/// Runs all collected `#[test_case]`s and exits QEMU with a success code.
+///
+/// A failing test panics, so reaching the end of this function means every test passed.
+pub fn test_runner(tests: &[&dyn Testable]) {
+    serial_println!("Running {} tests", tests.len());
+    for test in tests {
+        test.run();
+    }
+    exit_qemu(QemuExitCode::Success);
+}
+
+/// Panic handler shared by the test kernels: reports the failure on serial and exits QEMU.
+pub fn test_panic_handler(info: &PanicInfo) -> ! {
+    serial_println!("[failed]\n");
+    serial_println!("Error: {}\n", info);
+    exit_qemu(QemuExitCode::Failed);
+    hlt_loop()
+}
+
+pub fn hlt_loop() -> ! {
+    loop {unsafe {asm!("hlt")}}
+}
+
+#[cfg(test)]
+use bootloader::entry_point;
+
+#[cfg(test)]
+entry_point!(test_kernel_main);
+
+/// Entry point for `cargo ktest` of the library itself.
+#[cfg(test)]
+fn test_kernel_main(boot_info: &'static mut BootInfo) -> ! {
+    init(boot_info);
+    test_main();
+    hlt_loop()
+}
+
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    test_panic_handler(info)
+}
@@ -1,37 +1,52 @@
 #![no_std]
 #![no_main]
-#![feature(type_alias_impl_trait)]
+#![feature(custom_test_frameworks)]
+#![test_runner(kernel::test_runner)]
+#![reexport_test_harness_main = "test_main"]
 
-// subset of the standard library that additionally contains the allocation and collection types
-// the alloc crate ships with the Rust compiler as part of the standard library, so the compiler already knows about the crate.
-// By adding this extern crate statement, we specify that the compiler should try to include it.
-// extern crate alloc;
+use bootloader::{entry_point, BootInfo};
+use core::arch::asm;
 
-mod graphics;
-mod memory;
-use bootloader::{entry_point, BootInfo, boot_info::Optional};
-use memory::memmap::{MemoryMap, MemoryType};
-use x86_64::VirtAddr;
-use core::{arch::asm, mem};
-
-use crate::{graphics::{frame_buffer, console}, memory::paging::active_level_4_table};
+use kernel::apps::{demo, terminal};
+use kernel::graphics::{console, splash, window_manager};
+use kernel::{interrupts, println, timer};
 
 // This macro just creates a function named _start, which the linker will use as the entry point.
 // The function must have the signature fn(&'static mut BootInfo) -> !.
 entry_point!(kernel_main);
 
 fn kernel_main(boot_info: &'static mut BootInfo) -> ! {
-    let frame_buffer = mem::replace(&mut boot_info.framebuffer, Optional::None)
-        .into_option().unwrap();
-    frame_buffer::init(frame_buffer);
+    kernel::init_frame_buffer(boot_info);
+    splash::show();
+    // the timer keeps the splash up before anything else draws
+    interrupts::init();
+    timer::sleep(splash::SPLASH_MS);
     console::init();
     println!("Hello, {}!", "AIOS");
-    let phys_mem_offset = VirtAddr::new(boot_info.physical_memory_offset.into_option().unwrap());
-    loop {unsafe {asm!("hlt")}}
+    window_manager::init();
+    demo::launch();
+    terminal::launch();
+    timer::every(console::CURSOR_BLINK_MS, console::blink_cursor);
+    timer::every(console::CURSOR_BLINK_MS, terminal::blink_cursor);
+
+    #[cfg(test)]
+    test_main();
+
+    loop {
+        timer::run_due();
+        unsafe {asm!("hlt")}
+    }
 }
 
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
     println!("{}", info);
     loop {unsafe {asm!("hlt")}}
 }
+
+#[cfg(test)]
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo<'_>) -> ! {
+    kernel::test_panic_handler(info)
+}
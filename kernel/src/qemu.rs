@@ -0,0 +1,35 @@
+use x86_64::instructions::port::Port;
+
+/// I/O port of the `isa-debug-exit` device, as configured by the boot runner.
+const ISA_DEBUG_EXIT_PORT: u16 = 0xf4;
+
+/// Exit codes written to the `isa-debug-exit` device.
+///
+/// QEMU exits with `(value << 1) | 1`, so the codes are chosen not to collide
+/// with QEMU's own exit statuses. `Success` becomes exit status 33 on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum QemuExitCode {
+    Success = 0x10,
+    Failed = 0x11,
+}
+
+/// Shuts QEMU down with the given exit code.
+///
+/// Does nothing if QEMU runs without the `isa-debug-exit` device.
+pub fn exit_qemu(exit_code: QemuExitCode) {
+    unsafe {
+        let mut port = Port::new(ISA_DEBUG_EXIT_PORT);
+        port.write(exit_code as u32);
+    }
+}
+
+/// Asks the boot runner to compare the screen against the golden image
+/// `tests/golden/<name>.ppm`, then halts until the runner shuts QEMU down.
+///
+/// The runner decides whether the test passed, so this is the last thing a
+/// screenshot test does.
+pub fn request_screenshot(name: &str) -> ! {
+    crate::serial_println!("[screenshot {}]", name);
+    crate::hlt_loop()
+}
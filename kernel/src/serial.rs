@@ -0,0 +1,37 @@
+use core::fmt::{self, Write};
+
+use spin::{Mutex, Once};
+use uart_16550::SerialPort;
+
+/// Base I/O port of the first serial interface (COM1).
+const COM1_PORT: u16 = 0x3f8;
+
+// spin::Once for lazy init, spin::Mutex for interior mutability with Sync on bare metal
+pub static SERIAL1: Once<Mutex<SerialPort>> = Once::new();
+
+fn serial1() -> &'static Mutex<SerialPort> {
+    SERIAL1.call_once(|| {
+        let mut serial_port = unsafe { SerialPort::new(COM1_PORT) };
+        serial_port.init();
+        Mutex::new(serial_port)
+    })
+}
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    serial1().lock().write_fmt(args).expect("printing to serial failed");
+}
+
+/// Prints to the host through the serial interface.
+#[macro_export]
+macro_rules! serial_print {
+    ($($arg:tt)*) => ($crate::serial::_print(format_args!($($arg)*)));
+}
+
+/// Prints to the host through the serial interface, appending a newline.
+#[macro_export]
+macro_rules! serial_println {
+    () => ($crate::serial_print!("\n"));
+    ($fmt:expr) => ($crate::serial_print!(concat!($fmt, "\n")));
+    ($fmt:expr, $($arg:tt)*) => ($crate::serial_print!(concat!($fmt, "\n"), $($arg)*));
+}
@@ -0,0 +1,82 @@
+//! The system timer: the PIT interrupting `TICKS_PER_SECOND` times a second,
+//! and callbacks run periodically.
+//!
+//! The interrupt only counts ticks. Callbacks run from the idle loop through
+//! `run_due`, so that they can take the locks the interrupted code may hold.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use spin::Mutex;
+use x86_64::instructions::{self, port::Port};
+
+pub const TICKS_PER_SECOND: u64 = 100;
+/// Input clock of the PIT in Hz.
+const PIT_FREQUENCY: u64 = 1_193_182;
+const PIT_CHANNEL0_PORT: u16 = 0x40;
+const PIT_COMMAND_PORT: u16 = 0x43;
+/// Channel 0, low byte then high byte of the divisor, square wave generator.
+const PIT_SQUARE_WAVE: u8 = 0x36;
+
+static TICKS: AtomicU64 = AtomicU64::new(0);
+static CALLBACKS: Mutex<Vec<Periodic>> = Mutex::new(Vec::new());
+
+struct Periodic {
+    interval: u64,
+    /// Tick at which the callback runs next.
+    due: u64,
+    callback: fn(),
+}
+
+/// Programs the PIT to interrupt `TICKS_PER_SECOND` times a second.
+pub(crate) fn init() {
+    let divisor = (PIT_FREQUENCY / TICKS_PER_SECOND) as u16;
+    unsafe {
+        Port::new(PIT_COMMAND_PORT).write(PIT_SQUARE_WAVE);
+        let mut channel0 = Port::new(PIT_CHANNEL0_PORT);
+        channel0.write(divisor as u8);
+        channel0.write((divisor >> 8) as u8);
+    }
+}
+
+/// Counts a timer interrupt.
+pub(crate) fn tick() {
+    TICKS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Ticks since `interrupts::init`.
+pub fn ticks() -> u64 {
+    TICKS.load(Ordering::Relaxed)
+}
+
+/// Waits `ms` milliseconds, rounded up to whole ticks, halting in between.
+/// Interrupts must be enabled, or the wait never ends.
+pub fn sleep(ms: u64) {
+    let until = ticks() + ms.div_ceil(1000 / TICKS_PER_SECOND);
+    while ticks() < until {
+        instructions::hlt();
+    }
+}
+
+/// Has `callback` run every `ms` milliseconds, rounded up to whole ticks.
+pub fn every(ms: u64, callback: fn()) {
+    let interval = ms.div_ceil(1000 / TICKS_PER_SECOND).max(1);
+    CALLBACKS.lock().push(Periodic { interval, due: ticks() + interval, callback });
+}
+
+/// Runs the callbacks that are due. Periods missed while busy are skipped
+/// rather than made up for.
+pub fn run_due() {
+    let now = ticks();
+    let due: Vec<fn()> = CALLBACKS.lock().iter_mut()
+        .filter(|periodic| periodic.due <= now)
+        .map(|periodic| {
+            periodic.due = now + periodic.interval;
+            periodic.callback
+        })
+        .collect();
+    // without the lock held, so that callbacks can add others
+    for callback in due {
+        callback();
+    }
+}
@@ -0,0 +1,29 @@
+//! Renders known text on the console and compares the screen against
+//! `tests/golden/console.ppm`. The boot runner fails if the golden image is
+//! missing; capture it from QEMU with `cargo ktest --test golden_console -- --bless`
+//! to create or update it after intended changes.
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+use bootloader::{entry_point, BootInfo};
+use kernel::{println, qemu};
+
+entry_point!(main);
+
+fn main(boot_info: &'static mut BootInfo) -> ! {
+    // no interrupts::init, so the timer never blinks the cursor away before the screenshot
+    kernel::init(boot_info);
+
+    println!("Hello, {}!", "AIOS");
+    println!("The quick brown fox jumps over the lazy dog.");
+    println!("0123456789 !\"#$%&'()*+,-./:;<=>?@[\\]^_`{{|}}~");
+
+    qemu::request_screenshot("console")
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    kernel::test_panic_handler(info)
+}
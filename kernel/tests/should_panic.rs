@@ -0,0 +1,30 @@
+//! Runs without the test harness: the single test must panic, and the panic
+//! handler reports success instead of failure.
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+use bootloader::{entry_point, BootInfo};
+use kernel::{qemu::{exit_qemu, QemuExitCode}, serial_print, serial_println};
+
+entry_point!(main);
+
+fn main(_boot_info: &'static mut BootInfo) -> ! {
+    should_fail();
+    serial_println!("[test did not panic]");
+    exit_qemu(QemuExitCode::Failed);
+    kernel::hlt_loop()
+}
+
+fn should_fail() {
+    serial_print!("should_panic::should_fail...\t");
+    assert_eq!(0, 1);
+}
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    serial_println!("[ok]");
+    exit_qemu(QemuExitCode::Success);
+    kernel::hlt_loop()
+}
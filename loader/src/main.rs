@@ -9,14 +9,18 @@ use core::ptr::{copy_nonoverlapping, write_bytes};
 mod elf;
 
 use uefi::prelude::*;
-use uefi::proto::console::gop::PixelFormat;
-use uefi::proto::{console,media,loaded_image};
-use uefi::proto::media::file::{Directory, File, FileMode, FileAttribute, FileInfo};
+use uefi::proto::console::gop::{GraphicsOutput, Mode, PixelFormat};
+use uefi::proto::{media,loaded_image};
+use uefi::proto::media::file::{Directory, File, FileMode, FileAttribute, FileInfo, RegularFile};
 use uefi::table::boot::{AllocateType,MemoryType};
 
 #[macro_use]
 extern crate alloc;
-use log::{info,debug};
+use log::{info,debug,warn};
+
+// Resolution chosen when "loader.conf" does not set one
+// The largest mode is taken instead if the firmware does not offer it
+const DEFAULT_RESOLUTION: (usize, usize) = (1280, 800);
 
 // Loose coupling between loader and kernel
 // Only entry point function type (e.g. args) is shared in the form of "spec"
@@ -52,20 +56,76 @@ fn open_root_dir(bs: &BootServices, image_handle: Handle) -> Directory {
 }
 
 //
-// Get frame buffer
+// Read the preferred resolution from "loader.conf", a line like "resolution = 1920x1080"
+//
+fn read_preferred_resolution(root_dir: &mut Directory) -> Option<(usize, usize)> {
+    let file = root_dir.open(cstr16!("loader.conf"), FileMode::Read, FileAttribute::READ_ONLY).ok()?;
+    let mut file: RegularFile = file.into_regular_file()?;
+    let mut buf = [0_u8; 512];
+    let len = file.read(&mut buf).ok()?;
+    let text = core::str::from_utf8(&buf[..len]).ok()?;
+
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else { continue };
+        if key.trim() != "resolution" { continue };
+        let parsed = value.trim().split_once('x')
+            .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+        if parsed.is_none() {
+            warn!("loader.conf: invalid resolution {:?}", value.trim());
+        }
+        return parsed;
+    }
+    None
+}
+
+//
+// Choose the mode with the preferred resolution, or else the largest one
+// Modes without a linear frame buffer (BltOnly) cannot be drawn to after ExitBootServices
+//
+fn choose_mode(gop: &GraphicsOutput, preferred: (usize, usize)) -> Option<Mode> {
+    let mut best: Option<Mode> = None;
+    for mode in gop.modes() {
+        let info = mode.info();
+        debug!("mode {}: {:?} {:?}", mode.index(), info.resolution(), info.pixel_format());
+        if info.pixel_format() == PixelFormat::BltOnly { continue };
+        if info.resolution() == preferred {
+            return Some(mode);
+        }
+        let area = |mode: &Mode| {
+            let (width, height) = mode.info().resolution();
+            width * height
+        };
+        if best.as_ref().map_or(true, |best| area(&mode) > area(best)) {
+            best = Some(mode);
+        }
+    }
+    best
+}
+
+//
+// Set the graphics mode and get its frame buffer
 //
-fn get_frame_buffer(bs: &BootServices) -> FrameBuffer {
+fn get_frame_buffer(bs: &BootServices, preferred: (usize, usize)) -> FrameBuffer {
     let gop_handle = bs
-        .get_handle_for_protocol::<console::gop::GraphicsOutput>().unwrap();
+        .get_handle_for_protocol::<GraphicsOutput>().unwrap();
     let mut gop = bs
-        .open_protocol_exclusive::<console::gop::GraphicsOutput>(gop_handle).unwrap();
-    
+        .open_protocol_exclusive::<GraphicsOutput>(gop_handle).unwrap();
+
+    match choose_mode(&gop, preferred) {
+        Some(mode) => {
+            info!("Setting mode {}: {:?}", mode.index(), mode.info().resolution());
+            gop.set_mode(&mode).expect("Failed to set graphics mode");
+        }
+        None => warn!("No mode with a frame buffer, keeping the current one"),
+    }
+
+    let mode_info = gop.current_mode_info();
     FrameBuffer {
         buffer: gop.frame_buffer().as_mut_ptr(),
         size: gop.frame_buffer().size(),
-        resolution: gop.current_mode_info().resolution(),
-        pixel_format: gop.current_mode_info().pixel_format(),
-        stride: gop.current_mode_info().stride(),
+        resolution: mode_info.resolution(),
+        pixel_format: mode_info.pixel_format(),
+        stride: mode_info.stride(),
     }
 }
 
@@ -136,9 +196,11 @@ pub fn efi_main(image_handle: Handle, mut system_table: SystemTable<Boot>) -> St
         });
     }
 
-    let frame_buffer = get_frame_buffer(bs);
-
     let mut root_dir = open_root_dir(bs, image_handle);
+
+    // The mode is set while boot services still run; the kernel gets the frame buffer as is
+    let resolution = read_preferred_resolution(&mut root_dir).unwrap_or(DEFAULT_RESOLUTION);
+    let frame_buffer = get_frame_buffer(bs, resolution);
     let mut kernel_file = root_dir.open(
         cstr16!("kernel.elf"),
         FileMode::Read,